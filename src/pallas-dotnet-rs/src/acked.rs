@@ -0,0 +1,237 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+};
+
+use pallas::network::miniprotocols::Point as PallasPoint;
+use rnet::{net, Net};
+use tokio::{
+    sync::{
+        mpsc::{self, error::TryRecvError},
+        Semaphore,
+    },
+    task::JoinHandle,
+};
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    options::{interruptible, ClientOptions},
+    runtime,
+    session::Session,
+    ClientWrapper, NextResponse, Point, ACTION_CANCELLED, ACTION_PENDING, ACTION_ROLL_BACK, ACTION_ROLL_FORWARD,
+};
+
+#[derive(Net)]
+pub struct AckedResponse {
+    pub(crate) action: u8,
+    pub(crate) tip: Option<Point>,
+    pub(crate) block_cbor: Option<Vec<u8>>,
+    // What to acknowledge once the response is processed, the block's point
+    // or the rollback point. None for other actions.
+    pub(crate) point: Option<Point>,
+}
+
+impl AckedResponse {
+    fn new(response: NextResponse, point: Option<PallasPoint>) -> AckedResponse {
+        AckedResponse {
+            action: response.action,
+            tip: response.tip,
+            block_cbor: response.block_cbor,
+            point: point.map(Point::from),
+        }
+    }
+}
+
+// Chain-sync driven by a task on the runtime like ChainSyncStream, except
+// the buffer is bounded by the responses not acknowledged yet rather than
+// by the ones not taken yet. The task stops pulling once `window` roll
+// forwards and rollbacks are waiting for an acknowledgement.
+pub struct AckedStream {
+    receiver: mpsc::UnboundedReceiver<AckedResponse>,
+    window: Arc<Semaphore>,
+    // Points of the responses buffered or delivered, oldest first
+    unacked: Arc<Mutex<VecDeque<PallasPoint>>>,
+    // Where chain-sync goes back to when the stream stops with unacknowledged
+    // responses, the session's position until the first acknowledgement
+    acked: PallasPoint,
+    cancel: CancellationToken,
+    task: JoinHandle<()>,
+}
+
+impl AckedStream {
+    pub fn start(connection: Arc<tokio::sync::Mutex<Session>>, options: ClientOptions, window: usize, start: PallasPoint) -> AckedStream {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let window = Arc::new(Semaphore::new(window.max(1)));
+        let unacked = Arc::new(Mutex::new(VecDeque::new()));
+        let cancel = CancellationToken::new();
+
+        let task = {
+            let window = window.clone();
+            let unacked = unacked.clone();
+            let cancel = cancel.clone();
+
+            runtime::spawn(async move {
+                loop {
+                    let permit = match interruptible(window.clone().acquire_owned(), None, &cancel).await {
+                        Ok(Ok(permit)) => permit,
+                        _ => break,
+                    };
+
+                    let (next_response, position) = {
+                        let mut connection = connection.lock().await;
+                        let next_response = connection.next_response(&options, &cancel).await;
+
+                        (next_response, connection.position().cloned())
+                    };
+
+                    if next_response.action == ACTION_CANCELLED {
+                        break;
+                    }
+
+                    let is_error = next_response.is_error();
+
+                    // Awaits and reconnections hand their permit back
+                    let point = match next_response.action {
+                        ACTION_ROLL_FORWARD | ACTION_ROLL_BACK => {
+                            let point = position.unwrap_or(PallasPoint::Origin);
+
+                            permit.forget();
+                            unacked.lock().unwrap().push_back(point.clone());
+
+                            Some(point)
+                        }
+                        _ => None,
+                    };
+
+                    if sender.send(AckedResponse::new(next_response, point)).is_err() || is_error {
+                        break;
+                    }
+                }
+            })
+        };
+
+        AckedStream {
+            receiver,
+            window,
+            unacked,
+            acked: start,
+            cancel,
+            task,
+        }
+    }
+
+    // `None` when no response is buffered yet. Once the task is gone an error
+    // response is returned so the consumer can reconnect.
+    pub fn try_next(&mut self) -> Option<AckedResponse> {
+        match self.receiver.try_recv() {
+            Ok(response) => Some(response),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(AckedResponse::new(NextResponse::error(), None)),
+        }
+    }
+
+    // Acknowledgements are cumulative: the oldest unacknowledged delivery of
+    // `point` and every response before it are done with
+    pub fn ack(&mut self, point: &PallasPoint) -> Result<(), String> {
+        let mut unacked = self.unacked.lock().unwrap();
+
+        match unacked.iter().position(|p| p == point) {
+            Some(i) => {
+                unacked.drain(..=i);
+                self.window.add_permits(i + 1);
+                self.acked = point.clone();
+
+                Ok(())
+            }
+            None => Err(format!("{:?} is not waiting for an acknowledgement", point)),
+        }
+    }
+
+    // The point chain-sync has to go back to so that the responses not
+    // acknowledged are delivered again, `None` when there are none
+    pub async fn stop(self) -> Option<PallasPoint> {
+        self.cancel.cancel();
+        let _ = self.task.await;
+
+        let unacked = self.unacked.lock().unwrap();
+        (!unacked.is_empty()).then(|| self.acked.clone())
+    }
+}
+
+impl ClientWrapper {
+    // Starts an acknowledged chain-sync stream: up to `window` roll forwards
+    // and rollbacks are prefetched, more only once earlier ones are
+    // acknowledged with ack_point, which also advances the cursor. Stopping
+    // the stream rewinds chain-sync to the last acknowledged point, so
+    // nothing is lost between delivery and processing.
+    #[net]
+    pub fn start_acked_stream(client_wrapper: ClientWrapper, window: u64) -> bool {
+        ClientWrapper::start_acked_stream(client_wrapper, window)
+    }
+
+    pub fn start_acked_stream(client_wrapper: ClientWrapper, window: u64) -> bool {
+        let state = client_wrapper.state();
+
+        if state.is_streaming() {
+            println!("start_acked_stream error: chain-sync stream or sink is running");
+            return false;
+        }
+
+        let start = match runtime::block_on(state.lock_connection(&state.cancel_token())) {
+            Ok(connection) => connection.position().cloned().unwrap_or(PallasPoint::Origin),
+            Err(e) => {
+                println!("start_acked_stream error: {:?}", e);
+                return false;
+            }
+        };
+        let stream = AckedStream::start(state.connection.clone(), state.options.clone(), window as usize, start);
+
+        *state.acked_stream.lock().unwrap() = Some(stream);
+
+        true
+    }
+
+    #[net]
+    pub fn acked_stream_try_next(client_wrapper: ClientWrapper) -> AckedResponse {
+        ClientWrapper::acked_stream_try_next(client_wrapper)
+    }
+
+    pub fn acked_stream_try_next(client_wrapper: ClientWrapper) -> AckedResponse {
+        match client_wrapper.state().acked_stream.lock().unwrap().as_mut() {
+            Some(stream) => stream.try_next().unwrap_or(AckedResponse {
+                action: ACTION_PENDING,
+                tip: None,
+                block_cbor: None,
+                point: None,
+            }),
+            None => {
+                println!("acked_stream_try_next error: acked stream not started");
+                AckedResponse::new(NextResponse::error(), None)
+            }
+        }
+    }
+
+    #[net]
+    pub fn stop_acked_stream(client_wrapper: ClientWrapper) {
+        ClientWrapper::stop_acked_stream(client_wrapper)
+    }
+
+    pub fn stop_acked_stream(client_wrapper: ClientWrapper) {
+        let state = client_wrapper.state();
+        let stream = state.acked_stream.lock().unwrap().take();
+
+        let Some(stream) = stream else {
+            return;
+        };
+
+        runtime::block_on(async {
+            let Some(rewind) = stream.stop().await else {
+                return;
+            };
+
+            if let Err(e) = state.connection.lock().await.rewind(vec![rewind], &state.options).await {
+                println!("stop_acked_stream error: cannot rewind to the last acknowledged point: {}", e);
+            }
+        })
+    }
+}
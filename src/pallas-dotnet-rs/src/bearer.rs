@@ -0,0 +1,111 @@
+use pallas::network::multiplexer::Bearer;
+use tokio::net::TcpListener;
+#[cfg(unix)]
+use tokio::net::UnixListener;
+
+use crate::options::{with_timeout, ClientOptions};
+
+// Values of the `client: u8` taken by the exports
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ClientKind {
+    N2C = 1,
+    N2N = 2,
+}
+
+impl TryFrom<u8> for ClientKind {
+    type Error = String;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(ClientKind::N2C),
+            2 => Ok(ClientKind::N2N),
+            _ => Err(format!("unknown client type {}", value)),
+        }
+    }
+}
+
+// Values of `ClientOptions::bearer`. 0 picks the usual transport of the
+// client kind: a unix socket for N2C and TCP for N2N.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum BearerKind {
+    Unix = 1,
+    // `host:port`, e.g. a node socket exposed through socat
+    Tcp = 2,
+    NamedPipe = 3,
+}
+
+impl BearerKind {
+    pub fn select(value: u8, client: ClientKind) -> Result<BearerKind, String> {
+        match (value, client) {
+            (0, ClientKind::N2C) => Ok(BearerKind::Unix),
+            (0, ClientKind::N2N) => Ok(BearerKind::Tcp),
+            (1, _) => Ok(BearerKind::Unix),
+            (2, _) => Ok(BearerKind::Tcp),
+            (3, _) => Ok(BearerKind::NamedPipe),
+            _ => Err(format!("unknown bearer type {}", value)),
+        }
+    }
+
+    async fn open(self, address: &str) -> Result<Bearer, String> {
+        let bearer = match self {
+            #[cfg(unix)]
+            BearerKind::Unix => Bearer::connect_unix(address).await,
+            BearerKind::Tcp => Bearer::connect_tcp(address).await,
+            #[cfg(windows)]
+            BearerKind::NamedPipe => Bearer::connect_named_pipe(address).await,
+            #[allow(unreachable_patterns)]
+            _ => return Err(format!("{:?} bearer is not supported on this platform", self)),
+        };
+
+        bearer.map_err(|e| format!("connect failed: {:?}", e))
+    }
+
+    pub async fn connect(self, address: &str, options: &ClientOptions) -> Result<Bearer, String> {
+        with_timeout(self.open(address), options.connect_timeout())
+            .await
+            .map_err(|_| "connect timed out".to_string())?
+    }
+
+    pub async fn listen(self, address: &str) -> Result<Listener, String> {
+        let listener = match self {
+            #[cfg(unix)]
+            BearerKind::Unix => UnixListener::bind(address).map(Listener::Unix),
+            BearerKind::Tcp => TcpListener::bind(address).await.map(Listener::Tcp),
+            #[allow(unreachable_patterns)]
+            _ => return Err(format!("{:?} bearer cannot accept connections", self)),
+        };
+
+        listener.map_err(|e| format!("listen failed: {:?}", e))
+    }
+}
+
+pub enum Listener {
+    #[cfg(unix)]
+    Unix(UnixListener),
+    Tcp(TcpListener),
+}
+
+impl Listener {
+    // Socket path or `host:port` the listener is bound to
+    pub fn address(&self) -> String {
+        let address = match self {
+            #[cfg(unix)]
+            Listener::Unix(listener) => listener
+                .local_addr()
+                .map(|address| address.as_pathname().map(|path| path.to_string_lossy().to_string()).unwrap_or_default()),
+            Listener::Tcp(listener) => listener.local_addr().map(|address| address.to_string()),
+        };
+
+        address.unwrap_or_default()
+    }
+
+    pub async fn accept(&self) -> Result<Bearer, String> {
+        let bearer = match self {
+            #[cfg(unix)]
+            Listener::Unix(listener) => Bearer::accept_unix(listener).await.map(|(bearer, _)| bearer),
+            Listener::Tcp(listener) => Bearer::accept_tcp(listener).await.map(|(bearer, _)| bearer),
+        };
+
+        bearer.map_err(|e| format!("accept failed: {:?}", e))
+    }
+}
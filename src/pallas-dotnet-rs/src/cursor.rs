@@ -0,0 +1,178 @@
+use std::{
+    collections::VecDeque,
+    fs::File,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use pallas::network::miniprotocols::Point as PallasPoint;
+use rnet::net;
+
+use crate::{
+    options::{with_timeout, ClientOptions},
+    session::Session,
+    ClientWrapper, Point,
+};
+
+// Points kept when ClientOptions.cursor_points is 0, as many as a session
+// offers when it reconnects
+const DEFAULT_CURSOR_POINTS: usize = 32;
+
+// The last points the consumer acknowledged, one `slot hash` line each,
+// newest first. Every update rewrites the file aside and renames it over the
+// previous one, so a crash leaves either version whole.
+pub struct CursorStore {
+    path: PathBuf,
+    capacity: usize,
+    // Oldest first
+    points: VecDeque<PallasPoint>,
+}
+
+impl CursorStore {
+    // A missing file is an empty cursor
+    pub fn open(path: &Path, capacity: usize) -> Result<CursorStore, String> {
+        let capacity = match capacity {
+            0 => DEFAULT_CURSOR_POINTS,
+            capacity => capacity,
+        };
+
+        let points: VecDeque<PallasPoint> = match std::fs::read_to_string(path) {
+            Ok(contents) => contents.lines().rev().map(parse_point).collect::<Result<_, _>>()?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => VecDeque::new(),
+            Err(e) => return Err(format!("{:?}", e)),
+        };
+
+        Ok(CursorStore {
+            path: path.to_path_buf(),
+            capacity,
+            points,
+        })
+    }
+
+    // Newest first
+    pub fn points(&self) -> Vec<PallasPoint> {
+        self.points.iter().rev().cloned().collect()
+    }
+
+    // Acknowledging a point behind the newest one drops the points past it,
+    // as a rollback does
+    pub fn ack(&mut self, point: PallasPoint) -> std::io::Result<()> {
+        match &point {
+            PallasPoint::Specific(slot, _) => self
+                .points
+                .retain(|p| matches!(p, PallasPoint::Specific(s, _) if s < slot)),
+            PallasPoint::Origin => self.points.clear(),
+        }
+
+        self.points.push_back(point);
+
+        while self.points.len() > self.capacity {
+            self.points.pop_front();
+        }
+
+        self.save()
+    }
+
+    fn save(&self) -> std::io::Result<()> {
+        let contents: String = self.points.iter().rev().map(format_point).collect();
+        let temp = self.path.with_extension("tmp");
+
+        let mut file = File::create(&temp)?;
+        file.write_all(contents.as_bytes())?;
+        file.sync_all()?;
+
+        std::fs::rename(temp, &self.path)
+    }
+}
+
+fn format_point(point: &PallasPoint) -> String {
+    match point {
+        PallasPoint::Origin => "0 \n".to_string(),
+        PallasPoint::Specific(slot, hash) => format!("{} {}\n", slot, hex::encode(hash)),
+    }
+}
+
+fn parse_point(line: &str) -> Result<PallasPoint, String> {
+    let invalid = || format!("invalid cursor line {:?}", line);
+
+    let (slot, hash) = line.split_once(' ').ok_or_else(invalid)?;
+    let slot = slot.parse().map_err(|_| invalid())?;
+    let hash = hex::decode(hash).map_err(|_| invalid())?;
+
+    Ok(Point { slot, hash }.into())
+}
+
+// Intersects a new session at the newest point of the cursor still on the
+// node's chain. An empty cursor leaves chain-sync at origin.
+pub async fn resume(session: &mut Session, cursor: &CursorStore, options: &ClientOptions) -> Result<(), String> {
+    let points = cursor.points();
+
+    if points.is_empty() {
+        return Ok(());
+    }
+
+    let intersect = with_timeout(session.find_intersect(points), options.request_timeout())
+        .await
+        .map_err(|_| "intersect timed out".to_string())??;
+
+    match intersect {
+        (Some(_), _) => Ok(()),
+        (None, _) => Err("none of the cursor's points is on the node's chain".to_string()),
+    }
+}
+
+impl ClientWrapper {
+    // Records that the consumer is done with the response at `point`: the
+    // acked stream, if running, may deliver more, and with
+    // ClientOptions.cursor_path set the next connect intersects at the newest
+    // acknowledged point. Returns false when there is neither, the acked
+    // stream did not deliver the point or the cursor cannot be written.
+    #[net]
+    pub fn ack_point(client_wrapper: ClientWrapper, point: Point) -> bool {
+        ClientWrapper::ack_point(client_wrapper, point)
+    }
+
+    pub fn ack_point(client_wrapper: ClientWrapper, point: Point) -> bool {
+        let state = client_wrapper.state();
+        let point: PallasPoint = point.into();
+
+        let streamed = match state.acked_stream.lock().unwrap().as_mut() {
+            Some(stream) => match stream.ack(&point) {
+                Ok(()) => true,
+                Err(e) => {
+                    println!("ack_point error: {}", e);
+                    return false;
+                }
+            },
+            None => false,
+        };
+
+        match state.cursor.lock().unwrap().as_mut() {
+            Some(cursor) => match cursor.ack(point) {
+                Ok(()) => true,
+                Err(e) => {
+                    println!("ack_point error: {:?}", e);
+                    false
+                }
+            },
+            None if streamed => true,
+            None => {
+                println!("ack_point error: no cursor configured or acked stream running");
+                false
+            }
+        }
+    }
+
+    // Acknowledged points, newest first
+    #[net]
+    pub fn get_cursor_points(client_wrapper: ClientWrapper) -> Vec<Point> {
+        ClientWrapper::get_cursor_points(client_wrapper)
+    }
+
+    pub fn get_cursor_points(client_wrapper: ClientWrapper) -> Vec<Point> {
+        match client_wrapper.state().cursor.lock().unwrap().as_ref() {
+            Some(cursor) => cursor.points().into_iter().map(Point::from).collect(),
+            None => vec![],
+        }
+    }
+}
@@ -0,0 +1,245 @@
+use pallas::{
+    codec::{minicbor, utils::Bytes},
+    crypto::hash::Hash,
+    ledger::{
+        primitives::conway::{CostMdls, ExUnits, Language, MintedTx, Redeemer, RedeemerTag, TransactionInput, TransactionOutput},
+        traverse::{Era, MultiEraTx},
+    },
+};
+use rnet::{net, Net};
+use uplc::{
+    ast::{FakeNamedDeBruijn, NamedDeBruijn, Program},
+    machine::cost_model::ExBudget,
+    tx::{
+        error::Error,
+        redeemer_tag_to_string,
+        script_context::{self, DataLookupTable, ScriptContext, ScriptVersion, TxInfoV1, TxInfoV2, TxInfoV3},
+        to_plutus_data::ToPlutusData,
+    },
+};
+
+use crate::validation::{ProtocolParameters, ResolvedInput};
+
+#[derive(Net, Default)]
+pub struct SlotConfig {
+    zero_time: u64,
+    zero_slot: u64,
+    slot_length: u32,
+}
+
+#[derive(Net)]
+pub struct RedeemerExUnits {
+    pub(crate) tag: u8,
+    pub(crate) index: u32,
+    pub(crate) mem: u64,
+    pub(crate) steps: u64,
+}
+
+#[derive(Net)]
+pub struct EvaluationResult {
+    pub(crate) redeemers: Vec<RedeemerExUnits>,
+    pub(crate) error: Option<String>,
+    // Traces of the scripts run, up to the failing one when evaluation fails
+    pub(crate) logs: Vec<String>,
+}
+
+impl EvaluationResult {
+    fn failure(error: String, logs: Vec<String>) -> EvaluationResult {
+        EvaluationResult {
+            redeemers: vec![],
+            error: Some(error),
+            logs,
+        }
+    }
+}
+
+#[derive(Net)]
+pub struct ScriptEvaluator {}
+
+impl ScriptEvaluator {
+    // Runs the tx's scripts and returns the ex-units each redeemer needs. The
+    // cost models and the budget the redeemers share are taken from
+    // `protocol_params`; with no cost model set the evaluator's built-in
+    // ones are used.
+    #[net]
+    pub fn evaluate_tx(
+        tx_cbor: Vec<u8>,
+        resolved_inputs: Vec<ResolvedInput>,
+        protocol_params: ProtocolParameters,
+        slot_config: SlotConfig,
+    ) -> EvaluationResult {
+        ScriptEvaluator::evaluate_tx(tx_cbor, resolved_inputs, protocol_params, slot_config)
+    }
+
+    pub fn evaluate_tx(
+        tx_cbor: Vec<u8>,
+        resolved_inputs: Vec<ResolvedInput>,
+        protocol_params: ProtocolParameters,
+        slot_config: SlotConfig,
+    ) -> EvaluationResult {
+        // Babbage and Alonzo txs decode as Conway ones, which the evaluator
+        // builds the script contexts from
+        let tx = match MultiEraTx::decode_for_era(Era::Conway, &tx_cbor) {
+            Ok(MultiEraTx::Conway(tx)) => tx,
+            Ok(tx) => return EvaluationResult::failure(format!("InvalidTx(decoded as {:?})", tx.era()), vec![]),
+            Err(e) => return EvaluationResult::failure(format!("InvalidTx({})", e), vec![]),
+        };
+
+        let utxos = match resolved_inputs
+            .into_iter()
+            .map(|resolved| {
+                let tx_hash: [u8; 32] = resolved
+                    .tx_hash
+                    .as_slice()
+                    .try_into()
+                    .map_err(|_| format!("InvalidInputHash({})", hex::encode(&resolved.tx_hash)))?;
+
+                let output = minicbor::decode::<TransactionOutput>(&resolved.output_cbor)
+                    .map_err(|e| format!("InvalidOutput({})", e))?;
+
+                Ok(script_context::ResolvedInput {
+                    input: TransactionInput {
+                        transaction_id: Hash::new(tx_hash),
+                        index: resolved.index,
+                    },
+                    output,
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()
+        {
+            Ok(utxos) => utxos,
+            Err(e) => return EvaluationResult::failure(e, vec![]),
+        };
+
+        let cost_mdls = CostMdls {
+            plutus_v1: (!protocol_params.plutus_v1_cost_model.is_empty()).then_some(protocol_params.plutus_v1_cost_model),
+            plutus_v2: (!protocol_params.plutus_v2_cost_model.is_empty()).then_some(protocol_params.plutus_v2_cost_model),
+            plutus_v3: (!protocol_params.plutus_v3_cost_model.is_empty()).then_some(protocol_params.plutus_v3_cost_model),
+        };
+
+        let slot_config = script_context::SlotConfig {
+            zero_time: slot_config.zero_time,
+            zero_slot: slot_config.zero_slot,
+            slot_length: slot_config.slot_length,
+        };
+
+        let mut budget = ExBudget {
+            cpu: protocol_params.max_tx_ex_steps as i64,
+            mem: protocol_params.max_tx_ex_mem as i64,
+        };
+
+        let lookup_table = DataLookupTable::from_transaction(&tx, &utxos);
+        let mut redeemers = vec![];
+        let mut logs = vec![];
+
+        for (key, value) in tx.transaction_witness_set.redeemer.iter().flat_map(|redeemers| redeemers.iter()) {
+            let redeemer = Redeemer {
+                tag: key.tag,
+                index: key.index,
+                data: value.data.clone(),
+                ex_units: value.ex_units,
+            };
+
+            let ex_units = match eval_redeemer(&tx, &utxos, &slot_config, &redeemer, &lookup_table, &cost_mdls, &budget, &mut logs) {
+                Ok(ex_units) => ex_units,
+                Err(e) => {
+                    let e = Error::RedeemerError {
+                        tag: redeemer_tag_to_string(&redeemer.tag),
+                        index: redeemer.index,
+                        err: Box::new(e),
+                    };
+
+                    return EvaluationResult::failure(e.to_string(), logs);
+                }
+            };
+
+            // Evaluation fails before the budget runs out
+            budget.cpu -= ex_units.steps as i64;
+            budget.mem -= ex_units.mem as i64;
+
+            redeemers.push(RedeemerExUnits {
+                tag: match redeemer.tag {
+                    RedeemerTag::Spend => 0,
+                    RedeemerTag::Mint => 1,
+                    RedeemerTag::Cert => 2,
+                    RedeemerTag::Reward => 3,
+                    RedeemerTag::Vote => 4,
+                    RedeemerTag::Propose => 5,
+                },
+                index: redeemer.index,
+                mem: ex_units.mem,
+                steps: ex_units.steps,
+            });
+        }
+
+        EvaluationResult {
+            redeemers,
+            error: None,
+            logs,
+        }
+    }
+}
+
+// Runs the script `redeemer` points at the way uplc's `eval_redeemer` does,
+// appending its traces to `logs` whether it succeeds or not. Without a cost
+// model for its language the built-in one is used.
+#[allow(clippy::too_many_arguments)]
+fn eval_redeemer(
+    tx: &MintedTx,
+    utxos: &[script_context::ResolvedInput],
+    slot_config: &script_context::SlotConfig,
+    redeemer: &Redeemer,
+    lookup_table: &DataLookupTable,
+    cost_mdls: &CostMdls,
+    budget: &ExBudget,
+    logs: &mut Vec<String>,
+) -> Result<ExUnits, Error> {
+    let (script, datum) = script_context::find_script(redeemer, tx, utxos, lookup_table)?;
+
+    let (language, costs, tx_info, script): (Language, _, _, Bytes) = match script {
+        ScriptVersion::Native(_) => return Err(Error::NativeScriptPhaseTwo),
+        ScriptVersion::V1(script) => (Language::PlutusV1, &cost_mdls.plutus_v1, TxInfoV1::from_transaction(tx, utxos, slot_config)?, script.0),
+        ScriptVersion::V2(script) => (Language::PlutusV2, &cost_mdls.plutus_v2, TxInfoV2::from_transaction(tx, utxos, slot_config)?, script.0),
+        ScriptVersion::V3(script) => (Language::PlutusV3, &cost_mdls.plutus_v3, TxInfoV3::from_transaction(tx, utxos, slot_config)?, script.0),
+    };
+
+    let mut buffer = Vec::new();
+    let program: Program<NamedDeBruijn> = Program::<FakeNamedDeBruijn>::from_cbor(&script, &mut buffer)?.into();
+
+    let script_context = tx_info
+        .into_script_context(redeemer, datum.as_ref())
+        .ok_or(Error::NoRedeemers)?;
+
+    let program = match script_context {
+        ScriptContext::V1V2 { .. } => match datum {
+            Some(datum) => program.apply_data(datum),
+            None => program,
+        }
+        .apply_data(redeemer.data.clone())
+        .apply_data(script_context.to_plutus_data()),
+        ScriptContext::V3 { .. } => program.apply_data(script_context.to_plutus_data()),
+    };
+
+    let mut result = match costs {
+        Some(costs) => program.eval_as(&language, costs, Some(budget)),
+        None => program.eval_version(*budget, &language),
+    };
+
+    let cost = result.cost();
+    let mut traces = result.logs();
+
+    match result.result() {
+        Ok(_) => {
+            logs.append(&mut traces);
+
+            Ok(ExUnits {
+                mem: cost.mem as u64,
+                steps: cost.cpu as u64,
+            })
+        }
+        Err(e) => {
+            logs.extend(traces.iter().cloned());
+            Err(Error::Machine(e, cost, traces))
+        }
+    }
+}
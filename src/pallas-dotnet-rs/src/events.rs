@@ -0,0 +1,230 @@
+use std::collections::VecDeque;
+
+use pallas::{
+    codec::minicbor,
+    ledger::traverse::{MultiEraBlock, MultiEraCert, MultiEraTx},
+    network::miniprotocols::Point as PallasPoint,
+};
+use rnet::{net, Net};
+
+use crate::{
+    kept_after_rollback, options::interruptible, runtime, utxo::ledger_effects, ClientWrapper, NextResponse, Point, ACTION_ROLL_BACK,
+    ACTION_ROLL_FORWARD,
+};
+
+pub const EVENT_BLOCK: u8 = 1;
+pub const EVENT_TX: u8 = 2;
+pub const EVENT_OUTPUT_CREATED: u8 = 3;
+pub const EVENT_OUTPUT_SPENT: u8 = 4;
+// Negative quantities are burns
+pub const EVENT_MINT: u8 = 5;
+pub const EVENT_CERTIFICATE: u8 = 6;
+pub const EVENT_GOVERNANCE_PROPOSAL: u8 = 7;
+
+// Blocks whose events can still be undone
+const EVENT_WINDOW: usize = 2160;
+
+#[derive(Net, Clone, Default)]
+pub struct ChainEvent {
+    pub(crate) kind: u8,
+    // Counterpart of an event emitted earlier, for a rolled back block
+    pub(crate) undo: bool,
+    pub(crate) slot: u64,
+    pub(crate) block_hash: Vec<u8>,
+    pub(crate) block_number: u64,
+    // Empty for block events
+    pub(crate) tx_hash: Vec<u8>,
+    // Position of the tx in the block
+    pub(crate) tx_index: u64,
+    // Output created or spent, `output_tx_hash#index`
+    pub(crate) output_tx_hash: Vec<u8>,
+    // Output index, or position of the certificate or proposal in the tx
+    pub(crate) index: u64,
+    // Header, tx, output, certificate or proposal
+    pub(crate) cbor: Vec<u8>,
+    pub(crate) policy_id: Vec<u8>,
+    pub(crate) asset_name: Vec<u8>,
+    pub(crate) quantity: i64,
+}
+
+#[derive(Net)]
+pub struct ChainEvents {
+    pub(crate) action: u8,
+    pub(crate) tip: Option<Point>,
+    pub(crate) events: Vec<ChainEvent>,
+}
+
+impl From<NextResponse> for ChainEvents {
+    fn from(response: NextResponse) -> Self {
+        ChainEvents {
+            action: response.action,
+            tip: response.tip,
+            events: vec![],
+        }
+    }
+}
+
+fn cert_cbor(cert: &MultiEraCert) -> Vec<u8> {
+    match cert {
+        MultiEraCert::AlonzoCompatible(cert) => minicbor::to_vec(&***cert),
+        MultiEraCert::Conway(cert) => minicbor::to_vec(&***cert),
+        _ => Ok(vec![]),
+    }
+    .unwrap_or_default()
+}
+
+fn proposals_cbor(tx: &MultiEraTx) -> Vec<Vec<u8>> {
+    tx.as_conway()
+        .and_then(|tx| tx.transaction_body.proposal_procedures.as_ref())
+        .map(|proposals| proposals.iter().map(|proposal| minicbor::to_vec(proposal).unwrap_or_default()).collect())
+        .unwrap_or_default()
+}
+
+// Events of a block in the order they are applied, the undo of the block
+// emits them in reverse
+pub fn block_events(block: &MultiEraBlock) -> Vec<ChainEvent> {
+    let block_event = ChainEvent {
+        kind: EVENT_BLOCK,
+        slot: block.slot(),
+        block_hash: block.hash().to_vec(),
+        block_number: block.number(),
+        cbor: block.header().cbor().to_vec(),
+        ..ChainEvent::default()
+    };
+
+    let mut events = vec![];
+
+    for (tx_index, tx) in block.txs().iter().enumerate() {
+        let tx_event = ChainEvent {
+            kind: EVENT_TX,
+            tx_hash: tx.hash().to_vec(),
+            tx_index: tx_index as u64,
+            cbor: tx.encode(),
+            ..block_event.clone()
+        };
+
+        let event = |kind, output_tx_hash, index, cbor| ChainEvent {
+            kind,
+            output_tx_hash,
+            index,
+            cbor,
+            ..tx_event.clone()
+        };
+
+        let (consumed, produced) = ledger_effects(tx);
+        let mut tx_events = vec![];
+
+        for input in consumed {
+            tx_events.push(event(EVENT_OUTPUT_SPENT, input.hash().to_vec(), input.index(), vec![]));
+        }
+
+        for (index, output) in produced {
+            tx_events.push(event(EVENT_OUTPUT_CREATED, tx_event.tx_hash.clone(), index as u64, output.encode()));
+        }
+
+        // Failed txs mint nothing and have no other effect
+        if tx.is_valid() {
+            for policy in tx.mints() {
+                for asset in policy.assets() {
+                    tx_events.push(ChainEvent {
+                        policy_id: policy.policy().to_vec(),
+                        asset_name: asset.name().to_vec(),
+                        quantity: asset.mint_coin().unwrap_or_default(),
+                        ..event(EVENT_MINT, vec![], 0, vec![])
+                    });
+                }
+            }
+
+            for (index, cert) in tx.certs().iter().enumerate() {
+                tx_events.push(event(EVENT_CERTIFICATE, vec![], index as u64, cert_cbor(cert)));
+            }
+
+            for (index, proposal) in proposals_cbor(tx).into_iter().enumerate() {
+                tx_events.push(event(EVENT_GOVERNANCE_PROPOSAL, vec![], index as u64, proposal));
+            }
+        }
+
+        events.push(tx_event);
+        events.extend(tx_events);
+    }
+
+    events.insert(0, block_event);
+    events
+}
+
+// Events of the recently applied blocks, replayed as undo events when
+// chain-sync rolls back over them
+#[derive(Default)]
+pub struct EventLog {
+    // Oldest first
+    applied: VecDeque<(PallasPoint, Vec<ChainEvent>)>,
+}
+
+impl EventLog {
+    // `position` is where the consumer is after the response
+    pub fn on_response(&mut self, response: &NextResponse, position: Option<&PallasPoint>) -> Vec<ChainEvent> {
+        match (response.action, &response.block_cbor, position) {
+            (ACTION_ROLL_FORWARD, Some(block_cbor), _) => match MultiEraBlock::decode(block_cbor) {
+                Ok(block) => {
+                    let events = block_events(&block);
+
+                    self.applied.push_back((PallasPoint::Specific(block.slot(), block.hash().to_vec()), events.clone()));
+
+                    while self.applied.len() > EVENT_WINDOW {
+                        self.applied.pop_front();
+                    }
+
+                    events
+                }
+                Err(e) => {
+                    println!("chain_sync_next_events error: {:?}", e);
+                    vec![]
+                }
+            },
+            (ACTION_ROLL_BACK, _, Some(point)) => self.undo(point),
+            _ => vec![],
+        }
+    }
+
+    // Only the blocks still in the window can be undone
+    fn undo(&mut self, point: &PallasPoint) -> Vec<ChainEvent> {
+        let kept = kept_after_rollback(self.applied.iter().map(|(applied, _)| applied), point);
+
+        let mut events = vec![];
+
+        while self.applied.len() > kept {
+            let (_, block_events) = self.applied.pop_back().unwrap();
+            events.extend(block_events.into_iter().rev().map(|event| ChainEvent { undo: true, ..event }));
+        }
+
+        events
+    }
+}
+
+impl ClientWrapper {
+    // Same as chain_sync_next, with the block broken down into typed events.
+    // A rollback comes with the undo events of the blocks it removes.
+    #[net]
+    pub fn chain_sync_next_events(client_wrapper: ClientWrapper) -> ChainEvents {
+        ClientWrapper::chain_sync_next_events(client_wrapper)
+    }
+
+    pub fn chain_sync_next_events(client_wrapper: ClientWrapper) -> ChainEvents {
+        let state = client_wrapper.state();
+        let cancel = state.cancel_token();
+
+        if state.is_streaming() {
+            println!("chain_sync_next_events error: chain-sync stream or sink is running");
+            return ChainEvents::from(NextResponse::error());
+        }
+
+        runtime::block_on(async {
+            let mut connection = match interruptible(state.connection.lock(), None, &cancel).await {
+                Ok(connection) => connection,
+                Err(_) => return ChainEvents::from(NextResponse::cancelled()),
+            };
+
+            connection.next_events(&state.options, &cancel).await
+        })
+    }
+}
@@ -0,0 +1,304 @@
+use std::{collections::HashSet, ops::Range};
+
+use pallas::{
+    codec::minicbor::{data::Type, decode, Decoder, Encoder},
+    ledger::{
+        addresses::{Address, ByronAddress},
+        traverse::{Era, MultiEraBlock, MultiEraOutput, MultiEraPolicyAssets, MultiEraTx},
+    },
+};
+use rnet::{net, Net};
+
+use crate::{ClientWrapper, NextResponse, ACTION_ROLL_FORWARD};
+
+// A tx matches when any of its outputs, mints or metadata matches any of
+// the criteria. Inputs are not resolved, so spending from an address only
+// matches through the change going back to it.
+#[derive(Net, Default)]
+pub struct SyncFilter {
+    // Bech32 or base58 (Byron)
+    pub(crate) addresses: Vec<String>,
+    pub(crate) payment_credentials: Vec<Vec<u8>>,
+    pub(crate) stake_credentials: Vec<Vec<u8>>,
+    // Outputs holding or txs minting the policy
+    pub(crate) policy_ids: Vec<Vec<u8>>,
+    // Under any policy
+    pub(crate) asset_names: Vec<Vec<u8>>,
+    pub(crate) metadata_labels: Vec<u64>,
+    // Script addresses and minting policies
+    pub(crate) script_hashes: Vec<Vec<u8>>,
+}
+
+pub struct TxFilter {
+    addresses: HashSet<Vec<u8>>,
+    payment_credentials: HashSet<Vec<u8>>,
+    stake_credentials: HashSet<Vec<u8>>,
+    policy_ids: HashSet<Vec<u8>>,
+    asset_names: HashSet<Vec<u8>>,
+    metadata_labels: HashSet<u64>,
+    script_hashes: HashSet<Vec<u8>>,
+}
+
+impl TxFilter {
+    pub fn new(spec: SyncFilter) -> Result<TxFilter, String> {
+        let addresses = spec
+            .addresses
+            .iter()
+            .map(|address| {
+                Address::from_bech32(address)
+                    .or_else(|_| ByronAddress::from_base58(address).map(Address::Byron))
+                    .map(|address| address.to_vec())
+                    .map_err(|e| format!("invalid address {}: {:?}", address, e))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(TxFilter {
+            addresses,
+            payment_credentials: spec.payment_credentials.into_iter().collect(),
+            stake_credentials: spec.stake_credentials.into_iter().collect(),
+            policy_ids: spec.policy_ids.into_iter().collect(),
+            asset_names: spec.asset_names.into_iter().collect(),
+            metadata_labels: spec.metadata_labels.into_iter().collect(),
+            script_hashes: spec.script_hashes.into_iter().collect(),
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.addresses.is_empty()
+            && self.payment_credentials.is_empty()
+            && self.stake_credentials.is_empty()
+            && self.policy_ids.is_empty()
+            && self.asset_names.is_empty()
+            && self.metadata_labels.is_empty()
+            && self.script_hashes.is_empty()
+    }
+
+    fn matches_assets(&self, assets: &[MultiEraPolicyAssets]) -> bool {
+        assets.iter().any(|policy| {
+            self.policy_ids.contains(policy.policy().as_slice())
+                || policy.assets().iter().any(|asset| self.asset_names.contains(asset.name()))
+        })
+    }
+
+    fn matches_output(&self, output: &MultiEraOutput) -> bool {
+        if let Ok(address) = output.address() {
+            if self.addresses.contains(&address.to_vec()) {
+                return true;
+            }
+
+            if let Address::Shelley(address) = &address {
+                let payment = address.payment().as_hash().as_slice();
+
+                if self.payment_credentials.contains(payment) || (address.payment().is_script() && self.script_hashes.contains(payment)) {
+                    return true;
+                }
+
+                if let Some(stake) = address.delegation().as_hash() {
+                    let stake = stake.as_slice();
+
+                    if self.stake_credentials.contains(stake) || (address.delegation().is_script() && self.script_hashes.contains(stake)) {
+                        return true;
+                    }
+                }
+            }
+        }
+
+        self.matches_assets(&output.non_ada_assets())
+    }
+
+    pub fn matches(&self, tx: &MultiEraTx) -> bool {
+        let mints = tx.mints();
+
+        tx.outputs().iter().any(|output| self.matches_output(output))
+            || tx.collateral_return().is_some_and(|output| self.matches_output(&output))
+            || self.matches_assets(&mints)
+            || mints.iter().any(|policy| self.script_hashes.contains(policy.policy().as_slice()))
+            || self.metadata_labels.iter().any(|label| tx.metadata().find(*label).is_some())
+    }
+
+    // The block with only the matching txs, `None` when there is none. The
+    // header is kept verbatim, so the block hash is unchanged while the body
+    // hash no longer matches. Byron blocks are passed whole.
+    pub fn apply(&self, block_cbor: &[u8]) -> Result<Option<Vec<u8>>, String> {
+        let block = MultiEraBlock::decode(block_cbor).map_err(|e| format!("invalid block cbor: {:?}", e))?;
+        let txs = block.txs();
+
+        let selected: Vec<usize> = txs
+            .iter()
+            .enumerate()
+            .filter(|(_, tx)| self.matches(tx))
+            .map(|(i, _)| i)
+            .collect();
+
+        match selected.len() {
+            0 => Ok(None),
+            n if n == txs.len() || block.era() == Era::Byron => Ok(Some(block_cbor.to_vec())),
+            _ => rebuild(block_cbor, &selected).map(Some).map_err(|e| format!("cannot filter block: {:?}", e)),
+        }
+    }
+
+    // Filters a response in place, false when the whole block is filtered
+    // out. Blocks that cannot be decoded are delivered as they are.
+    pub fn retain(&self, response: &mut NextResponse) -> bool {
+        if response.action != ACTION_ROLL_FORWARD {
+            return true;
+        }
+
+        let filtered = match &response.block_cbor {
+            Some(block_cbor) => self.apply(block_cbor),
+            None => return true,
+        };
+
+        match filtered {
+            Ok(Some(block_cbor)) => {
+                response.block_cbor = Some(block_cbor);
+                true
+            }
+            Ok(None) => false,
+            Err(e) => {
+                println!("chain sync filter error: {}", e);
+                true
+            }
+        }
+    }
+}
+
+fn skip(d: &mut Decoder) -> Result<Range<usize>, decode::Error> {
+    let start = d.position();
+    d.skip()?;
+    Ok(start..d.position())
+}
+
+// Definite or indefinite length, the break ending the latter is consumed
+fn items(d: &mut Decoder, len: Option<u64>, mut item: impl FnMut(&mut Decoder) -> Result<(), decode::Error>) -> Result<(), decode::Error> {
+    match len {
+        Some(len) => (0..len).try_for_each(|_| item(d)),
+        None => {
+            while d.datatype()? != Type::Break {
+                item(d)?;
+            }
+
+            d.set_position(d.position() + 1);
+            Ok(())
+        }
+    }
+}
+
+// Re-encodes a Shelley or later `[era, block]` from the raw bytes of its
+// header and of the selected txs, renumbering their auxiliary data and
+// invalid flags. The block array keeps its definite or indefinite framing.
+fn rebuild(block_cbor: &[u8], selected: &[usize]) -> Result<Vec<u8>, decode::Error> {
+    let mut d = Decoder::new(block_cbor);
+
+    d.array()?;
+    let era = d.u16()?;
+    let fields = d.array()?;
+
+    let header = skip(&mut d)?;
+
+    let mut bodies = vec![];
+    let len = d.array()?;
+    items(&mut d, len, |d| skip(d).map(|body| bodies.push(body)))?;
+
+    let mut witnesses = vec![];
+    let len = d.array()?;
+    items(&mut d, len, |d| skip(d).map(|witness| witnesses.push(witness)))?;
+
+    let mut auxiliary_data = vec![];
+    let len = d.map()?;
+    items(&mut d, len, |d| {
+        let index = d.u32()? as usize;
+        skip(d).map(|data| auxiliary_data.push((index, data)))
+    })?;
+
+    // Alonzo onwards
+    let has_invalid = match fields {
+        Some(fields) => fields == 5,
+        None => d.datatype()? != Type::Break,
+    };
+
+    let mut invalid = vec![];
+    if has_invalid {
+        let len = d.array()?;
+        items(&mut d, len, |d| d.u32().map(|index| invalid.push(index as usize)))?;
+    }
+
+    let raw = |range: &Range<usize>| &block_cbor[range.clone()];
+    let mut e = Encoder::new(vec![]);
+
+    e.array(2).unwrap().u16(era).unwrap();
+
+    match fields {
+        Some(fields) => e.array(fields).unwrap(),
+        None => e.begin_array().unwrap(),
+    };
+
+    e.writer_mut().extend_from_slice(raw(&header));
+
+    e.array(selected.len() as u64).unwrap();
+    for i in selected {
+        e.writer_mut().extend_from_slice(raw(&bodies[*i]));
+    }
+
+    e.array(selected.len() as u64).unwrap();
+    for i in selected {
+        e.writer_mut().extend_from_slice(raw(&witnesses[*i]));
+    }
+
+    let kept: Vec<(usize, &Range<usize>)> = selected
+        .iter()
+        .enumerate()
+        .filter_map(|(new, old)| auxiliary_data.iter().find(|(i, _)| i == old).map(|(_, data)| (new, data)))
+        .collect();
+
+    e.map(kept.len() as u64).unwrap();
+    for (new, data) in kept {
+        e.u32(new as u32).unwrap();
+        e.writer_mut().extend_from_slice(raw(data));
+    }
+
+    if has_invalid {
+        let kept: Vec<usize> = selected
+            .iter()
+            .enumerate()
+            .filter(|(_, old)| invalid.contains(old))
+            .map(|(new, _)| new)
+            .collect();
+
+        e.array(kept.len() as u64).unwrap();
+        for new in kept {
+            e.u32(new as u32).unwrap();
+        }
+    }
+
+    if fields.is_none() {
+        e.end().unwrap();
+    }
+
+    Ok(e.into_writer())
+}
+
+impl ClientWrapper {
+    // From now on chain_sync_next and the chain-sync stream only deliver the
+    // blocks holding a matching tx, stripped of the other txs. Rollbacks and
+    // awaits go through unchanged. Returns false when an address is invalid,
+    // an empty filter removes the current one.
+    #[net]
+    pub fn set_chain_sync_filter(client_wrapper: ClientWrapper, filter: SyncFilter) -> bool {
+        ClientWrapper::set_chain_sync_filter(client_wrapper, filter)
+    }
+
+    pub fn set_chain_sync_filter(client_wrapper: ClientWrapper, filter: SyncFilter) -> bool {
+        let filter = match TxFilter::new(filter) {
+            Ok(filter) => filter,
+            Err(e) => {
+                println!("set_chain_sync_filter error: {}", e);
+                return false;
+            }
+        };
+
+        client_wrapper.state().followers.lock().unwrap().filter = (!filter.is_empty()).then_some(filter);
+
+        true
+    }
+}
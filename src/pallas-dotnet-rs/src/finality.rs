@@ -0,0 +1,189 @@
+use std::collections::VecDeque;
+
+use pallas::{ledger::traverse::MultiEraBlock, network::miniprotocols::Point as PallasPoint};
+use rnet::net;
+
+use crate::{
+    filter::TxFilter, kept_after_rollback, options::interruptible, runtime, ClientWrapper, NextResponse, Point, ACTION_PENDING,
+    ACTION_ROLL_BACK, ACTION_ROLL_FORWARD,
+};
+
+struct TentativeBlock {
+    point: PallasPoint,
+    number: u64,
+    block_cbor: Vec<u8>,
+    // Node's tip when the block arrived
+    tip: Option<Point>,
+}
+
+// Holds the blocks chain-sync delivered until they are `depth` blocks deep,
+// rollbacks simply drop the tentative blocks past their point. A rollback
+// past a confirmed block queues an error instead. Disabled while the depth
+// is 0.
+#[derive(Default)]
+pub struct FinalityBuffer {
+    depth: u64,
+    // Oldest first
+    tentative: VecDeque<TentativeBlock>,
+    // Waiting for chain_sync_next_confirmed, oldest first
+    confirmed: VecDeque<NextResponse>,
+    last_confirmed: Option<PallasPoint>,
+}
+
+impl FinalityBuffer {
+    pub fn set_depth(&mut self, depth: u64) {
+        self.depth = depth;
+
+        if depth == 0 {
+            self.tentative.clear();
+            self.confirmed.clear();
+            self.last_confirmed = None;
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.depth > 0
+    }
+
+    // Confirmed blocks go through the filter, the depth is counted on the
+    // unfiltered chain
+    pub fn follow(&mut self, response: &NextResponse, point: Option<&PallasPoint>, filter: Option<&TxFilter>) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        match (response.action, &response.block_cbor, point) {
+            (ACTION_ROLL_FORWARD, Some(block_cbor), Some(point)) => match MultiEraBlock::decode(block_cbor) {
+                Ok(block) => {
+                    self.tentative.push_back(TentativeBlock {
+                        point: point.clone(),
+                        number: block.number(),
+                        block_cbor: block_cbor.clone(),
+                        tip: response.tip.clone(),
+                    });
+
+                    self.confirm(filter);
+                }
+                Err(e) => println!("finality buffer error: {:?}", e),
+            },
+            (ACTION_ROLL_BACK, _, Some(point)) => self.roll_back(point),
+            _ => {}
+        }
+    }
+
+    fn confirm(&mut self, filter: Option<&TxFilter>) {
+        let newest = match self.tentative.back() {
+            Some(block) => block.number,
+            None => return,
+        };
+
+        while self.tentative.front().is_some_and(|block| newest.saturating_sub(block.number) >= self.depth) {
+            let block = self.tentative.pop_front().unwrap();
+            self.last_confirmed = Some(block.point);
+
+            let mut response = NextResponse {
+                action: ACTION_ROLL_FORWARD,
+                tip: block.tip,
+                block_cbor: Some(block.block_cbor),
+            };
+
+            match filter {
+                Some(filter) if !filter.retain(&mut response) => {}
+                _ => self.confirmed.push_back(response),
+            }
+        }
+    }
+
+    // Also used when chain-sync intersects
+    pub fn roll_back(&mut self, point: &PallasPoint) {
+        let slot = match point {
+            PallasPoint::Specific(slot, _) => *slot,
+            PallasPoint::Origin => 0,
+        };
+
+        let kept = kept_after_rollback(self.tentative.iter().map(|block| &block.point), point);
+        self.tentative.truncate(kept);
+
+        // Kept tentative blocks build on the confirmed one. Otherwise the
+        // point has to be the confirmed block itself or come after it, a
+        // block of another fork at its slot replaces it.
+        if let Some(confirmed_point @ PallasPoint::Specific(confirmed, _)) = &self.last_confirmed {
+            if kept == 0 && confirmed_point != point && *confirmed >= slot {
+                println!("chain_sync_next_confirmed error: rollback to slot {} is deeper than the confirmed block at slot {}", slot, confirmed);
+
+                // The confirmed chain continues from the rollback point
+                self.confirmed.push_back(NextResponse::error());
+                self.last_confirmed = Some(point.clone());
+            }
+        }
+    }
+
+    pub fn next_confirmed(&mut self) -> Option<NextResponse> {
+        self.confirmed.pop_front()
+    }
+}
+
+impl ClientWrapper {
+    // Blocks delivered by chain-sync are held back until `depth` blocks are
+    // built on top of them (2160 for mainnet's k, or a smaller confirmation
+    // count), then queued for chain_sync_next_confirmed. chain_sync_next and
+    // the chain-sync stream keep delivering the tentative chain. 0 disables
+    // the buffer and drops what it holds.
+    #[net]
+    pub fn set_finality_depth(client_wrapper: ClientWrapper, depth: u64) {
+        ClientWrapper::set_finality_depth(client_wrapper, depth)
+    }
+
+    pub fn set_finality_depth(client_wrapper: ClientWrapper, depth: u64) {
+        client_wrapper.state().finality.lock().unwrap().set_depth(depth);
+    }
+
+    // Next confirmed block, rollbacks never reach this side. One deeper than
+    // a confirmed block is handed back as ACTION_ERROR after the blocks
+    // confirmed before it, the blocks after follow the new chain. When none
+    // is queued chain-sync is driven until one is, handing back the awaits,
+    // errors and reconnections met on the way. While the chain-sync stream
+    // or a sink runs, they feed the buffer and ACTION_PENDING is returned
+    // instead.
+    #[net]
+    pub fn chain_sync_next_confirmed(client_wrapper: ClientWrapper) -> NextResponse {
+        ClientWrapper::chain_sync_next_confirmed(client_wrapper)
+    }
+
+    pub fn chain_sync_next_confirmed(client_wrapper: ClientWrapper) -> NextResponse {
+        let state = client_wrapper.state();
+        let cancel = state.cancel_token();
+
+        if !state.finality.lock().unwrap().is_enabled() {
+            println!("chain_sync_next_confirmed error: finality depth not set");
+            return NextResponse::error();
+        }
+
+        loop {
+            if let Some(confirmed) = state.finality.lock().unwrap().next_confirmed() {
+                return confirmed;
+            }
+
+            if state.is_streaming() {
+                return NextResponse {
+                    action: ACTION_PENDING,
+                    tip: None,
+                    block_cbor: None,
+                };
+            }
+
+            let next_response = runtime::block_on(async {
+                let mut connection = match interruptible(state.connection.lock(), None, &cancel).await {
+                    Ok(connection) => connection,
+                    Err(_) => return NextResponse::cancelled(),
+                };
+
+                connection.next_response(&state.options, &cancel).await
+            });
+
+            if !matches!(next_response.action, ACTION_ROLL_FORWARD | ACTION_ROLL_BACK) {
+                return next_response;
+            }
+        }
+    }
+}
@@ -0,0 +1,132 @@
+use std::{path::PathBuf, sync::Mutex};
+
+use pallas::{ledger::traverse::MultiEraBlock, network::miniprotocols::Point as PallasPoint};
+use pallas_hardano::storage::immutable;
+use rnet::{net, Net};
+
+use crate::{NextResponse, Point, ACTION_AWAIT, ACTION_ROLL_FORWARD};
+
+type Blocks = Box<dyn Iterator<Item = Result<Vec<u8>, String>>>;
+
+// Reads the chunk files of a node's `db/immutable` directory. Blocks come in
+// the `[era, block]` envelope N2C chain-sync delivers, so both sources can
+// feed the same consumer.
+pub struct ImmutableDb {
+    blocks: Mutex<Blocks>,
+    tip: Option<PallasPoint>,
+}
+
+impl ImmutableDb {
+    // Like chain-sync after an intersection, the block at `point` itself is
+    // not delivered again.
+    pub fn open(path: PathBuf, point: PallasPoint) -> Result<ImmutableDb, String> {
+        let tip = immutable::get_tip(&path).map_err(|e| format!("cannot read tip: {:?}", e))?;
+
+        let mut blocks = immutable::read_blocks_from_point(&path, point.clone())
+            .map_err(|e| format!("cannot read from {:?}: {:?}", point, e))?
+            .map(|block| block.map_err(|e| format!("{:?}", e)))
+            .peekable();
+
+        if let PallasPoint::Specific(slot, hash) = &point {
+            let at_point = match blocks.peek() {
+                Some(Ok(block)) => MultiEraBlock::decode(block).is_ok_and(|b| b.slot() == *slot && b.hash().to_vec() == *hash),
+                _ => false,
+            };
+
+            if at_point {
+                blocks.next();
+            }
+        }
+
+        Ok(ImmutableDb {
+            blocks: Mutex::new(Box::new(blocks)),
+            tip,
+        })
+    }
+
+    // ACTION_AWAIT once every immutable block has been read
+    pub fn next(&self) -> NextResponse {
+        let tip = self.tip.clone().map(Point::from);
+
+        match self.blocks.lock().unwrap().next() {
+            Some(Ok(block)) => NextResponse {
+                action: ACTION_ROLL_FORWARD,
+                tip,
+                block_cbor: Some(block),
+            },
+            Some(Err(e)) => {
+                println!("immutable_db_next error: {}", e);
+                NextResponse::error()
+            }
+            None => NextResponse {
+                action: ACTION_AWAIT,
+                tip,
+                block_cbor: None,
+            },
+        }
+    }
+}
+
+#[derive(Net, Clone)]
+pub struct ImmutableDbWrapper {
+    db_ptr: usize,
+}
+
+impl ImmutableDbWrapper {
+    pub fn db(&self) -> &ImmutableDb {
+        unsafe { &*(self.db_ptr as *const ImmutableDb) }
+    }
+
+    // Starts after `point`, an empty hash starts at the first block. None when
+    // the directory or the point cannot be read.
+    #[net]
+    pub fn open_immutable_db(path: String, point: Point) -> Option<ImmutableDbWrapper> {
+        ImmutableDbWrapper::open_immutable_db(path, point)
+    }
+
+    pub fn open_immutable_db(path: String, point: Point) -> Option<ImmutableDbWrapper> {
+        match ImmutableDb::open(PathBuf::from(path), point.into()) {
+            Ok(db) => Some(ImmutableDbWrapper {
+                db_ptr: Box::into_raw(Box::new(db)) as usize,
+            }),
+            Err(e) => {
+                println!("open_immutable_db error: {}", e);
+                None
+            }
+        }
+    }
+
+    #[net]
+    pub fn immutable_db_next(db_wrapper: ImmutableDbWrapper) -> NextResponse {
+        ImmutableDbWrapper::immutable_db_next(db_wrapper)
+    }
+
+    pub fn immutable_db_next(db_wrapper: ImmutableDbWrapper) -> NextResponse {
+        db_wrapper.db().next()
+    }
+
+    // Last block of the immutable chain, origin when there is none
+    #[net]
+    pub fn get_immutable_db_tip(path: String) -> Point {
+        ImmutableDbWrapper::get_immutable_db_tip(path)
+    }
+
+    pub fn get_immutable_db_tip(path: String) -> Point {
+        match immutable::get_tip(&PathBuf::from(path)) {
+            Ok(tip) => tip.unwrap_or(PallasPoint::Origin).into(),
+            Err(e) => {
+                println!("get_immutable_db_tip error: {:?}", e);
+                PallasPoint::Origin.into()
+            }
+        }
+    }
+
+    #[net]
+    pub fn close_immutable_db(db_wrapper: ImmutableDbWrapper) {
+        ImmutableDbWrapper::close_immutable_db(db_wrapper)
+    }
+
+    pub fn close_immutable_db(db_wrapper: ImmutableDbWrapper) {
+        drop(unsafe { Box::from_raw(db_wrapper.db_ptr as *mut ImmutableDb) });
+    }
+}
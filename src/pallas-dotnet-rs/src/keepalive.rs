@@ -0,0 +1,104 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use pallas::network::miniprotocols::keepalive;
+use rnet::{net, Net};
+use tokio::task::JoinHandle;
+
+use crate::{options::with_timeout, runtime, ClientWrapper};
+
+pub const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(20);
+
+#[derive(Net, Clone, Default)]
+pub struct KeepAliveStats {
+    // Round-trip time of the last exchange in microseconds
+    pub(crate) rtt_us: u64,
+    pub(crate) roundtrips: u64,
+    pub(crate) error: Option<String>,
+}
+
+impl KeepAliveStats {
+    pub fn rtt(&self) -> Duration {
+        Duration::from_micros(self.rtt_us)
+    }
+
+    pub fn roundtrips(&self) -> u64 {
+        self.roundtrips
+    }
+}
+
+// Sends a keepalive cookie every `interval` so relays don't drop a peer that
+// idles at the tip. The task stops on the first failed round-trip.
+pub struct KeepAlive {
+    stats: Arc<Mutex<KeepAliveStats>>,
+    task: JoinHandle<()>,
+}
+
+impl KeepAlive {
+    pub fn start(mut client: keepalive::Client, interval: Duration, timeout: Option<Duration>) -> KeepAlive {
+        let stats = Arc::new(Mutex::new(KeepAliveStats::default()));
+
+        let task = {
+            let stats = stats.clone();
+
+            runtime::spawn(async move {
+                let mut ticker = tokio::time::interval(interval);
+                // The first tick completes immediately
+                ticker.tick().await;
+
+                loop {
+                    ticker.tick().await;
+
+                    let start = Instant::now();
+
+                    let error = match with_timeout(client.keepalive_roundtrip(), timeout).await {
+                        Ok(Ok(())) => None,
+                        Ok(Err(e)) => Some(format!("{:?}", e)),
+                        Err(_) => Some("keepalive timed out".to_string()),
+                    };
+
+                    let mut stats = stats.lock().unwrap();
+
+                    match error {
+                        None => {
+                            stats.rtt_us = start.elapsed().as_micros() as u64;
+                            stats.roundtrips += 1;
+                        }
+                        Some(e) => {
+                            stats.error = Some(e);
+                            break;
+                        }
+                    }
+                }
+            })
+        };
+
+        KeepAlive { stats, task }
+    }
+
+    pub fn stats(&self) -> Arc<Mutex<KeepAliveStats>> {
+        self.stats.clone()
+    }
+
+    pub fn stop(&self) {
+        self.task.abort();
+    }
+}
+
+impl ClientWrapper {
+    // Only N2N connections run keepalive, N2C clients report empty stats.
+    // For a peer set these are the stats of the peer running chain-sync.
+    #[net]
+    pub fn get_keep_alive_stats(client_wrapper: ClientWrapper) -> KeepAliveStats {
+        ClientWrapper::get_keep_alive_stats(client_wrapper)
+    }
+
+    pub fn get_keep_alive_stats(client_wrapper: ClientWrapper) -> KeepAliveStats {
+        match client_wrapper.state().peers.lock().unwrap().active() {
+            Some(peer) => peer.keepalive.lock().unwrap().clone(),
+            None => KeepAliveStats::default(),
+        }
+    }
+}
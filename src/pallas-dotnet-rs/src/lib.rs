@@ -1,59 +1,174 @@
-use std::{ 
-    ops::Deref, vec
+use std::{
+    ops::Deref, sync::Arc, time::{Duration, Instant}, vec
 };
-use lazy_static::lazy_static;
 use pallas::{
     ledger::{
         addresses::{Address, ByronAddress},
-        traverse::{MultiEraHeader, MultiEraTx},
+        traverse::{MultiEraBlock, MultiEraHeader},
     },
     network::{
         facades::{NodeClient, PeerClient},
         miniprotocols::{
-            blockfetch, chainsync::{self}, localstate::queries_v16::{self, Addr}, txsubmission::{self, EraTxBody, TxIdAndSize}, Point as PallasPoint, MAINNET_MAGIC, PREVIEW_MAGIC, PRE_PRODUCTION_MAGIC, TESTNET_MAGIC
-        }
+            blockfetch, chainsync::{self, Tip}, localstate::queries_v16::{self, Addr}, Point as PallasPoint, MAINNET_MAGIC, PREVIEW_MAGIC, PRE_PRODUCTION_MAGIC, TESTNET_MAGIC
+        },
     },
 };
+use acked::AckedStream;
+use bearer::ClientKind;
+use cursor::CursorStore;
+use options::{interruptible, with_timeout, ClientOptions, Interrupted};
+use peer::PeerConnection;
+use peer_set::{PeerSet, PeerTable};
 use rnet::{net, Net};
-use tokio::runtime::Runtime;
+use session::{Followers, Session, Target};
+use sink::SinkTask;
+use stream::ChainSyncStream;
+use versions::{accepted, VersionInfo};
+use submission::{Mempool, ServeError, TxStatus, TxSubmissionOutcome, TxSubmissionSession, TX_SUBMISSION_TIMEOUT};
+use finality::FinalityBuffer;
+use tokio::sync::{Mutex, MutexGuard};
+use tokio_util::sync::CancellationToken;
+
+mod acked;
+mod bearer;
+mod cursor;
+mod evaluation;
+mod events;
+mod filter;
+mod finality;
+mod immutable;
+mod keepalive;
+#[cfg(test)]
+mod mock;
+mod options;
+mod peer;
+mod peer_set;
+mod peer_sharing;
+mod runtime;
+mod server;
+mod session;
+mod sink;
+mod store;
+mod stream;
+mod submission;
+#[cfg(test)]
+mod tests;
+mod utxo;
+mod validation;
+mod versions;
 
 rnet::root!();
 
-lazy_static! {
-    static ref RT: Runtime = Runtime::new().expect("Failed to create Tokio runtime");
-}
-
 #[derive(Net)]
 pub struct NetworkMagic {}
 
 impl NetworkMagic {
     #[net]
+    pub fn mainnet_magic() -> u64 {
+        NetworkMagic::mainnet_magic()
+    }
+
     pub fn mainnet_magic() -> u64 {
         MAINNET_MAGIC
     }
 
     #[net]
+    pub fn testnet_magic() -> u64 {
+        NetworkMagic::testnet_magic()
+    }
+
     pub fn testnet_magic() -> u64 {
         TESTNET_MAGIC
     }
 
     #[net]
+    pub fn preview_magic() -> u64 {
+        NetworkMagic::preview_magic()
+    }
+
     pub fn preview_magic() -> u64 {
         PREVIEW_MAGIC
     }
 
     #[net]
+    pub fn pre_production_magic() -> u64 {
+        NetworkMagic::pre_production_magic()
+    }
+
     pub fn pre_production_magic() -> u64 {
         PRE_PRODUCTION_MAGIC
     }
 }
 
-#[derive(Net)]
+#[derive(Net, Clone)]
 pub struct Point {
     slot: u64,
     hash: Vec<u8>,
 }
 
+impl From<PallasPoint> for Point {
+    fn from(point: PallasPoint) -> Self {
+        match point {
+            PallasPoint::Origin => Point {
+                slot: 0,
+                hash: vec![],
+            },
+            PallasPoint::Specific(slot, hash) => Point { slot, hash },
+        }
+    }
+}
+
+// Origin is passed as slot 0 with an empty hash
+impl From<Point> for PallasPoint {
+    fn from(point: Point) -> Self {
+        match point.hash.is_empty() {
+            true => PallasPoint::Origin,
+            false => PallasPoint::Specific(point.slot, point.hash),
+        }
+    }
+}
+
+// Byron epoch boundary blocks take the first slot of their epoch, which the
+// first regular block of the epoch may share. They also repeat the number of
+// the block before them.
+pub fn is_epoch_boundary(block: &MultiEraBlock) -> bool {
+    matches!(block.header(), MultiEraHeader::EpochBoundary(_))
+}
+
+// Whether a block at `slot` may follow the tip at `tip_slot`
+pub fn extends_tip(slot: u64, is_boundary: bool, tip_slot: u64, tip_is_boundary: bool) -> bool {
+    slot > tip_slot || (slot == tip_slot && tip_is_boundary && !is_boundary)
+}
+
+// How many of `points`, oldest first, stay on the chain after a rollback to
+// `point`. The point is matched by slot and hash, a block sharing its slot
+// (an epoch boundary, or a block of another fork) is rolled back. A point
+// that is not among them keeps the points of earlier slots only.
+pub fn kept_after_rollback<'a>(points: impl IntoIterator<Item = &'a PallasPoint>, point: &PallasPoint) -> usize {
+    let points: Vec<&PallasPoint> = points.into_iter().collect();
+
+    match point {
+        PallasPoint::Origin => 0,
+        PallasPoint::Specific(at, _) => match points.iter().rposition(|p| *p == point) {
+            Some(i) => i + 1,
+            None => points
+                .iter()
+                .position(|p| matches!(p, PallasPoint::Specific(slot, _) if slot >= at))
+                .unwrap_or(points.len()),
+        },
+    }
+}
+
+pub const ACTION_ERROR: u8 = 0;
+pub const ACTION_ROLL_FORWARD: u8 = 1;
+pub const ACTION_ROLL_BACK: u8 = 2;
+pub const ACTION_AWAIT: u8 = 3;
+// No response buffered yet by a chain-sync stream
+pub const ACTION_PENDING: u8 = 4;
+pub const ACTION_CANCELLED: u8 = 5;
+// The connection was re-established, chain-sync continues from the last delivered point
+pub const ACTION_RECONNECTED: u8 = 6;
+
 #[derive(Net)]
 pub struct NextResponse {
     action: u8,
@@ -61,50 +176,336 @@ pub struct NextResponse {
     block_cbor: Option<Vec<u8>>,
 }
 
+impl NextResponse {
+    pub fn error() -> NextResponse {
+        NextResponse {
+            action: ACTION_ERROR,
+            tip: None,
+            block_cbor: None,
+        }
+    }
+
+    pub fn cancelled() -> NextResponse {
+        NextResponse {
+            action: ACTION_CANCELLED,
+            tip: None,
+            block_cbor: None,
+        }
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.action == ACTION_ERROR
+    }
+}
+
+// Responses of chain_sync_next_batch with their blocks back to back in
+// `blocks`, so that a batch crosses to .NET as one buffer. The block of
+// `responses[i]` is `blocks[offsets[i]..offsets[i + 1]]`, empty when the
+// response has none. `block_cbor` of the responses is left empty.
+#[derive(Net, Default)]
+pub struct NextBatch {
+    responses: Vec<NextResponse>,
+    blocks: Vec<u8>,
+    offsets: Vec<u64>,
+}
+
+impl NextBatch {
+    fn push(&mut self, mut next_response: NextResponse) {
+        if self.offsets.is_empty() {
+            self.offsets.push(0);
+        }
+
+        if let Some(block_cbor) = next_response.block_cbor.take() {
+            self.blocks.extend_from_slice(&block_cbor);
+        }
+
+        self.offsets.push(self.blocks.len() as u64);
+        self.responses.push(next_response);
+    }
+}
+
 pub enum Client {
     N2C(NodeClient),
-    N2N(PeerClient)
+    N2N(PeerConnection),
+    PeerSet(PeerSet)
 }
 
-#[derive(Net)]
+impl Client {
+    pub async fn connect(path_or_server: String, network_magic: u64, client: ClientKind, options: &ClientOptions) -> Result<(Client, VersionInfo), String> {
+        match client {
+            ClientKind::N2C => {
+                let bearer = options.bearer(client)?.connect(&path_or_server, options).await?;
+
+                let mut node_client = NodeClient::new(bearer);
+                let versions = options.handshake().n2c_versions(network_magic);
+
+                let (version, data) = match accepted(with_timeout(node_client.handshake().handshake(versions), options.handshake_timeout()).await) {
+                    Ok(accepted) => accepted,
+                    Err(e) => {
+                        node_client.abort().await;
+                        return Err(e);
+                    }
+                };
+
+                Ok((Client::N2C(node_client), VersionInfo::n2c(version, &data)))
+            },
+            ClientKind::N2N => PeerConnection::connect(&path_or_server, network_magic, options)
+                .await
+                .map(|(connection, version)| (Client::N2N(connection), version)),
+        }
+    }
+
+    // Also returns the point the consumer is at after the response
+    pub async fn next_response(&mut self, options: &ClientOptions, cancel: &CancellationToken) -> (NextResponse, Option<PallasPoint>) {
+        match self {
+            Client::N2C(client) => {
+                // Get the next block
+                let result = match client.chainsync().state() {
+                    // The node answered with Await, wait for the next block
+                    chainsync::State::MustReply => interruptible(client.chainsync().recv_while_must_reply(), options.idle_timeout(), cancel).await,
+                    // A previous request was interrupted before the node replied
+                    chainsync::State::CanAwait => interruptible(client.chainsync().recv_while_can_await(), options.request_timeout(), cancel).await,
+                    // When the client has the agency, send a request for the next block
+                    _ => interruptible(client.chainsync().request_next(), options.request_timeout(), cancel).await
+                };
+
+                let result = match result {
+                    Ok(result) => result,
+                    Err(Interrupted::Cancelled) => return (NextResponse::cancelled(), None),
+                    Err(Interrupted::TimedOut) => {
+                        println!("chain_sync_next error: request timed out");
+                        return (NextResponse::error(), None);
+                    }
+                };
+
+                match result {
+                    Ok(next) => match next {
+                        chainsync::NextResponse::RollForward(block, tip) => {
+                            let point = MultiEraBlock::decode(&block.0)
+                                .ok()
+                                .map(|b| PallasPoint::Specific(b.slot(), b.hash().to_vec()));
+
+                            let next_response = NextResponse {
+                                action: ACTION_ROLL_FORWARD,
+                                tip: Some(tip.0.into()),
+                                block_cbor: Some(block.0),
+                            };
+
+                            (next_response, point)
+                        },
+                        chainsync::NextResponse::RollBackward(point, tip) => (NextResponse {
+                            action: ACTION_ROLL_BACK,
+                            tip: Some(tip.0.into()),
+                            block_cbor: None
+                        }, Some(point)),
+                        chainsync::NextResponse::Await => (NextResponse {
+                            action: ACTION_AWAIT,
+                            tip: None,
+                            block_cbor: None,
+                        }, None),
+                    },
+                    Err(e) => {
+                        println!("chain_sync_next error: {:?}", e);
+                        (NextResponse::error(), None)
+                    }
+                }
+            },
+            Client::N2N(client) => client.next_response(options, cancel).await,
+            Client::PeerSet(peer_set) => peer_set.next_response(options, cancel).await
+        }
+    }
+
+    pub async fn find_intersect(&mut self, points: Vec<PallasPoint>) -> Result<(Option<PallasPoint>, Tip), String> {
+        match self {
+            Client::N2C(client) => client.chainsync().find_intersect(points).await.map_err(|e| format!("{:?}", e)),
+            Client::N2N(client) => client.chainsync().find_intersect(points).await.map_err(|e| format!("{:?}", e)),
+            // Also remembered as the re-intersection point for failover
+            Client::PeerSet(peer_set) => peer_set.find_intersect(points).await
+        }
+    }
+
+    // False while a chain-sync request waits for the node, which keeps the
+    // agency until it replies
+    pub fn is_idle(&mut self) -> bool {
+        let state = match self {
+            Client::N2C(client) => client.chainsync().state(),
+            client => match client.peer() {
+                Some(peer) => peer.chainsync().state(),
+                None => return true,
+            },
+        };
+
+        *state == chainsync::State::Idle
+    }
+
+    // The N2N connection running chain-sync
+    pub fn peer(&mut self) -> Option<&mut PeerConnection> {
+        match self {
+            Client::N2C(_) => None,
+            Client::N2N(client) => Some(client),
+            Client::PeerSet(peer_set) => Some(peer_set.active()),
+        }
+    }
+
+    pub async fn abort(self) {
+        match self {
+            Client::N2C(client) => client.abort().await,
+            Client::N2N(client) => client.abort().await,
+            Client::PeerSet(peer_set) => peer_set.abort().await,
+        }
+    }
+}
+
+// State behind `ClientWrapper::client_ptr`. The connection is shared with
+// background tasks, so every access goes through its lock.
+pub struct ClientState {
+    connection: Arc<Mutex<Session>>,
+    options: ClientOptions,
+    version: Arc<std::sync::Mutex<VersionInfo>>,
+    peers: Arc<std::sync::Mutex<PeerTable>>,
+    cancel: std::sync::Mutex<CancellationToken>,
+    chain_sync_stream: std::sync::Mutex<Option<ChainSyncStream>>,
+    tx_session: std::sync::Mutex<Option<TxSubmissionSession>>,
+    finality: Arc<std::sync::Mutex<FinalityBuffer>>,
+    followers: Arc<std::sync::Mutex<Followers>>,
+    sink: std::sync::Mutex<Option<SinkTask>>,
+    cursor: std::sync::Mutex<Option<CursorStore>>,
+    acked_stream: std::sync::Mutex<Option<AckedStream>>,
+}
+
+impl ClientState {
+    // Token for the call about to start. `ClientWrapper::cancel` trips the
+    // current token and installs a fresh one for later calls.
+    pub fn cancel_token(&self) -> CancellationToken {
+        self.cancel.lock().unwrap().clone()
+    }
+
+    // A stream or sink task holds the connection while chain-sync waits at
+    // the tip, other calls wait for it no longer than a request may take
+    pub async fn lock_connection(&self, cancel: &CancellationToken) -> Result<MutexGuard<'_, Session>, Interrupted> {
+        interruptible(self.connection.lock(), self.options.request_timeout(), cancel).await
+    }
+
+    // Chain-sync is consumed by a background task, pulling from it directly
+    // would take responses away from that task
+    pub fn is_streaming(&self) -> bool {
+        self.chain_sync_stream.lock().unwrap().is_some()
+            || self.sink.lock().unwrap().is_some()
+            || self.acked_stream.lock().unwrap().is_some()
+    }
+}
+
+#[derive(Net, Clone)]
 pub struct ClientWrapper {
     client: u8,
     client_ptr: usize
 }
 
 impl ClientWrapper {
+    pub fn state(&self) -> &ClientState {
+        unsafe { &*(self.client_ptr as *const ClientState) }
+    }
+
+    // `None` when the node cannot be reached or the cursor cannot be opened
+    // or resumed from
+    #[net]
+    pub fn connect(path_or_server: String, network_magic: u64, client: u8) -> Option<ClientWrapper> {
+        ClientWrapper::connect(path_or_server, network_magic, client, ClientOptions::default())
+    }
+
     #[net]
-    pub fn connect(path_or_server: String, network_magic: u64, client: u8) -> ClientWrapper {
-        ClientWrapper::connect(path_or_server, network_magic, client)
-    }
-
-    pub fn connect(path_or_server: String, network_magic: u64, client: u8) -> ClientWrapper {
-        let _client = match client {
-            1 => Client::N2C(RT.block_on(async {
-                NodeClient::connect(path_or_server, network_magic).await.unwrap()
-            })),
-            2 => Client::N2N(RT.block_on(async {
-                PeerClient::connect(path_or_server, network_magic).await.unwrap()
-            })),
-            _ => panic!("cannot establish connection: unknown client type")
+    pub fn connect_with_options(path_or_server: String, network_magic: u64, client: u8, options: ClientOptions) -> Option<ClientWrapper> {
+        ClientWrapper::connect_with_options(path_or_server, network_magic, client, options)
+    }
+
+    pub fn connect_with_options(path_or_server: String, network_magic: u64, client: u8, options: ClientOptions) -> Option<ClientWrapper> {
+        ClientWrapper::connect(path_or_server, network_magic, client, options)
+    }
+
+    pub fn connect(path_or_server: String, network_magic: u64, client: u8, options: ClientOptions) -> Option<ClientWrapper> {
+        let client = match ClientKind::try_from(client) {
+            Ok(client) => client,
+            Err(e) => {
+                println!("connect error: {}", e);
+                return None;
+            }
         };
 
-        match _client {
-            Client::N2C(node_client) => {
-                let node_client_box = Box::new(node_client);
+        let target = Target::Single {
+            path_or_server,
+            network_magic,
+            client,
+        };
 
-                let client_ptr = Box::into_raw(node_client_box) as usize;
+        ClientWrapper::new(client, target, options)
+    }
 
-                ClientWrapper { client, client_ptr }
-            },
-            Client::N2N(peer_client) => {
-                let peer_client_box = Box::new(peer_client);
+    pub fn new(client: ClientKind, target: Target, options: ClientOptions) -> Option<ClientWrapper> {
+        let peers = Arc::new(std::sync::Mutex::new(PeerTable::default()));
 
-                let client_ptr = Box::into_raw(peer_client_box) as usize;
+        let cursor = match options.cursor().map(|(path, points)| CursorStore::open(path, points)).transpose() {
+            Ok(cursor) => cursor,
+            Err(e) => {
+                println!("connect error: cannot open cursor: {}", e);
+                return None;
+            }
+        };
 
-                ClientWrapper { client, client_ptr }
+        let mut connection = match runtime::block_on(Session::connect(target, &options, peers.clone())) {
+            Ok(connected) => connected,
+            Err(e) => {
+                println!("connect error: cannot establish connection: {}", e);
+                return None;
+            }
+        };
+
+        if let Some(cursor) = &cursor {
+            if let Err(e) = runtime::block_on(cursor::resume(&mut connection, cursor, &options)) {
+                runtime::block_on(connection.abort());
+                println!("connect error: cannot resume from cursor: {}", e);
+                return None;
             }
         }
+
+        let version = connection.version();
+        let finality = connection.finality();
+        let followers = connection.followers();
+
+        let state = Box::new(ClientState {
+            connection: Arc::new(Mutex::new(connection)),
+            options,
+            version,
+            peers,
+            cancel: std::sync::Mutex::new(CancellationToken::new()),
+            chain_sync_stream: std::sync::Mutex::new(None),
+            tx_session: std::sync::Mutex::new(None),
+            finality,
+            followers,
+            sink: std::sync::Mutex::new(None),
+            cursor: std::sync::Mutex::new(cursor),
+            acked_stream: std::sync::Mutex::new(None),
+        });
+
+        let client_ptr = Box::into_raw(state) as usize;
+
+        Some(ClientWrapper {
+            client: client as u8,
+            client_ptr,
+        })
+    }
+
+    // Aborts the call currently running on this client, which then returns
+    // its cancelled result (`ACTION_CANCELLED` for chain_sync_next).
+    #[net]
+    pub fn cancel(client_wrapper: ClientWrapper) {
+        ClientWrapper::cancel(client_wrapper)
+    }
+
+    pub fn cancel(client_wrapper: ClientWrapper) {
+        let mut cancel = client_wrapper.state().cancel.lock().unwrap();
+
+        cancel.cancel();
+        *cancel = CancellationToken::new();
     }
 
     #[net]
@@ -112,86 +513,69 @@ impl ClientWrapper {
         client_wrapper: ClientWrapper,
         address: String,
     ) -> Vec<Vec<u8>> {
-        unsafe {
-            match client_wrapper.client {
-                1 => {
-                    let client_ptr = client_wrapper.client_ptr as *mut NodeClient;
-                    let mut client = Box::from_raw(client_ptr);
-    
-                    // Query Utxo by address cbor
-                    let utxos_by_address_cbor = RT.block_on(async {
-                        let client = client.statequery();
-        
-                        client.send_reacquire(None).await.unwrap();
-                        client.recv_while_acquiring().await.unwrap();
-        
-                        let era = queries_v16::get_current_era(client).await.unwrap();
-                        let addrz: Address = Address::from_bech32(&address).unwrap();
-                        let addrz: Addr = addrz.to_vec().into();
-                        let query = queries_v16::BlockQuery::GetUTxOByAddress(vec![addrz]);
-                        queries_v16::get_cbor(client, era, query).await.unwrap()
-                    });
-        
-                    // Convert client back to a raw pointer for future use
-                    let _ = Box::into_raw(client);
-        
-                    utxos_by_address_cbor
-                        .into_iter()
-                        .map(|tag_wrap_instance| tag_wrap_instance.0.deref().clone())
-                        .collect()
+        ClientWrapper::get_utxo_by_address_cbor(client_wrapper, address)
+    }
+
+    pub fn get_utxo_by_address_cbor(
+        client_wrapper: ClientWrapper,
+        address: String,
+    ) -> Vec<Vec<u8>> {
+        let state = client_wrapper.state();
+        let cancel = state.cancel_token();
+
+        // Query Utxo by address cbor
+        let utxos_by_address_cbor = runtime::block_on(interruptible(async {
+            match &mut **state.connection.lock().await {
+                Client::N2C(client) => {
+                    let client = client.statequery();
+
+                    client.send_reacquire(None).await.unwrap();
+                    client.recv_while_acquiring().await.unwrap();
+
+                    let era = queries_v16::get_current_era(client).await.unwrap();
+                    let addrz: Address = Address::from_bech32(&address).unwrap();
+                    let addrz: Addr = addrz.to_vec().into();
+                    let query = queries_v16::BlockQuery::GetUTxOByAddress(vec![addrz]);
+                    queries_v16::get_cbor(client, era, query).await.unwrap()
                 },
                 _ => panic!("unknown client type for get_utxo_by_address_cbor")
             }
+        }, state.options.request_timeout(), &cancel));
+
+        match utxos_by_address_cbor {
+            Ok(utxos_by_address_cbor) => utxos_by_address_cbor
+                .into_iter()
+                .map(|tag_wrap_instance| tag_wrap_instance.0.deref().clone())
+                .collect(),
+            Err(e) => {
+                println!("get_utxo_by_address_cbor error: {:?}", e);
+                vec![]
+            }
         }
     }
 
     #[net]
     pub fn get_tip(client_wrapper: ClientWrapper) -> Point {
-        unsafe {
-            match client_wrapper.client {
-                1 => {
-                    let client_ptr = client_wrapper.client_ptr as *mut NodeClient;
-                    let mut client = Box::from_raw(client_ptr);
-        
-                    // Get the tip using StateQuery Protocol
-                    let tip = RT.block_on(async {
-                        let state_query_client = client.statequery();
-        
-                        state_query_client.acquire(None).await.unwrap();
-        
-                        queries_v16::get_chain_point(state_query_client)
-                            .await
-                            .unwrap()
-                    });
-        
-                    // Convert client back to a raw pointer for future use
-                    let _ = Box::into_raw(client);
-        
-                    match tip {
-                        PallasPoint::Origin => Point {
-                            slot: 0,
-                            hash: vec![],
-                        },
-                        PallasPoint::Specific(slot, hash) => Point { slot, hash },
-                    }
-                },
-                2 => {
-                    let client_ptr = client_wrapper.client_ptr as *mut PeerClient;
-                    let mut client = Box::from_raw(client_ptr);
-        
-                    // Get the tip using ChainSync Protocol
-                    let tip = RT.block_on(async {
-                        client.chainsync().intersect_tip().await.unwrap()
-                    });
-        
-                    let _ = Box::into_raw(client);
-        
-                    match tip {
-                        PallasPoint::Origin => Point { slot: 0, hash: vec![] },
-                        PallasPoint::Specific(slot, hash) => Point { slot, hash }
-                    }
-                },
-                _ => panic!("unknown client type for get_tip")
+        ClientWrapper::get_tip(client_wrapper)
+    }
+
+    pub fn get_tip(client_wrapper: ClientWrapper) -> Point {
+        let state = client_wrapper.state();
+        let cancel = state.cancel_token();
+
+        let tip = runtime::block_on(async {
+            let mut connection = state.lock_connection(&cancel).await.map_err(|e| format!("{:?}", e))?;
+
+            interruptible(connection.tip(), state.options.request_timeout(), &cancel)
+                .await
+                .map_err(|e| format!("{:?}", e))?
+        });
+
+        match tip {
+            Ok(tip) => tip.into(),
+            Err(e) => {
+                println!("get_tip error: {}", e);
+                PallasPoint::Origin.into()
             }
         }
     }
@@ -202,338 +586,310 @@ impl ClientWrapper {
     }
 
     pub fn find_intersect(client_wrapper: ClientWrapper, known_point: Point) -> Option<Point> {
-        unsafe {
-            match client_wrapper.client {
-                1 => {
-                    let client_ptr = client_wrapper.client_ptr as *mut NodeClient;
-
-                    // Convert the raw pointer back to a Box to deallocate the memory
-                    let mut _client = Box::from_raw(client_ptr);
-
-                    let client = _client.chainsync();
-        
-                    let known_points = vec![PallasPoint::Specific(known_point.slot, known_point.hash)];
-        
-                    // Get the intersecting point and the tip
-                    let (intersect_point, _tip) =
-                        RT.block_on(async { client.find_intersect(known_points).await.unwrap() });
-        
-                    // Convert client back to a raw pointer for future use
-                    let _ = Box::into_raw(_client);
-        
-                    // Match on the intersecting point
-                    intersect_point.map(|pallas_point| match pallas_point {
-                        PallasPoint::Origin => Point {
-                            slot: 0,
-                            hash: vec![],
-                        },
-                        PallasPoint::Specific(slot, hash) => Point { slot, hash },
-                    })
-                },
-                2 => {
-                    let client_ptr = client_wrapper.client_ptr as *mut PeerClient;
-                    let mut client = Box::from_raw(client_ptr);
-        
-                    let known_points = vec![PallasPoint::Specific(known_point.slot, known_point.hash)];
-        
-                    let (intersect_point, _) = RT.block_on(async {
-                        client.chainsync().find_intersect(known_points).await.unwrap()
-                    });
-        
-                    let _ = Box::into_raw(client);
-        
-                    intersect_point.map(|pallas_point| match pallas_point {
-                        PallasPoint::Origin => Point {
-                            slot: 0,
-                            hash: vec![],
-                        },
-                        PallasPoint::Specific(slot, hash) => Point { slot, hash },
-                    })
-                },
-                _ => panic!("unknown client type for find_intersect")
+        let state = client_wrapper.state();
+        let cancel = state.cancel_token();
+
+        let known_points = vec![PallasPoint::Specific(known_point.slot, known_point.hash)];
+
+        // Get the intersecting point and the tip
+        let intersect = runtime::block_on(async {
+            let mut connection = state.lock_connection(&cancel).await.map_err(|e| format!("{:?}", e))?;
+
+            interruptible(connection.find_intersect(known_points), state.options.request_timeout(), &cancel)
+                .await
+                .map_err(|e| format!("{:?}", e))?
+        });
+
+        match intersect {
+            Ok((intersect_point, _tip)) => intersect_point.map(Point::from),
+            Err(e) => {
+                println!("find_intersect error: {}", e);
+                None
             }
         }
     }
 
     #[net]
     pub fn chain_sync_next(client_wrapper: ClientWrapper) -> NextResponse {
-        unsafe {
-            match client_wrapper.client {
-                1 => {
-                    let client_ptr = client_wrapper.client_ptr as *mut NodeClient;
-                    let mut client = Box::from_raw(client_ptr);
-        
-                    // Get the next block
-                    let result = RT.block_on(async {
-                        if client.chainsync().has_agency() {
-                            // When the client has the agency, send a request for the next block
-                            client.chainsync().request_next().await
-                        } else {
-                            // When the client does not have the agency, wait for the server's response
-                            client.chainsync().recv_while_must_reply().await
-                        }
-                    });
-        
-                    let next_response = match result {
-                        Ok(next) => match next {
-                            chainsync::NextResponse::RollForward(block, tip) => NextResponse {
-                                action: 1,
-                                tip: match tip.0 {
-                                    PallasPoint::Origin => Some(Point {
-                                        slot: 0,
-                                        hash: vec![],
-                                    }),
-                                    PallasPoint::Specific(slot, hash) => Some(Point { slot, hash }),
-                                },
-                                block_cbor: Some(block.0),
-                            },
-                            chainsync::NextResponse::RollBackward(_, tip) => NextResponse {
-                                action: 2,
-                                tip: match tip.0 {
-                                    PallasPoint::Origin => Some(Point {
-                                        slot: 0,
-                                        hash: vec![],
-                                    }),
-                                    PallasPoint::Specific(slot, hash) => Some(Point { slot, hash }),
-                                },
-                                block_cbor: None
-                            },
-                            chainsync::NextResponse::Await => NextResponse {
-                                action: 3,
-                                tip: None,
-                                block_cbor: None,
-                            },
-                        },
-                        Err(e) => {
-                            println!("chain_sync_next error: {:?}", e);
-                            NextResponse {
-                                action: 0,
-                                tip: None,
-                                block_cbor: None,
-                            }
-                        }
-                    };
-        
-                    // Convert client back to a raw pointer for future use
-                    let _ = Box::into_raw(client);
-        
-                    next_response
-                },
-                2 => {
-                    let client_ptr = client_wrapper.client_ptr as *mut PeerClient;
-                    let mut client = Box::from_raw(client_ptr);
-        
-                    // Get the next block
-                    let result = RT.block_on(async {
-                        if client.chainsync().has_agency() {
-                            // When the client has the agency, send a request for the next block
-                            client.chainsync().request_next().await
-                        } else {
-                            // When the client does not have the agency, wait for the server's response
-                            client.chainsync().recv_while_must_reply().await
-                        }
-                    });
-        
-                    let next_response = match result {
-                        Ok(next) => match next {
-                            chainsync::NextResponse::RollForward(header, tip) => match MultiEraHeader::decode(header.variant, None, &header.cbor) {
-                                Ok(h) => NextResponse {
-                                    action: 1,
-                                    tip: match tip.0 {
-                                        PallasPoint::Origin => Some(Point {
-                                            slot: 0,
-                                            hash: vec![]
-                                        }),
-                                        PallasPoint::Specific(slot, hash) => Some(Point { slot, hash })
-                                    },
-                                    block_cbor: ClientWrapper::fetch_block(&mut client.blockfetch, Point {
-                                        slot: h.slot(),
-                                        hash: h.hash().to_vec()
-                                    })
-                                },
-                                Err(e) => {
-                                    println!("chain_sync_next error: {:?}", e);
-                                    NextResponse {
-                                        action: 0,
-                                        tip: None,
-                                        block_cbor: None
-                                    }
-                                }
-                            },
-                            chainsync::NextResponse::RollBackward(point, tip) => NextResponse {
-                                action: 2,
-                                tip: match tip.0 {
-                                    PallasPoint::Origin => Some(Point {
-                                        slot: 0,
-                                        hash: vec![],
-                                    }),
-                                    PallasPoint::Specific(slot, hash) => Some(Point { slot, hash}),
-                                },
-                                block_cbor: match point {
-                                    PallasPoint::Origin => ClientWrapper::fetch_block(&mut client.blockfetch, Point { slot: 0, hash: vec![] }),
-                                    PallasPoint::Specific(slot, hash) => ClientWrapper::fetch_block(&mut client.blockfetch, Point { slot, hash })
-                                }
-                            },
-                            chainsync::NextResponse::Await => NextResponse {
-                                action: 3,
-                                tip: None,
-                                block_cbor: None
-                            }
-                        },
-                        Err(e) => {
-                            println!("chain_sync_next error: {:?}", e);
-                            NextResponse {
-                                action: 0,
-                                tip: None,
-                                block_cbor: None,
-                            }
-                        }
-                    };
-        
-                    let _ = Box::into_raw(client);
-        
-                    next_response
-                },
-                _ => panic!("unknown client type for chain_sync_next")
-            }
+        ClientWrapper::chain_sync_next(client_wrapper)
+    }
+
+    pub fn chain_sync_next(client_wrapper: ClientWrapper) -> NextResponse {
+        let state = client_wrapper.state();
+        let cancel = state.cancel_token();
+
+        if state.is_streaming() {
+            println!("chain_sync_next error: chain-sync stream or sink is running");
+            return NextResponse::error();
         }
+
+        runtime::block_on(async {
+            // Timeouts are applied per protocol step inside next_response
+            let mut connection = match interruptible(state.connection.lock(), None, &cancel).await {
+                Ok(connection) => connection,
+                Err(_) => return NextResponse::cancelled()
+            };
+
+            connection.next_response(&state.options, &cancel).await
+        })
     }
 
+    // Up to `max_count` responses in a single call, sparing the per-call
+    // overhead while catching up. Stops after any response other than a roll
+    // forward or rollback, or once `max_wait_ms` have passed since the call
+    // (0 for no limit). A request still waiting at the deadline is dropped
+    // and resumed by the next call, which can leave the batch empty. An error
+    // or cancellation ends the batch.
     #[net]
-    pub fn disconnect(client_wrapper: ClientWrapper) {
-        unsafe {
-            match client_wrapper.client {
-                1 => {
-                    let client_ptr = client_wrapper.client_ptr as *mut NodeClient;
-        
-                    let mut _client = Box::from_raw(client_ptr);
-        
-                    RT.block_on(async {
-                        _client.abort().await;
-                    });
-                },
-                2 => {
-                    let client_ptr = client_wrapper.client_ptr as *mut PeerClient;
-        
-                    let mut _client = Box::from_raw(client_ptr);
-        
-                    RT.block_on(async {
-                        _client.abort().await;
-                    });
+    pub fn chain_sync_next_batch(client_wrapper: ClientWrapper, max_count: u64, max_wait_ms: u64) -> NextBatch {
+        ClientWrapper::chain_sync_next_batch(client_wrapper, max_count, max_wait_ms)
+    }
+
+    pub fn chain_sync_next_batch(client_wrapper: ClientWrapper, max_count: u64, max_wait_ms: u64) -> NextBatch {
+        let state = client_wrapper.state();
+        let cancel = state.cancel_token();
+        let deadline = (max_wait_ms > 0).then(|| Instant::now() + Duration::from_millis(max_wait_ms));
+        let remaining = || deadline.map(|deadline| deadline.saturating_duration_since(Instant::now()));
+
+        let mut batch = NextBatch::default();
+
+        if state.is_streaming() {
+            println!("chain_sync_next_batch error: chain-sync stream or sink is running");
+            batch.push(NextResponse::error());
+            return batch;
+        }
+
+        runtime::block_on(async {
+            let mut connection = match interruptible(state.connection.lock(), remaining(), &cancel).await {
+                Ok(connection) => connection,
+                Err(Interrupted::Cancelled) => {
+                    batch.push(NextResponse::cancelled());
+                    return batch;
+                }
+                Err(Interrupted::TimedOut) => return batch,
+            };
+
+            while batch.responses.len() < max_count.max(1) as usize {
+                let next_response = match interruptible(connection.next_response(&state.options, &cancel), remaining(), &cancel).await {
+                    Ok(next_response) => next_response,
+                    Err(Interrupted::Cancelled) => NextResponse::cancelled(),
+                    Err(Interrupted::TimedOut) => break,
+                };
+                let more = matches!(next_response.action, ACTION_ROLL_FORWARD | ACTION_ROLL_BACK);
+
+                batch.push(next_response);
+
+                if !more {
+                    break;
                 }
-                _ => panic!("unknown client type for disconnect")
             }
+
+            batch
+        })
+    }
+
+    #[net]
+    pub fn disconnect(client_wrapper: ClientWrapper) {
+        ClientWrapper::disconnect(client_wrapper)
+    }
+
+    pub fn disconnect(client_wrapper: ClientWrapper) {
+        let state = unsafe { Box::from_raw(client_wrapper.client_ptr as *mut ClientState) };
+
+        // A call still running on another thread returns instead of keeping
+        // the connection
+        state.cancel.lock().unwrap().cancel();
+
+        if let Some(stream) = state.chain_sync_stream.lock().unwrap().take() {
+            runtime::block_on(stream.stop());
+        }
+
+        if let Some(sink) = state.sink.lock().unwrap().take() {
+            runtime::block_on(sink.stop());
+        }
+
+        // Unacknowledged responses are delivered again by the cursor, if any
+        if let Some(stream) = state.acked_stream.lock().unwrap().take() {
+            runtime::block_on(stream.stop());
+        }
+
+        if let Some(tx_session) = state.tx_session.lock().unwrap().take() {
+            tx_session.stop();
+        }
+
+        // Otherwise the connection closes once the last cancelled call
+        // releases it
+        if let Ok(connection) = Arc::try_unwrap(state.connection) {
+            runtime::block_on(connection.into_inner().abort());
         }
     }
 
     #[net]
     pub fn fetch_block(client_wrapper: ClientWrapper, point: Point) -> Option<Vec<u8>> {
-        unsafe {
-            match client_wrapper.client {
-                2 => {
-                    let client_ptr = client_wrapper.client_ptr as *mut PeerClient;
-                    let mut client = Box::from_raw(client_ptr);
-        
-                    let block = ClientWrapper::fetch_block(&mut client.blockfetch, point);
+        ClientWrapper::fetch_block(client_wrapper, point)
+    }
 
-                    let _ = Box::into_raw(client);
+    pub fn fetch_block(client_wrapper: ClientWrapper, point: Point) -> Option<Vec<u8>> {
+        let state = client_wrapper.state();
+        let cancel = state.cancel_token();
+
+        let block = runtime::block_on(interruptible(async {
+            match state.connection.lock().await.peer() {
+                Some(client) => ClientWrapper::fetch_single_block(
+                    &mut client.blockfetch,
+                    PallasPoint::Specific(point.slot, point.hash),
+                ).await,
+                None => panic!("unkown client type for fetch_block")
+            }
+        }, state.options.request_timeout(), &cancel));
 
-                    block
-                },
-                _ => panic!("unkown client type for fetch_block")
+        block.unwrap_or_else(|e| {
+            println!("fetch_block error: {:?}", e);
+            None
+        })
+    }
+
+    pub async fn fetch_single_block(block_fetch_client: &mut blockfetch::Client, point: PallasPoint) -> Option<Vec<u8>> {
+        match block_fetch_client.fetch_single(point).await {
+            Ok(block) => Some(block),
+            Err(e) => {
+                println!("fetch_block error: {:?}", e);
+                None
             }
         }
     }
 
-    pub fn fetch_block(block_fetch_client: &mut blockfetch::Client, point: Point) -> Option<Vec<u8>> {
-        Some(RT.block_on(async {
-            block_fetch_client.fetch_single(PallasPoint::Specific(point.slot, point.hash)).await.unwrap()
-        }))
+    #[net]
+    pub fn submit_tx(server: String, magic: u64, tx: Vec<u8>) -> TxSubmissionOutcome {
+        ClientWrapper::submit_tx(server, magic, tx)
+    }
+
+    pub fn submit_tx(server: String, magic: u64, tx: Vec<u8>) -> TxSubmissionOutcome {
+        ClientWrapper::submit_txs(server, magic, vec![tx])
     }
 
     #[net]
-    pub fn submit_tx(server: String, magic: u64, tx: Vec<u8>) -> Vec<u8> {
-        ClientWrapper::submit_tx(server, magic, tx)
+    pub fn submit_txs(server: String, magic: u64, txs: Vec<Vec<u8>>) -> TxSubmissionOutcome {
+        ClientWrapper::submit_txs(server, magic, txs)
     }
 
-    pub fn submit_tx(server: String, magic: u64, tx: Vec<u8>) -> Vec<u8> {
-        let ids = RT.block_on(async {
-            let tx_clone = tx.clone();
-            let multi_era_tx = MultiEraTx::decode(&tx_clone).unwrap();
-            let tx_era = multi_era_tx.era() as u16;
-            let mempool = vec![(multi_era_tx.hash(), tx.clone())];
-            let mut peer = PeerClient::connect(server, magic).await.unwrap();
-            let client_txsub = peer.txsubmission();
+    pub fn submit_txs(server: String, magic: u64, txs: Vec<Vec<u8>>) -> TxSubmissionOutcome {
+        let mut mempool = Mempool::default();
 
-            client_txsub.send_init().await.unwrap();
+        for tx in txs {
+            if let Err(e) = mempool.push(tx) {
+                return mempool.outcome(Some(e));
+            }
+        }
 
-            let _ = match client_txsub.next_request().await.unwrap() {
-                txsubmission::Request::TxIds(ack, _) => {
-                    assert_eq!(*client_txsub.state(), txsubmission::State::TxIdsBlocking);
-                    ack
-                }
-                txsubmission::Request::TxIdsNonBlocking(ack, _) => {
-                    assert_eq!(*client_txsub.state(), txsubmission::State::TxIdsNonBlocking);
-                    ack
+        runtime::block_on(async {
+            let mut peer = match PeerClient::connect(server, magic).await {
+                Ok(peer) => peer,
+                Err(e) => {
+                    mempool.fail_pending(TxStatus::ProtocolError);
+                    return mempool.outcome(Some(format!("{:?}", e)));
                 }
-                _ => panic!("unexpected message"),
             };
 
-            let to_send = mempool.clone();
-            let ids_and_size = to_send
-                .clone()
-                .into_iter()
-                .map(|(h, b)| {
-                    TxIdAndSize(txsubmission::EraTxId(tx_era, h.to_vec()), b.len() as u32)
-                })
-                .collect();
+            let result = submission::submit_all(peer.txsubmission(), &mut mempool, TX_SUBMISSION_TIMEOUT).await;
 
-            client_txsub.reply_tx_ids(ids_and_size).await.unwrap();
+            peer.abort().await;
 
-            let ids = match client_txsub.next_request().await.unwrap() {
-                txsubmission::Request::Txs(ids) => ids,
-                _ => panic!("unexpected message"),
-            };
+            match result {
+                Ok(()) => mempool.outcome(None),
+                Err(ServeError::Timeout) => {
+                    mempool.fail_pending(TxStatus::Timeout);
+                    mempool.outcome(Some("timed out waiting for the peer".to_string()))
+                }
+                Err(ServeError::Protocol(e)) => {
+                    mempool.fail_pending(TxStatus::ProtocolError);
+                    mempool.outcome(Some(e))
+                }
+            }
+        })
+    }
 
-            let txs_to_send: Vec<_> = to_send
-                .into_iter()
-                .map(|(_, b)| EraTxBody(tx_era, b))
-                .collect();
-            client_txsub.reply_txs(txs_to_send).await.unwrap();
+    // Starts a background tx submission session on a N2N connection, or
+    // keeps the running one. Returns false for N2C connections and once a
+    // stopped session consumed the connection's mini-protocol client.
+    #[net]
+    pub fn start_tx_submission(client_wrapper: ClientWrapper) -> bool {
+        ClientWrapper::start_tx_submission(client_wrapper)
+    }
 
-            match client_txsub.next_request().await.unwrap() {
-                txsubmission::Request::TxIdsNonBlocking(_, _) => {
-                    assert_eq!(*client_txsub.state(), txsubmission::State::TxIdsNonBlocking);
+    pub fn start_tx_submission(client_wrapper: ClientWrapper) -> bool {
+        let state = client_wrapper.state();
+        let mut tx_session = state.tx_session.lock().unwrap();
+
+        if tx_session.is_none() {
+            let txsubmission = match state.connection.blocking_lock().peer() {
+                Some(client) => client.txsubmission.take(),
+                None => {
+                    println!("start_tx_submission error: tx submission needs a N2N connection");
+                    return false;
                 }
-                _ => panic!("unexpected message"),
             };
 
-            client_txsub.reply_tx_ids(vec![]).await.unwrap();
+            let Some(txsubmission) = txsubmission else {
+                println!("start_tx_submission error: the tx submission client was consumed by a stopped session");
+                return false;
+            };
 
-            match client_txsub.next_request().await.unwrap() {
-                txsubmission::Request::TxIds(ack, _) => {
-                    assert_eq!(*client_txsub.state(), txsubmission::State::TxIdsBlocking);
+            *tx_session = Some(TxSubmissionSession::start(txsubmission));
+        }
 
-                    client_txsub.send_done().await.unwrap();
-                    assert_eq!(*client_txsub.state(), txsubmission::State::Done);
+        true
+    }
 
-                    ack
-                }
-                txsubmission::Request::TxIdsNonBlocking(ack, _) => {
-                    assert_eq!(*client_txsub.state(), txsubmission::State::TxIdsNonBlocking);
+    // Returns the tx id, or None when no session is running, the tx is
+    // invalid or the session already holds TX_SESSION_CAPACITY txs the peer
+    // has not acknowledged
+    #[net]
+    pub fn enqueue_tx(client_wrapper: ClientWrapper, tx: Vec<u8>) -> Option<Vec<u8>> {
+        ClientWrapper::enqueue_tx(client_wrapper, tx)
+    }
 
-                    ack
-                }
-                _ => panic!("unexpected message"),
-            };
+    pub fn enqueue_tx(client_wrapper: ClientWrapper, tx: Vec<u8>) -> Option<Vec<u8>> {
+        let result = match &*client_wrapper.state().tx_session.lock().unwrap() {
+            Some(tx_session) => tx_session.enqueue(tx),
+            None => Err("tx submission session not started".to_string()),
+        };
 
-            let id_bytes = ids
-                .iter()
-                .flat_map(|id| id.1.to_vec()) // Assuming `Hash<32>` is a tuple struct with the first element being an array `[u8; 32]`
-                .collect();
-            id_bytes
-        });
-        ids
+        match result {
+            Ok(tx_id) => Some(tx_id),
+            Err(e) => {
+                println!("enqueue_tx error: {}", e);
+                None
+            }
+        }
+    }
+
+    #[net]
+    pub fn get_tx_submission_status(client_wrapper: ClientWrapper) -> TxSubmissionOutcome {
+        ClientWrapper::get_tx_submission_status(client_wrapper)
+    }
+
+    pub fn get_tx_submission_status(client_wrapper: ClientWrapper) -> TxSubmissionOutcome {
+        match &*client_wrapper.state().tx_session.lock().unwrap() {
+            Some(tx_session) => tx_session.outcome(),
+            None => TxSubmissionOutcome {
+                results: vec![],
+                error: Some("tx submission session not started".to_string()),
+            },
+        }
+    }
+
+    #[net]
+    pub fn stop_tx_submission(client_wrapper: ClientWrapper) {
+        ClientWrapper::stop_tx_submission(client_wrapper)
+    }
+
+    pub fn stop_tx_submission(client_wrapper: ClientWrapper) {
+        // The mini-protocol client is consumed by the session and
+        // cannot be restarted on this connection
+        if let Some(tx_session) = client_wrapper.state().tx_session.lock().unwrap().take() {
+            tx_session.stop();
+        }
     }
 }
 
@@ -542,6 +898,10 @@ pub struct PallasUtility {}
 
 impl PallasUtility {
     #[net]
+    pub fn address_bytes_to_bech32(address_bytes: Vec<u8>) -> String {
+        PallasUtility::address_bytes_to_bech32(address_bytes)
+    }
+
     pub fn address_bytes_to_bech32(address_bytes: Vec<u8>) -> String {
         match Address::from_bytes(&address_bytes).unwrap().to_bech32() {
             Ok(address) => address,
@@ -550,4 +910,4 @@ impl PallasUtility {
                 .to_base58(),
         }
     }
-}
\ No newline at end of file
+}
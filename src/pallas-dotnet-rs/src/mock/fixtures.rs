@@ -0,0 +1,349 @@
+use std::collections::BTreeMap;
+
+use pallas::{
+    codec::minicbor::{encode, Encoder},
+    crypto::{hash::Hasher, key::ed25519::SecretKey},
+    network::miniprotocols::Point as PallasPoint,
+};
+use uplc::ast::{DeBruijn, Program};
+
+// Era tag of a Babbage block inside the `[era, block]` envelope
+const BABBAGE_BLOCK_TAG: u64 = 6;
+// Babbage variant of a N2N chain-sync header
+pub const BABBAGE_HEADER_VARIANT: u8 = 5;
+// Ledger era index answered to GetCurrentEra
+pub const BABBAGE_ERA: u16 = 5;
+
+// Traced by `always_succeeds_script()`
+pub const ALWAYS_SUCCEEDS_TRACE: &str = "always succeeds";
+
+type Result = std::result::Result<(), encode::Error<std::convert::Infallible>>;
+
+#[derive(Clone)]
+pub struct MockBlock {
+    pub number: u64,
+    pub slot: u64,
+    pub hash: Vec<u8>,
+    pub header_cbor: Vec<u8>,
+    // `[era, block]` as served by chain-sync (N2C) and block-fetch (N2N)
+    pub block_cbor: Vec<u8>,
+}
+
+impl MockBlock {
+    pub fn point(&self) -> PallasPoint {
+        PallasPoint::Specific(self.slot, self.hash.clone())
+    }
+}
+
+fn header_body(e: &mut Encoder<Vec<u8>>, number: u64, slot: u64, prev_hash: Option<&[u8]>) -> Result {
+    e.array(10)?;
+    e.u64(number)?.u64(slot)?;
+
+    match prev_hash {
+        Some(hash) => e.bytes(hash)?,
+        None => e.null()?,
+    };
+
+    // issuer and vrf keys, vrf result
+    e.bytes(&[0; 32])?.bytes(&[0; 32])?;
+    e.array(2)?.bytes(&[0; 64])?.bytes(&[0; 80])?;
+
+    // body size and hash are not checked by the client
+    e.u64(0)?.bytes(&[0; 32])?;
+
+    // operational certificate and protocol version
+    e.array(4)?.bytes(&[0; 32])?.u64(0)?.u64(0)?.bytes(&[0; 64])?;
+    e.array(2)?.u64(8)?.u64(0)?;
+
+    Ok(())
+}
+
+fn header(number: u64, slot: u64, prev_hash: Option<&[u8]>) -> Vec<u8> {
+    let mut e = Encoder::new(vec![]);
+
+    e.array(2).unwrap();
+    header_body(&mut e, number, slot, prev_hash).unwrap();
+    // KES signature
+    e.bytes(&[0; 448]).unwrap();
+
+    e.into_writer()
+}
+
+// Babbage tx body, the witness set is left empty
+#[derive(Clone, Default)]
+pub struct MockTx {
+    // (tx hash, index)
+    pub inputs: Vec<(Vec<u8>, u64)>,
+    // Encoded with `output()`
+    pub outputs: Vec<Vec<u8>>,
+    pub collateral: Vec<(Vec<u8>, u64)>,
+    pub collateral_return: Option<Vec<u8>>,
+    // Listed among the invalid txs of the block, as after a failed phase-2
+    // validation
+    pub invalid: bool,
+    // Labels of the auxiliary data, each with an empty string
+    pub metadata: Vec<u64>,
+}
+
+impl MockTx {
+    pub fn body(&self) -> Vec<u8> {
+        let mut e = Encoder::new(vec![]);
+        let inputs = |e: &mut Encoder<Vec<u8>>, inputs: &[(Vec<u8>, u64)]| {
+            e.array(inputs.len() as u64).unwrap();
+
+            for (hash, index) in inputs {
+                e.array(2).unwrap().bytes(hash).unwrap().u64(*index).unwrap();
+            }
+        };
+
+        let fields = 3 + u64::from(!self.collateral.is_empty()) + u64::from(self.collateral_return.is_some());
+        e.map(fields).unwrap();
+
+        e.u8(0).unwrap();
+        inputs(&mut e, &self.inputs);
+
+        e.u8(1).unwrap().array(self.outputs.len() as u64).unwrap();
+        for output in &self.outputs {
+            e.writer_mut().extend_from_slice(output);
+        }
+
+        e.u8(2).unwrap().u64(170_000).unwrap();
+
+        if !self.collateral.is_empty() {
+            e.u8(13).unwrap();
+            inputs(&mut e, &self.collateral);
+        }
+
+        if let Some(output) = &self.collateral_return {
+            e.u8(16).unwrap();
+            e.writer_mut().extend_from_slice(output);
+        }
+
+        e.into_writer()
+    }
+
+    pub fn hash(&self) -> Vec<u8> {
+        Hasher::<256>::hash(&self.body()).to_vec()
+    }
+}
+
+// Babbage block holding `txs`. The header is embedded verbatim so the hash
+// computed by the client matches the one of the header.
+fn block(header_cbor: &[u8], txs: &[MockTx]) -> Vec<u8> {
+    let mut e = Encoder::new(vec![]);
+
+    e.array(2).unwrap().u64(BABBAGE_BLOCK_TAG).unwrap();
+    e.array(5).unwrap();
+    e.writer_mut().extend_from_slice(header_cbor);
+
+    e.array(txs.len() as u64).unwrap();
+    for tx in txs {
+        e.writer_mut().extend_from_slice(&tx.body());
+    }
+
+    // witness sets, auxiliary data and invalid txs
+    e.array(txs.len() as u64).unwrap();
+    for _ in txs {
+        e.map(0).unwrap();
+    }
+
+    let with_metadata: Vec<(usize, &MockTx)> = txs.iter().enumerate().filter(|(_, tx)| !tx.metadata.is_empty()).collect();
+    e.map(with_metadata.len() as u64).unwrap();
+    for (i, tx) in with_metadata {
+        e.u64(i as u64).unwrap().map(tx.metadata.len() as u64).unwrap();
+
+        for label in &tx.metadata {
+            e.u64(*label).unwrap().str("").unwrap();
+        }
+    }
+
+    let invalid: Vec<usize> = txs.iter().enumerate().filter(|(_, tx)| tx.invalid).map(|(i, _)| i).collect();
+    e.array(invalid.len() as u64).unwrap();
+    for i in invalid {
+        e.u64(i as u64).unwrap();
+    }
+
+    e.into_writer()
+}
+
+// Linear chain of `count` blocks, one every 20 slots starting at slot 100
+pub fn chain(count: u64) -> Vec<MockBlock> {
+    chain_with(vec![vec![]; count as usize])
+}
+
+// Same as `chain`, block `i` holding `txs[i]`
+pub fn chain_with(txs: Vec<Vec<MockTx>>) -> Vec<MockBlock> {
+    let mut blocks: Vec<MockBlock> = vec![];
+
+    for (number, txs) in (1..).zip(txs) {
+        let slot = 80 + number * 20;
+        let prev_hash = blocks.last().map(|b| b.hash.clone());
+        let header_cbor = header(number, slot, prev_hash.as_deref());
+
+        blocks.push(MockBlock {
+            number,
+            slot,
+            hash: Hasher::<256>::hash(&header_cbor).to_vec(),
+            block_cbor: block(&header_cbor, &txs),
+            header_cbor,
+        });
+    }
+
+    blocks
+}
+
+// Enterprise address on a test network paying to an all-zero key hash
+pub fn address() -> Vec<u8> {
+    key_address(0)
+}
+
+// Enterprise address on a test network paying to a key hash filled with `key`
+pub fn key_address(key: u8) -> Vec<u8> {
+    let mut address = vec![0x60];
+    address.extend_from_slice(&[key; 28]);
+    address
+}
+
+// Legacy output paying `coin` lovelace and one unit of each (policy, name)
+pub fn output(address: &[u8], coin: u64, assets: &[(&[u8], &[u8])]) -> Vec<u8> {
+    let mut e = Encoder::new(vec![]);
+
+    e.array(2).unwrap().bytes(address).unwrap();
+
+    if assets.is_empty() {
+        e.u64(coin).unwrap();
+        return e.into_writer();
+    }
+
+    let mut policies: BTreeMap<&[u8], Vec<&[u8]>> = BTreeMap::new();
+    for (policy, name) in assets {
+        policies.entry(*policy).or_default().push(*name);
+    }
+
+    e.array(2).unwrap().u64(coin).unwrap().map(policies.len() as u64).unwrap();
+
+    for (policy, names) in policies {
+        e.bytes(policy).unwrap().map(names.len() as u64).unwrap();
+
+        for name in names {
+            e.bytes(name).unwrap().u64(1).unwrap();
+        }
+    }
+
+    e.into_writer()
+}
+
+// Babbage tx spending `index` of an all-zero tx hash to `address()`
+pub fn tx(index: u64) -> Vec<u8> {
+    let mut e = Encoder::new(vec![]);
+
+    e.array(4).unwrap();
+
+    e.map(3).unwrap();
+    e.u8(0).unwrap().array(1).unwrap().array(2).unwrap().bytes(&[0; 32]).unwrap().u64(index).unwrap();
+    e.u8(1).unwrap().array(1).unwrap().array(2).unwrap().bytes(&address()).unwrap().u64(2_000_000).unwrap();
+    e.u8(2).unwrap().u64(170_000).unwrap();
+
+    // empty witness set, valid, no auxiliary data
+    e.map(0).unwrap().bool(true).unwrap().null().unwrap();
+
+    e.into_writer()
+}
+
+// Key signing `signed_tx`
+fn signing_key() -> SecretKey {
+    SecretKey::from([1; 32])
+}
+
+// Enterprise address on a test network paying to `signing_key()`
+pub fn signed_address() -> Vec<u8> {
+    let mut address = vec![0x60];
+    address.extend_from_slice(Hasher::<224>::hash(signing_key().public_key().as_ref()).as_ref());
+    address
+}
+
+// Babbage tx spending `index` of an all-zero tx hash paid to
+// `signed_address()`, paying `coin` to `address()` and `fee`. Signed by its
+// key, so it passes phase-1 validation when the input holds coin + fee.
+pub fn signed_tx(index: u64, coin: u64, fee: u64) -> Vec<u8> {
+    let mut body = Encoder::new(vec![]);
+
+    body.map(3).unwrap();
+    body.u8(0).unwrap().array(1).unwrap().array(2).unwrap().bytes(&[0; 32]).unwrap().u64(index).unwrap();
+    body.u8(1).unwrap().array(1).unwrap().array(2).unwrap().bytes(&address()).unwrap().u64(coin).unwrap();
+    body.u8(2).unwrap().u64(fee).unwrap();
+
+    let body = body.into_writer();
+    let key = signing_key();
+    let signature = key.sign(Hasher::<256>::hash(&body));
+
+    let mut e = Encoder::new(vec![]);
+
+    e.array(4).unwrap();
+    e.writer_mut().extend_from_slice(&body);
+
+    // vkey witnesses as [vkey, signature]
+    e.map(1).unwrap().u8(0).unwrap().array(1).unwrap();
+    e.array(2).unwrap().bytes(key.public_key().as_ref()).unwrap().bytes(signature.as_ref()).unwrap();
+
+    e.bool(true).unwrap().null().unwrap();
+
+    e.into_writer()
+}
+
+// Plutus V3 script accepting any script context after tracing `ALWAYS_SUCCEEDS_TRACE`,
+// as a witness set carries it
+pub fn always_succeeds_script() -> Vec<u8> {
+    let source = format!(
+        "(program 1.1.0 (lam ctx [(force (builtin trace)) (con string \"{}\") (con unit ())]))",
+        ALWAYS_SUCCEEDS_TRACE
+    );
+    let program: Program<DeBruijn> = uplc::parser::program(&source)
+        .unwrap()
+        .try_into()
+        .unwrap();
+
+    program.to_cbor().unwrap()
+}
+
+// Enterprise address on a test network locked by `always_succeeds_script()`
+pub fn plutus_script_address() -> Vec<u8> {
+    let mut address = vec![0x70];
+    address.extend_from_slice(Hasher::<224>::hash_tagged(&always_succeeds_script(), 3).as_ref());
+    address
+}
+
+// Tx spending `index` of an all-zero tx hash locked by
+// `always_succeeds_script()`, with a spend redeemer on no ex-units
+pub fn plutus_tx(index: u64) -> Vec<u8> {
+    let mut e = Encoder::new(vec![]);
+
+    e.array(4).unwrap();
+
+    e.map(3).unwrap();
+    e.u8(0).unwrap().array(1).unwrap().array(2).unwrap().bytes(&[0; 32]).unwrap().u64(index).unwrap();
+    e.u8(1).unwrap().array(1).unwrap().array(2).unwrap().bytes(&address()).unwrap().u64(2_000_000).unwrap();
+    e.u8(2).unwrap().u64(200_000).unwrap();
+
+    // redeemers as [tag, index, data, ex-units], scripts
+    e.map(2).unwrap();
+    e.u8(5).unwrap().array(1).unwrap();
+    e.array(4).unwrap().u8(0).unwrap().u64(0).unwrap().u8(0).unwrap();
+    e.array(2).unwrap().u64(0).unwrap().u64(0).unwrap();
+    e.u8(7).unwrap().array(1).unwrap().bytes(&always_succeeds_script()).unwrap();
+
+    e.bool(true).unwrap().null().unwrap();
+
+    e.into_writer()
+}
+
+// Result of GetUTxOByAddress with a single output at `address()`
+pub fn utxo() -> Vec<u8> {
+    let mut e = Encoder::new(vec![]);
+
+    e.map(1).unwrap();
+    e.array(2).unwrap().bytes(&[0; 32]).unwrap().u64(0).unwrap();
+    e.array(2).unwrap().bytes(&address()).unwrap().u64(2_000_000).unwrap();
+
+    e.into_writer()
+}
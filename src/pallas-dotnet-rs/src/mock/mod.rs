@@ -0,0 +1,494 @@
+// In-process node serving a scripted chain through pallas' server facades,
+// used by the test suite in place of a live cardano-node.
+
+use std::{
+    future::pending,
+    net::Ipv4Addr,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use pallas::{
+    codec::utils::{AnyCbor, Bytes, TagWrap},
+    network::{
+        facades::NodeServer,
+        miniprotocols::{
+            blockfetch::{self, BlockRequest},
+            chainsync::{self, BlockContent, ClientRequest, HeaderContent, Tip},
+            keepalive,
+            localstate::{
+                self,
+                queries_v16::{BlockQuery, HardForkQuery, LedgerQuery, Request},
+                ClientQueryRequest,
+            },
+            handshake, txsubmission, Point as PallasPoint, PREVIEW_MAGIC, PROTOCOL_N2N_BLOCK_FETCH, PROTOCOL_N2N_CHAIN_SYNC,
+            PROTOCOL_N2N_HANDSHAKE, PROTOCOL_N2N_KEEP_ALIVE, PROTOCOL_N2N_TX_SUBMISSION,
+        },
+        multiplexer::{Bearer, Plexer},
+    },
+};
+use tokio::{
+    net::{TcpListener, UnixListener},
+    task::JoinHandle,
+};
+
+use crate::{
+    peer_sharing::{self, SharedAddress, PROTOCOL_N2N_PEER_SHARING},
+    runtime,
+    server::accept_n2n,
+};
+
+pub mod fixtures;
+
+pub use fixtures::MockBlock;
+
+pub const MAGIC: u64 = PREVIEW_MAGIC;
+
+// Delay between an Await reply and the response that follows it
+const AWAIT_DELAY: Duration = Duration::from_millis(100);
+
+static SOCKETS: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Clone, Debug)]
+pub enum Step {
+    // Serves `blocks[i]`
+    Forward(usize),
+    // Rolls back to `blocks[i]`, or to origin
+    Backward(Option<usize>),
+    // Answers Await, the next step follows after AWAIT_DELAY
+    Await,
+    // Closes the connection the first time it is reached
+    Disconnect,
+}
+
+// Chain served to every connection. Each connection walks the script from
+// the start, or from the step after the block it intersected at. Once the
+// script is exhausted the node awaits forever.
+pub struct MockChain {
+    pub blocks: Vec<MockBlock>,
+    script: Vec<Step>,
+    utxos: Vec<Vec<u8>>,
+    peers: Vec<SharedAddress>,
+    disconnected: Mutex<Vec<usize>>,
+    received_txs: Mutex<Vec<Vec<u8>>>,
+}
+
+impl MockChain {
+    // `count` blocks served in order
+    pub fn new(count: u64) -> MockChain {
+        let script = (0..count as usize).map(Step::Forward).collect();
+        MockChain::scripted(count, script)
+    }
+
+    pub fn scripted(count: u64, script: Vec<Step>) -> MockChain {
+        MockChain {
+            blocks: fixtures::chain(count),
+            script,
+            utxos: vec![fixtures::utxo()],
+            peers: vec![
+                SharedAddress::V4(Ipv4Addr::new(10, 0, 0, 1), 3001),
+                SharedAddress::V4(Ipv4Addr::new(10, 0, 0, 2), 3001),
+            ],
+            disconnected: Mutex::new(vec![]),
+            received_txs: Mutex::new(vec![]),
+        }
+    }
+
+    pub fn tip(&self) -> Tip {
+        match self.blocks.last() {
+            Some(block) => Tip(block.point(), block.number),
+            None => Tip(PallasPoint::Origin, 0),
+        }
+    }
+
+    fn point(&self, block: Option<usize>) -> PallasPoint {
+        block.map_or(PallasPoint::Origin, |i| self.blocks[i].point())
+    }
+
+    fn next(&self, position: &mut usize) -> Option<Step> {
+        loop {
+            let step = self.script.get(*position)?.clone();
+            *position += 1;
+
+            if let Step::Disconnect = step {
+                let mut disconnected = self.disconnected.lock().unwrap();
+
+                if disconnected.contains(position) {
+                    continue;
+                }
+
+                disconnected.push(*position);
+            }
+
+            return Some(step);
+        }
+    }
+
+    // Moves `position` past the block of the first known point
+    fn intersect(&self, points: &[PallasPoint], position: &mut usize) -> Option<PallasPoint> {
+        for point in points {
+            if *point == PallasPoint::Origin {
+                *position = 0;
+                return Some(point.clone());
+            }
+
+            let Some(i) = self.blocks.iter().position(|b| b.point() == *point) else {
+                continue;
+            };
+
+            *position = self
+                .script
+                .iter()
+                .position(|step| matches!(step, Step::Forward(f) if *f == i))
+                .map_or(self.script.len(), |p| p + 1);
+
+            return Some(point.clone());
+        }
+
+        None
+    }
+
+    fn range(&self, from: &PallasPoint, to: &PallasPoint) -> Vec<Vec<u8>> {
+        let slot = |point: &PallasPoint| match point {
+            PallasPoint::Origin => 0,
+            PallasPoint::Specific(slot, _) => *slot,
+        };
+
+        let Some(start) = self.blocks.iter().position(|b| b.point() == *from) else {
+            return vec![];
+        };
+
+        self.blocks[start..]
+            .iter()
+            .take_while(|b| b.slot <= slot(to))
+            .map(|b| b.block_cbor.clone())
+            .collect()
+    }
+
+    fn answer(&self, query: AnyCbor) -> AnyCbor {
+        match query.into_decode::<Request>() {
+            Ok(Request::LedgerQuery(LedgerQuery::HardForkQuery(HardForkQuery::GetCurrentEra))) => {
+                AnyCbor::from_encode(fixtures::BABBAGE_ERA)
+            }
+            Ok(Request::GetChainPoint) => AnyCbor::from_encode(self.tip().0),
+            Ok(Request::LedgerQuery(LedgerQuery::BlockQuery(_, BlockQuery::GetCBOR(_)))) => {
+                let utxos: Vec<TagWrap<Bytes, 24>> = self.utxos.iter().map(|utxo| TagWrap(utxo.clone().into())).collect();
+                AnyCbor::from_encode(utxos)
+            }
+            query => panic!("mock node cannot answer {:?}", query),
+        }
+    }
+
+    pub fn received_txs(&self) -> Vec<Vec<u8>> {
+        self.received_txs.lock().unwrap().clone()
+    }
+}
+
+fn block_content(block: &MockBlock) -> BlockContent {
+    BlockContent(block.block_cbor.clone())
+}
+
+fn header_content(block: &MockBlock) -> HeaderContent {
+    HeaderContent {
+        variant: fixtures::BABBAGE_HEADER_VARIANT,
+        byron_prefix: None,
+        cbor: block.header_cbor.clone(),
+    }
+}
+
+// Both chain-sync flavours only differ in the content of RollForward
+macro_rules! serve_chainsync {
+    ($server:expr, $chain:expr, $content:ident) => {{
+        let server = $server;
+        let chain: &MockChain = $chain;
+        let mut position = 0;
+
+        while let Ok(Some(request)) = server.recv_while_idle().await {
+            let result = match request {
+                ClientRequest::Intersect(points) => match chain.intersect(&points, &mut position) {
+                    Some(point) => server.send_intersect_found(point, chain.tip()).await,
+                    None => server.send_intersect_not_found(chain.tip()).await,
+                },
+                ClientRequest::RequestNext => {
+                    let mut awaited = false;
+
+                    loop {
+                        let step = chain.next(&mut position);
+
+                        match step {
+                            Some(Step::Forward(i)) => break server.send_roll_forward($content(&chain.blocks[i]), chain.tip()).await,
+                            Some(Step::Backward(to)) => break server.send_roll_backward(chain.point(to), chain.tip()).await,
+                            Some(Step::Disconnect) => return,
+                            Some(Step::Await) | None => {
+                                if !awaited {
+                                    awaited = true;
+
+                                    if server.send_await_reply().await.is_err() {
+                                        return;
+                                    }
+                                }
+
+                                match step {
+                                    Some(_) => tokio::time::sleep(AWAIT_DELAY).await,
+                                    None => pending::<()>().await,
+                                }
+                            }
+                        }
+                    }
+                }
+            };
+
+            if result.is_err() {
+                return;
+            }
+        }
+    }};
+}
+
+async fn serve_n2c_chainsync(server: &mut chainsync::N2CServer, chain: &MockChain) {
+    serve_chainsync!(server, chain, block_content)
+}
+
+async fn serve_n2n_chainsync(server: &mut chainsync::N2NServer, chain: &MockChain) {
+    serve_chainsync!(server, chain, header_content)
+}
+
+async fn serve_statequery(server: &mut localstate::Server, chain: &MockChain) {
+    while let Ok(Some(_)) = server.recv_while_idle().await {
+        if server.send_acquired().await.is_err() {
+            return;
+        }
+
+        loop {
+            let result = match server.recv_while_acquired().await {
+                Ok(ClientQueryRequest::Query(query)) => server.send_result(chain.answer(query)).await,
+                Ok(ClientQueryRequest::ReAcquire(_)) => server.send_acquired().await,
+                Ok(ClientQueryRequest::Release) => break,
+                Err(_) => return,
+            };
+
+            if result.is_err() {
+                return;
+            }
+        }
+    }
+}
+
+async fn serve_blockfetch(server: &mut blockfetch::Server, chain: &MockChain) {
+    while let Ok(Some(BlockRequest((from, to)))) = server.recv_while_idle().await {
+        let blocks = chain.range(&from, &to);
+
+        if blocks.is_empty() {
+            if server.send_no_blocks().await.is_err() {
+                return;
+            }
+
+            continue;
+        }
+
+        if server.send_start_batch().await.is_err() {
+            return;
+        }
+
+        for block in blocks {
+            if server.send_block(block).await.is_err() {
+                return;
+            }
+        }
+
+        if server.send_batch_done().await.is_err() {
+            return;
+        }
+    }
+}
+
+async fn serve_keepalive(server: &mut keepalive::Server) {
+    while server.keepalive_roundtrip().await.is_ok() {}
+}
+
+async fn serve_peersharing(server: &mut peer_sharing::Server, chain: &MockChain) {
+    while let Ok(Some(amount)) = server.recv_request().await {
+        let peers = chain.peers.iter().take(amount as usize).cloned().collect();
+
+        if server.send_peers(peers).await.is_err() {
+            return;
+        }
+    }
+}
+
+// Requests every tx the client announces and acknowledges it right away
+async fn serve_txsubmission(server: &mut txsubmission::Server, chain: &MockChain) {
+    if server.wait_for_init().await.is_err() {
+        return;
+    }
+
+    let mut ack = 0;
+
+    loop {
+        if server.acknowledge_and_request_tx_ids(true, ack, 10).await.is_err() {
+            return;
+        }
+
+        let ids: Vec<_> = match server.receive_next_reply().await {
+            Ok(txsubmission::Reply::TxIds(ids_and_size)) => ids_and_size.into_iter().map(|id| id.0).collect(),
+            _ => return,
+        };
+
+        ack = ids.len() as u16;
+
+        if server.request_txs(ids).await.is_err() {
+            return;
+        }
+
+        match server.receive_next_reply().await {
+            Ok(txsubmission::Reply::Txs(bodies)) => chain
+                .received_txs
+                .lock()
+                .unwrap()
+                .extend(bodies.into_iter().map(|body| body.1)),
+            _ => return,
+        }
+    }
+}
+
+// The connection is closed as soon as the chain-facing protocols stop, the
+// others idle once the client stops driving them.
+async fn serve_n2c(server: NodeServer, chain: Arc<MockChain>) {
+    let NodeServer {
+        plexer,
+        mut chainsync,
+        mut statequery,
+        ..
+    } = server;
+
+    tokio::select! {
+        _ = serve_n2c_chainsync(&mut chainsync, &chain) => {},
+        _ = serve_statequery(&mut statequery, &chain) => {},
+    }
+
+    plexer.abort().await;
+}
+
+// Pallas' PeerServer has no peer sharing, so the N2N side is assembled here.
+// The handshake echoes the client's version data like the relay server does.
+async fn serve_n2n(bearer: Bearer, chain: Arc<MockChain>) {
+    let mut plexer = Plexer::new(bearer);
+
+    let handshake_channel = plexer.subscribe_server(PROTOCOL_N2N_HANDSHAKE);
+    let chainsync_channel = plexer.subscribe_server(PROTOCOL_N2N_CHAIN_SYNC);
+    let blockfetch_channel = plexer.subscribe_server(PROTOCOL_N2N_BLOCK_FETCH);
+    let keepalive_channel = plexer.subscribe_server(PROTOCOL_N2N_KEEP_ALIVE);
+    let peersharing_channel = plexer.subscribe_server(PROTOCOL_N2N_PEER_SHARING);
+    let txsubmission_channel = plexer.subscribe_server(PROTOCOL_N2N_TX_SUBMISSION);
+    let plexer = plexer.spawn();
+
+    if accept_n2n(&mut handshake::Server::new(handshake_channel), MAGIC).await.is_err() {
+        return plexer.abort().await;
+    }
+
+    let mut chainsync = chainsync::Server::new(chainsync_channel);
+    let mut blockfetch = blockfetch::Server::new(blockfetch_channel);
+    let mut keepalive = keepalive::Server::new(keepalive_channel);
+    let mut peersharing = peer_sharing::Server::new(peersharing_channel);
+    let mut txsubmission = txsubmission::Server::new(txsubmission_channel);
+
+    tokio::select! {
+        _ = serve_n2n_chainsync(&mut chainsync, &chain) => {},
+        _ = serve_blockfetch(&mut blockfetch, &chain) => {},
+        _ = async { serve_keepalive(&mut keepalive).await; pending::<()>().await } => {},
+        _ = async { serve_peersharing(&mut peersharing, &chain).await; pending::<()>().await } => {},
+        _ = async { serve_txsubmission(&mut txsubmission, &chain).await; pending::<()>().await } => {},
+    }
+
+    plexer.abort().await;
+}
+
+pub struct MockNode {
+    pub chain: Arc<MockChain>,
+    address: String,
+    socket: Option<PathBuf>,
+    task: JoinHandle<()>,
+}
+
+impl MockNode {
+    // N2C node listening on a fresh unix socket
+    pub fn n2c(chain: MockChain) -> MockNode {
+        let chain = Arc::new(chain);
+
+        let socket = std::env::temp_dir().join(format!(
+            "pallas-dotnet-mock-{}-{}.socket",
+            std::process::id(),
+            SOCKETS.fetch_add(1, Ordering::SeqCst)
+        ));
+
+        let listener = runtime::block_on(async { UnixListener::bind(&socket) }).expect("mock node cannot bind its socket");
+
+        let task = {
+            let chain = chain.clone();
+
+            runtime::spawn(async move {
+                loop {
+                    // Handshake-only connections (version queries) fail to be accepted
+                    if let Ok(server) = NodeServer::accept(&listener, MAGIC).await {
+                        tokio::spawn(serve_n2c(server, chain.clone()));
+                    }
+                }
+            })
+        };
+
+        MockNode {
+            chain,
+            address: socket.to_string_lossy().to_string(),
+            socket: Some(socket),
+            task,
+        }
+    }
+
+    // N2N node listening on a free local TCP port
+    pub fn n2n(chain: MockChain) -> MockNode {
+        let chain = Arc::new(chain);
+
+        let listener = runtime::block_on(TcpListener::bind("127.0.0.1:0")).expect("mock node cannot bind a port");
+        let address = listener.local_addr().unwrap().to_string();
+
+        let task = {
+            let chain = chain.clone();
+
+            runtime::spawn(async move {
+                loop {
+                    if let Ok((bearer, _)) = Bearer::accept_tcp(&listener).await {
+                        tokio::spawn(serve_n2n(bearer, chain.clone()));
+                    }
+                }
+            })
+        };
+
+        MockNode {
+            chain,
+            address,
+            socket: None,
+            task,
+        }
+    }
+
+    // Socket path or `host:port` to connect to
+    pub fn address(&self) -> String {
+        self.address.clone()
+    }
+}
+
+impl Drop for MockNode {
+    // Stops accepting, connections already served keep running until the
+    // client goes away.
+    fn drop(&mut self) {
+        self.task.abort();
+
+        if let Some(socket) = &self.socket {
+            let _ = std::fs::remove_file(socket);
+        }
+    }
+}
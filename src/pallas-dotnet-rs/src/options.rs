@@ -0,0 +1,125 @@
+use std::{future::Future, path::Path, time::Duration};
+
+use rnet::Net;
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    bearer::{BearerKind, ClientKind},
+    keepalive::KEEPALIVE_INTERVAL,
+    session::{RECONNECT_ATTEMPTS, RECONNECT_BACKOFF},
+    versions::HandshakeOptions,
+};
+
+// Timeouts are in milliseconds, 0 disables the timeout.
+#[derive(Net, Clone)]
+pub struct ClientOptions {
+    pub(crate) connect_timeout_ms: u64,
+    pub(crate) handshake_timeout_ms: u64,
+    pub(crate) request_timeout_ms: u64,
+    pub(crate) idle_timeout_ms: u64,
+    // N2N only, 0 uses KEEPALIVE_INTERVAL
+    pub(crate) keepalive_interval_ms: u64,
+    // 0 disables reconnecting when chain-sync fails. Defaults to
+    // RECONNECT_ATTEMPTS, like the C# ClientOptions
+    pub(crate) max_reconnect_attempts: u32,
+    // Delay before the first attempt, doubled after each failure. 0 uses RECONNECT_BACKOFF
+    pub(crate) reconnect_backoff_ms: u64,
+    // See BearerKind, 0 uses the default transport of the client kind
+    pub(crate) bearer: u8,
+    pub(crate) handshake: HandshakeOptions,
+    // File of the acknowledged points connect resumes from, empty for none
+    pub(crate) cursor_path: String,
+    // Points kept in the cursor, 0 uses DEFAULT_CURSOR_POINTS
+    pub(crate) cursor_points: u32,
+}
+
+impl Default for ClientOptions {
+    fn default() -> ClientOptions {
+        ClientOptions {
+            connect_timeout_ms: 0,
+            handshake_timeout_ms: 0,
+            request_timeout_ms: 0,
+            idle_timeout_ms: 0,
+            keepalive_interval_ms: 0,
+            max_reconnect_attempts: RECONNECT_ATTEMPTS,
+            reconnect_backoff_ms: 0,
+            bearer: 0,
+            handshake: HandshakeOptions::default(),
+            cursor_path: String::new(),
+            cursor_points: 0,
+        }
+    }
+}
+
+fn millis(ms: u64) -> Option<Duration> {
+    (ms > 0).then(|| Duration::from_millis(ms))
+}
+
+impl ClientOptions {
+    pub fn connect_timeout(&self) -> Option<Duration> {
+        millis(self.connect_timeout_ms)
+    }
+
+    pub fn handshake_timeout(&self) -> Option<Duration> {
+        millis(self.handshake_timeout_ms)
+    }
+
+    pub fn request_timeout(&self) -> Option<Duration> {
+        millis(self.request_timeout_ms)
+    }
+
+    // How long to wait for the next block once the node answered with Await
+    pub fn idle_timeout(&self) -> Option<Duration> {
+        millis(self.idle_timeout_ms)
+    }
+
+    pub fn keepalive_interval(&self) -> Duration {
+        millis(self.keepalive_interval_ms).unwrap_or(KEEPALIVE_INTERVAL)
+    }
+
+    pub fn max_reconnect_attempts(&self) -> u32 {
+        self.max_reconnect_attempts
+    }
+
+    pub fn reconnect_backoff(&self) -> Duration {
+        millis(self.reconnect_backoff_ms).unwrap_or(RECONNECT_BACKOFF)
+    }
+
+    pub fn bearer(&self, client: ClientKind) -> Result<BearerKind, String> {
+        BearerKind::select(self.bearer, client)
+    }
+
+    pub fn handshake(&self) -> &HandshakeOptions {
+        &self.handshake
+    }
+
+    pub fn cursor(&self) -> Option<(&Path, usize)> {
+        (!self.cursor_path.is_empty()).then(|| (Path::new(&self.cursor_path), self.cursor_points as usize))
+    }
+}
+
+#[derive(Debug)]
+pub enum Interrupted {
+    Cancelled,
+    TimedOut,
+}
+
+pub async fn with_timeout<F: Future>(future: F, timeout: Option<Duration>) -> Result<F::Output, Interrupted> {
+    match timeout {
+        Some(timeout) => tokio::time::timeout(timeout, future)
+            .await
+            .map_err(|_| Interrupted::TimedOut),
+        None => Ok(future.await),
+    }
+}
+
+pub async fn interruptible<F: Future>(
+    future: F,
+    timeout: Option<Duration>,
+    cancel: &CancellationToken,
+) -> Result<F::Output, Interrupted> {
+    tokio::select! {
+        _ = cancel.cancelled() => Err(Interrupted::Cancelled),
+        result = with_timeout(future, timeout) => result,
+    }
+}
@@ -0,0 +1,254 @@
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use pallas::{
+    ledger::traverse::MultiEraHeader,
+    network::{
+        miniprotocols::{
+            blockfetch,
+            chainsync::{self, HeaderContent},
+            handshake, keepalive, txsubmission, Point as PallasPoint, PROTOCOL_N2N_BLOCK_FETCH,
+            PROTOCOL_N2N_CHAIN_SYNC, PROTOCOL_N2N_HANDSHAKE, PROTOCOL_N2N_KEEP_ALIVE,
+            PROTOCOL_N2N_TX_SUBMISSION,
+        },
+        multiplexer::{Plexer, RunningPlexer},
+    },
+};
+use tokio::sync::Mutex;
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    bearer::ClientKind,
+    keepalive::{KeepAlive, KeepAliveStats},
+    options::{interruptible, with_timeout, ClientOptions, Interrupted},
+    peer_sharing::{self, PROTOCOL_N2N_PEER_SHARING},
+    versions::{accepted, VersionInfo},
+    ClientWrapper, NextResponse, ACTION_AWAIT, ACTION_CANCELLED, ACTION_ROLL_BACK, ACTION_ROLL_FORWARD,
+};
+
+// Parts of a peer that stay reachable while chain-sync holds the connection
+#[derive(Clone)]
+pub struct PeerHandles {
+    pub keepalive: Arc<std::sync::Mutex<KeepAliveStats>>,
+    pub peer_sharing: Arc<Mutex<peer_sharing::Client>>,
+}
+
+// N2N connection split into its mini-protocol clients so that some of them
+// can be driven by background tasks while chain-sync stays with the caller.
+pub struct PeerConnection {
+    pub plexer: RunningPlexer,
+    pub chainsync: chainsync::N2NClient,
+    pub blockfetch: blockfetch::Client,
+    pub txsubmission: Option<txsubmission::Client>,
+    pub keepalive: KeepAlive,
+    pub handles: PeerHandles,
+    // Time from connecting to an accepted handshake
+    pub latency: Duration,
+    // Header whose block is being fetched, handed out again if the request
+    // is dropped before the block arrived
+    pending: Option<chainsync::NextResponse<HeaderContent>>,
+}
+
+impl PeerConnection {
+    pub async fn connect(server: &str, network_magic: u64, options: &ClientOptions) -> Result<(PeerConnection, VersionInfo), String> {
+        let start = Instant::now();
+
+        let bearer = options.bearer(ClientKind::N2N)?.connect(server, options).await?;
+
+        let mut plexer = Plexer::new(bearer);
+
+        let handshake_channel = plexer.subscribe_client(PROTOCOL_N2N_HANDSHAKE);
+        let chainsync_channel = plexer.subscribe_client(PROTOCOL_N2N_CHAIN_SYNC);
+        let blockfetch_channel = plexer.subscribe_client(PROTOCOL_N2N_BLOCK_FETCH);
+        let txsubmission_channel = plexer.subscribe_client(PROTOCOL_N2N_TX_SUBMISSION);
+        let keepalive_channel = plexer.subscribe_client(PROTOCOL_N2N_KEEP_ALIVE);
+        let peersharing_channel = plexer.subscribe_client(PROTOCOL_N2N_PEER_SHARING);
+
+        let plexer = plexer.spawn();
+
+        let mut handshake_client = handshake::Client::new(handshake_channel);
+        let versions = options.handshake().n2n_versions(network_magic);
+
+        let (version, data) = match accepted(with_timeout(handshake_client.handshake(versions), options.handshake_timeout()).await) {
+            Ok(accepted) => accepted,
+            Err(e) => {
+                plexer.abort().await;
+                return Err(e);
+            }
+        };
+
+        let keepalive = KeepAlive::start(
+            keepalive::Client::new(keepalive_channel),
+            options.keepalive_interval(),
+            options.request_timeout(),
+        );
+
+        let handles = PeerHandles {
+            keepalive: keepalive.stats(),
+            peer_sharing: Arc::new(Mutex::new(peer_sharing::Client::new(peersharing_channel))),
+        };
+
+        let connection = PeerConnection {
+            plexer,
+            chainsync: chainsync::Client::new(chainsync_channel),
+            blockfetch: blockfetch::Client::new(blockfetch_channel),
+            txsubmission: Some(txsubmission::Client::new(txsubmission_channel)),
+            keepalive,
+            handles,
+            latency: start.elapsed(),
+            pending: None,
+        };
+
+        Ok((connection, VersionInfo::n2n(version, &data)))
+    }
+
+    // Callers moving chain-sync to another point make the pending header stale
+    pub fn chainsync(&mut self) -> &mut chainsync::N2NClient {
+        self.pending = None;
+        &mut self.chainsync
+    }
+
+    // Last keepalive round-trip, or the connect latency until the first one
+    pub fn rtt(&self) -> Duration {
+        let stats = self.handles.keepalive.lock().unwrap();
+
+        match stats.roundtrips() {
+            0 => self.latency,
+            _ => stats.rtt(),
+        }
+    }
+
+    pub async fn next_header(
+        &mut self,
+        options: &ClientOptions,
+        cancel: &CancellationToken,
+    ) -> Result<chainsync::NextResponse<HeaderContent>, NextResponse> {
+        if let Some(next) = &self.pending {
+            return Ok(copy_response(next));
+        }
+
+        let client = self.chainsync();
+
+        let result = match client.state() {
+            // The node answered with Await, wait for the next block
+            chainsync::State::MustReply => interruptible(client.recv_while_must_reply(), options.idle_timeout(), cancel).await,
+            // A previous request was interrupted before the node replied
+            chainsync::State::CanAwait => interruptible(client.recv_while_can_await(), options.request_timeout(), cancel).await,
+            // When the client has the agency, send a request for the next block
+            _ => interruptible(client.request_next(), options.request_timeout(), cancel).await
+        };
+
+        match result {
+            Ok(Ok(next)) => {
+                self.pending = Some(copy_response(&next));
+                Ok(next)
+            }
+            Ok(Err(e)) => {
+                println!("chain_sync_next error: {:?}", e);
+                Err(NextResponse::error())
+            }
+            Err(Interrupted::Cancelled) => Err(NextResponse::cancelled()),
+            Err(Interrupted::TimedOut) => {
+                println!("chain_sync_next error: request timed out");
+                Err(NextResponse::error())
+            }
+        }
+    }
+
+    pub async fn next_response(&mut self, options: &ClientOptions, cancel: &CancellationToken) -> (NextResponse, Option<PallasPoint>) {
+        match self.next_header(options, cancel).await {
+            Ok(next) => {
+                let point = response_point(&next);
+                let next_response = fetch_response(next, &mut self.blockfetch, options, cancel).await;
+                self.fetched(&next_response);
+                (next_response, point)
+            }
+            Err(next_response) => (next_response, None),
+        }
+    }
+
+    // Drops the pending header once its response is delivered. A cancelled
+    // fetch keeps it for the next request.
+    pub fn fetched(&mut self, next_response: &NextResponse) {
+        if next_response.action != ACTION_CANCELLED {
+            self.pending = None;
+        }
+    }
+
+    pub async fn abort(self) {
+        self.keepalive.stop();
+        self.plexer.abort().await
+    }
+}
+
+// Pallas' chain-sync responses are not Clone
+pub fn copy_header(header: &HeaderContent) -> HeaderContent {
+    HeaderContent {
+        variant: header.variant,
+        byron_prefix: header.byron_prefix,
+        cbor: header.cbor.clone(),
+    }
+}
+
+pub fn copy_response(next: &chainsync::NextResponse<HeaderContent>) -> chainsync::NextResponse<HeaderContent> {
+    match next {
+        chainsync::NextResponse::RollForward(header, tip) => chainsync::NextResponse::RollForward(copy_header(header), tip.clone()),
+        chainsync::NextResponse::RollBackward(point, tip) => chainsync::NextResponse::RollBackward(point.clone(), tip.clone()),
+        chainsync::NextResponse::Await => chainsync::NextResponse::Await,
+    }
+}
+
+// Point the consumer is at once the given response has been delivered
+pub fn response_point(next: &chainsync::NextResponse<HeaderContent>) -> Option<PallasPoint> {
+    match next {
+        chainsync::NextResponse::RollForward(header, _) => MultiEraHeader::decode(header.variant, None, &header.cbor)
+            .ok()
+            .map(|h| PallasPoint::Specific(h.slot(), h.hash().to_vec())),
+        chainsync::NextResponse::RollBackward(point, _) => Some(point.clone()),
+        chainsync::NextResponse::Await => None,
+    }
+}
+
+// Completes a header response with the block body from `blockfetch`, which
+// does not have to belong to the peer that served the header.
+pub async fn fetch_response(
+    next: chainsync::NextResponse<HeaderContent>,
+    blockfetch: &mut blockfetch::Client,
+    options: &ClientOptions,
+    cancel: &CancellationToken,
+) -> NextResponse {
+    match next {
+        chainsync::NextResponse::RollForward(header, tip) => match MultiEraHeader::decode(header.variant, None, &header.cbor) {
+            Ok(h) => match interruptible(ClientWrapper::fetch_single_block(blockfetch, PallasPoint::Specific(h.slot(), h.hash().to_vec())), options.request_timeout(), cancel).await {
+                Ok(Some(block_cbor)) => NextResponse {
+                    action: ACTION_ROLL_FORWARD,
+                    tip: Some(tip.0.into()),
+                    block_cbor: Some(block_cbor)
+                },
+                Err(Interrupted::Cancelled) => NextResponse::cancelled(),
+                _ => NextResponse::error()
+            },
+            Err(e) => {
+                println!("chain_sync_next error: {:?}", e);
+                NextResponse::error()
+            }
+        },
+        chainsync::NextResponse::RollBackward(point, tip) => NextResponse {
+            action: ACTION_ROLL_BACK,
+            tip: Some(tip.0.into()),
+            block_cbor: match point {
+                PallasPoint::Origin => None,
+                point => interruptible(ClientWrapper::fetch_single_block(blockfetch, point), options.request_timeout(), cancel)
+                    .await
+                    .unwrap_or(None)
+            }
+        },
+        chainsync::NextResponse::Await => NextResponse {
+            action: ACTION_AWAIT,
+            tip: None,
+            block_cbor: None
+        }
+    }
+}
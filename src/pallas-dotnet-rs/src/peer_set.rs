@@ -0,0 +1,340 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+};
+
+use pallas::network::miniprotocols::{chainsync::Tip, Point as PallasPoint};
+use rnet::{net, Net};
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    bearer::ClientKind,
+    options::{interruptible, ClientOptions},
+    peer::{copy_response, fetch_response, response_point, PeerConnection, PeerHandles},
+    session::{self, Target, RECONNECT_BACKOFF_MAX},
+    versions::VersionInfo,
+    ClientWrapper, NextResponse,
+};
+
+// Handles of every configured peer, shared with the exports that must not
+// wait for the connection lock. `None` while a peer is disconnected.
+#[derive(Default)]
+pub struct PeerTable {
+    servers: Vec<String>,
+    handles: Vec<Option<PeerHandles>>,
+    active: usize,
+}
+
+impl PeerTable {
+    pub fn single(server: String, handles: PeerHandles) -> PeerTable {
+        PeerTable {
+            servers: vec![server],
+            handles: vec![Some(handles)],
+            active: 0,
+        }
+    }
+
+    pub fn active(&self) -> Option<&PeerHandles> {
+        self.handles.get(self.active).and_then(|handles| handles.as_ref())
+    }
+}
+
+#[derive(Net)]
+pub struct PeerStatus {
+    pub(crate) server: String,
+    pub(crate) connected: bool,
+    pub(crate) active: bool,
+    pub(crate) rtt_us: u64,
+}
+
+// N2N client over several relays. Chain-sync runs on the peer with the lowest
+// round-trip time and moves to the next best one when that peer fails,
+// re-intersecting at the recent points handed to the consumer.
+pub struct PeerSet {
+    servers: Vec<String>,
+    network_magic: u64,
+    options: ClientOptions,
+    fetch_from_fastest: bool,
+    peers: Vec<Option<PeerConnection>>,
+    active: usize,
+    // Points to intersect at on a new peer, oldest first. Starts with the
+    // points of the last find_intersect and follows the delivered points, so
+    // a peer that missed the newest blocks still finds an intersection.
+    cursor: VecDeque<PallasPoint>,
+    table: Arc<Mutex<PeerTable>>,
+}
+
+impl PeerSet {
+    pub async fn connect(
+        servers: Vec<String>,
+        network_magic: u64,
+        options: &ClientOptions,
+        fetch_from_fastest: bool,
+        table: Arc<Mutex<PeerTable>>,
+    ) -> Result<(PeerSet, VersionInfo), String> {
+        let mut peer_set = PeerSet {
+            peers: servers.iter().map(|_| None).collect(),
+            servers,
+            network_magic,
+            options: options.clone(),
+            fetch_from_fastest,
+            active: 0,
+            cursor: VecDeque::new(),
+            table,
+        };
+
+        let version = peer_set.connect_missing().await?;
+        peer_set.active = peer_set.fastest().ok_or("no peer available")?;
+        peer_set.publish();
+
+        Ok((peer_set, version))
+    }
+
+    pub fn active(&mut self) -> &mut PeerConnection {
+        self.peers[self.active].as_mut().expect("active peer is connected")
+    }
+
+    // Connects every peer that is currently down and returns the version
+    // negotiated with the first one that answered.
+    async fn connect_missing(&mut self) -> Result<VersionInfo, String> {
+        let mut version = None;
+        let mut errors = vec![];
+
+        for (server, peer) in self.servers.iter().zip(self.peers.iter_mut()) {
+            if peer.is_some() {
+                continue;
+            }
+
+            match PeerConnection::connect(server, self.network_magic, &self.options).await {
+                Ok((connection, connection_version)) => {
+                    *peer = Some(connection);
+                    version.get_or_insert(connection_version);
+                }
+                Err(e) => errors.push(format!("{}: {}", server, e)),
+            }
+        }
+
+        version.ok_or_else(|| errors.join(", "))
+    }
+
+    fn fastest(&self) -> Option<usize> {
+        self.peers
+            .iter()
+            .enumerate()
+            .filter_map(|(i, peer)| peer.as_ref().map(|peer| (i, peer.rtt())))
+            .min_by_key(|(_, rtt)| *rtt)
+            .map(|(i, _)| i)
+    }
+
+    fn publish(&self) {
+        let mut table = self.table.lock().unwrap();
+
+        table.servers = self.servers.clone();
+        table.handles = self
+            .peers
+            .iter()
+            .map(|peer| peer.as_ref().map(|peer| peer.handles.clone()))
+            .collect();
+        table.active = self.active;
+    }
+
+    async fn drop_peer(&mut self, index: usize) {
+        if let Some(peer) = self.peers[index].take() {
+            peer.abort().await;
+        }
+    }
+
+    // Moves chain-sync to the best remaining peer, intersected at the cursor.
+    // Peers that are down are reconnected once the connected ones are
+    // exhausted.
+    async fn failover(&mut self, cancel: &CancellationToken) -> Result<(), String> {
+        self.drop_peer(self.active).await;
+
+        let mut reconnected = false;
+
+        loop {
+            let Some(candidate) = self.fastest() else {
+                if reconnected {
+                    return Err("no peer available".to_string());
+                }
+
+                reconnected = true;
+                let _ = self.connect_missing().await;
+                continue;
+            };
+
+            let peer = self.peers[candidate].as_mut().unwrap();
+
+            // Before anything was delivered the new peer starts from origin
+            let points = match self.cursor.is_empty() {
+                true => vec![PallasPoint::Origin],
+                false => self.cursor.iter().rev().cloned().collect(),
+            };
+
+            let intersected = matches!(
+                interruptible(peer.chainsync().find_intersect(points), self.options.request_timeout(), cancel).await,
+                Ok(Ok((Some(_), _)))
+            );
+
+            if intersected {
+                eprintln!("chain_sync failover to {}", self.servers[candidate]);
+                self.active = candidate;
+                self.publish();
+                return Ok(());
+            }
+
+            self.drop_peer(candidate).await;
+        }
+    }
+
+    async fn try_next(&mut self, options: &ClientOptions, cancel: &CancellationToken) -> (NextResponse, Option<PallasPoint>) {
+        let next = match self.active().next_header(options, cancel).await {
+            Ok(next) => next,
+            Err(next_response) => return (next_response, None),
+        };
+
+        let point = response_point(&next);
+
+        let fastest = match self.fetch_from_fastest {
+            true => self.fastest().unwrap_or(self.active),
+            false => self.active,
+        };
+
+        let next_response = match fastest == self.active {
+            true => fetch_response(next, &mut self.active().blockfetch, options, cancel).await,
+            false => {
+                let peer = self.peers[fastest].as_mut().unwrap();
+                let next_response = fetch_response(copy_response(&next), &mut peer.blockfetch, options, cancel).await;
+
+                // The fastest peer may not have adopted the block yet
+                match next_response.is_error() {
+                    true => fetch_response(next, &mut self.active().blockfetch, options, cancel).await,
+                    false => next_response,
+                }
+            }
+        };
+
+        self.active().fetched(&next_response);
+
+        if !next_response.is_error() {
+            if let Some(point) = &point {
+                session::record(&mut self.cursor, point.clone());
+            }
+        }
+
+        (next_response, point)
+    }
+
+    // Fails over at most max_reconnect_attempts times per response, waiting
+    // with the same exponential backoff as a session reconnect.
+    pub async fn next_response(&mut self, options: &ClientOptions, cancel: &CancellationToken) -> (NextResponse, Option<PallasPoint>) {
+        let mut backoff = options.reconnect_backoff();
+        let mut attempts = 0;
+
+        // A failover dropped half way left no active peer
+        let mut failed = self.peers[self.active].is_none();
+
+        loop {
+            if failed {
+                if attempts == options.max_reconnect_attempts() {
+                    eprintln!("chain_sync_next error: giving up after {} failovers", attempts);
+                    return (NextResponse::error(), None);
+                }
+
+                attempts += 1;
+
+                if interruptible(tokio::time::sleep(backoff), None, cancel).await.is_err() {
+                    return (NextResponse::cancelled(), None);
+                }
+
+                backoff = (backoff * 2).min(RECONNECT_BACKOFF_MAX);
+
+                if let Err(e) = self.failover(cancel).await {
+                    println!("chain_sync_next error: {}", e);
+                    return (NextResponse::error(), None);
+                }
+            }
+
+            let (next_response, point) = self.try_next(options, cancel).await;
+
+            if !next_response.is_error() {
+                return (next_response, point);
+            }
+
+            failed = true;
+        }
+    }
+
+    pub async fn find_intersect(&mut self, points: Vec<PallasPoint>) -> Result<(Option<PallasPoint>, Tip), String> {
+        let mut cursor = points.clone();
+        cursor.sort_by_key(|point| match point {
+            PallasPoint::Origin => 0,
+            PallasPoint::Specific(slot, _) => *slot + 1,
+        });
+        self.cursor = cursor.into();
+
+        self.active()
+            .chainsync()
+            .find_intersect(points)
+            .await
+            .map_err(|e| format!("{:?}", e))
+    }
+
+    pub async fn abort(self) {
+        for peer in self.peers.into_iter().flatten() {
+            peer.abort().await;
+        }
+    }
+}
+
+impl ClientWrapper {
+    #[net]
+    pub fn connect_peer_set(
+        servers: Vec<String>,
+        network_magic: u64,
+        options: ClientOptions,
+        fetch_from_fastest: bool,
+    ) -> Option<ClientWrapper> {
+        ClientWrapper::connect_peer_set(servers, network_magic, options, fetch_from_fastest)
+    }
+
+    pub fn connect_peer_set(
+        servers: Vec<String>,
+        network_magic: u64,
+        options: ClientOptions,
+        fetch_from_fastest: bool,
+    ) -> Option<ClientWrapper> {
+        let target = Target::PeerSet {
+            servers,
+            network_magic,
+            fetch_from_fastest,
+        };
+
+        ClientWrapper::new(ClientKind::N2N, target, options)
+    }
+
+    #[net]
+    pub fn get_peer_set_status(client_wrapper: ClientWrapper) -> Vec<PeerStatus> {
+        ClientWrapper::get_peer_set_status(client_wrapper)
+    }
+
+    pub fn get_peer_set_status(client_wrapper: ClientWrapper) -> Vec<PeerStatus> {
+        let table = client_wrapper.state().peers.lock().unwrap();
+
+        table
+            .servers
+            .iter()
+            .zip(table.handles.iter())
+            .enumerate()
+            .map(|(i, (server, handles))| PeerStatus {
+                server: server.clone(),
+                connected: handles.is_some(),
+                active: i == table.active,
+                rtt_us: handles
+                    .as_ref()
+                    .map(|handles| handles.keepalive.lock().unwrap().rtt().as_micros() as u64)
+                    .unwrap_or(0),
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,212 @@
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+use pallas::{
+    codec::minicbor::{decode, encode, Decode, Decoder, Encode, Encoder},
+    network::multiplexer::{AgentChannel, ChannelBuffer},
+};
+use rnet::{net, Net};
+
+use crate::{options::interruptible, runtime, ClientWrapper};
+
+// Pallas 0.30 has no peer-sharing mini-protocol, so its messages are encoded
+// here following the ouroboros-network CDDL.
+pub const PROTOCOL_N2N_PEER_SHARING: u16 = 10;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SharedAddress {
+    V4(Ipv4Addr, u16),
+    V6(Ipv6Addr, u16),
+}
+
+#[derive(Debug)]
+enum Message {
+    ShareRequest(u8),
+    SharePeers(Vec<SharedAddress>),
+    Done,
+}
+
+impl Encode<()> for SharedAddress {
+    fn encode<W: encode::Write>(&self, e: &mut Encoder<W>, _ctx: &mut ()) -> Result<(), encode::Error<W::Error>> {
+        match self {
+            SharedAddress::V4(ip, port) => {
+                e.array(3)?.u8(0)?.u32(u32::from(*ip))?.u16(*port)?;
+            }
+            SharedAddress::V6(ip, port) => {
+                let bits = u128::from(*ip);
+                e.array(6)?.u8(1)?;
+
+                for word in (0..4).rev() {
+                    e.u32((bits >> (word * 32)) as u32)?;
+                }
+
+                e.u16(*port)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<'b> Decode<'b, ()> for SharedAddress {
+    fn decode(d: &mut Decoder<'b>, _ctx: &mut ()) -> Result<Self, decode::Error> {
+        d.array()?;
+
+        match d.u8()? {
+            0 => Ok(SharedAddress::V4(Ipv4Addr::from(d.u32()?), d.u16()?)),
+            1 => {
+                let mut bits = 0u128;
+
+                for _ in 0..4 {
+                    bits = (bits << 32) | d.u32()? as u128;
+                }
+
+                Ok(SharedAddress::V6(Ipv6Addr::from(bits), d.u16()?))
+            }
+            _ => Err(decode::Error::message("unknown peer address variant")),
+        }
+    }
+}
+
+impl Encode<()> for Message {
+    fn encode<W: encode::Write>(&self, e: &mut Encoder<W>, _ctx: &mut ()) -> Result<(), encode::Error<W::Error>> {
+        match self {
+            Message::ShareRequest(amount) => {
+                e.array(2)?.u8(0)?.u8(*amount)?;
+            }
+            Message::SharePeers(addresses) => {
+                e.array(2)?.u8(1)?.array(addresses.len() as u64)?;
+
+                for address in addresses {
+                    e.encode(address)?;
+                }
+            }
+            Message::Done => {
+                e.array(1)?.u8(2)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<'b> Decode<'b, ()> for Message {
+    fn decode(d: &mut Decoder<'b>, _ctx: &mut ()) -> Result<Self, decode::Error> {
+        d.array()?;
+
+        match d.u8()? {
+            0 => Ok(Message::ShareRequest(d.u8()?)),
+            1 => Ok(Message::SharePeers(d.array_iter()?.collect::<Result<_, _>>()?)),
+            2 => Ok(Message::Done),
+            _ => Err(decode::Error::message("unknown peer sharing message")),
+        }
+    }
+}
+
+pub struct Client(ChannelBuffer);
+
+impl Client {
+    pub fn new(channel: AgentChannel) -> Self {
+        Client(ChannelBuffer::new(channel))
+    }
+
+    pub async fn share_peers(&mut self, amount: u8) -> Result<Vec<SharedAddress>, String> {
+        self.0
+            .send_msg_chunks(&Message::ShareRequest(amount))
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        match self.0.recv_full_msg().await.map_err(|e| format!("{:?}", e))? {
+            Message::SharePeers(addresses) => Ok(addresses),
+            other => Err(format!("unexpected peer sharing message {:?}", other)),
+        }
+    }
+}
+
+// Node side, used by the mock node
+#[cfg(test)]
+pub struct Server(ChannelBuffer);
+
+#[cfg(test)]
+impl Server {
+    pub fn new(channel: AgentChannel) -> Self {
+        Server(ChannelBuffer::new(channel))
+    }
+
+    // Requested amount, None once the client is done
+    pub async fn recv_request(&mut self) -> Result<Option<u8>, String> {
+        match self.0.recv_full_msg().await.map_err(|e| format!("{:?}", e))? {
+            Message::ShareRequest(amount) => Ok(Some(amount)),
+            Message::Done => Ok(None),
+            other => Err(format!("unexpected peer sharing message {:?}", other)),
+        }
+    }
+
+    pub async fn send_peers(&mut self, addresses: Vec<SharedAddress>) -> Result<(), String> {
+        self.0
+            .send_msg_chunks(&Message::SharePeers(addresses))
+            .await
+            .map_err(|e| format!("{:?}", e))
+    }
+}
+
+#[derive(Net)]
+pub struct PeerAddress {
+    pub(crate) ip: String,
+    pub(crate) port: u16,
+}
+
+impl From<SharedAddress> for PeerAddress {
+    fn from(address: SharedAddress) -> Self {
+        match address {
+            SharedAddress::V4(ip, port) => PeerAddress {
+                ip: ip.to_string(),
+                port,
+            },
+            SharedAddress::V6(ip, port) => PeerAddress {
+                ip: ip.to_string(),
+                port,
+            },
+        }
+    }
+}
+
+impl ClientWrapper {
+    // Asks the peer for up to `amount` addresses of other relays. Needs a
+    // connection whose handshake negotiated peer sharing (V11+).
+    #[net]
+    pub fn share_peers(client_wrapper: ClientWrapper, amount: u8) -> Vec<PeerAddress> {
+        ClientWrapper::share_peers(client_wrapper, amount)
+    }
+
+    pub fn share_peers(client_wrapper: ClientWrapper, amount: u8) -> Vec<PeerAddress> {
+        let state = client_wrapper.state();
+
+        let Some(peer) = state.peers.lock().unwrap().active().cloned() else {
+            println!("share_peers error: peer sharing needs a N2N connection");
+            return vec![];
+        };
+
+        if !state.version.lock().unwrap().peer_sharing {
+            println!("share_peers error: peer sharing was not negotiated");
+            return vec![];
+        }
+
+        let cancel = state.cancel_token();
+
+        let addresses = runtime::block_on(interruptible(async {
+            peer.peer_sharing.lock().await.share_peers(amount).await
+        }, state.options.request_timeout(), &cancel));
+
+        match addresses {
+            Ok(Ok(addresses)) => addresses.into_iter().map(PeerAddress::from).collect(),
+            Ok(Err(e)) => {
+                println!("share_peers error: {}", e);
+                vec![]
+            }
+            Err(e) => {
+                println!("share_peers error: {:?}", e);
+                vec![]
+            }
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use std::{
+    future::Future,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, RwLock,
+    },
+    time::Duration,
+};
+
+use rnet::{net, Net};
+use tokio::{
+    runtime::{Builder, Runtime, RuntimeFlavor},
+    task::JoinHandle,
+};
+
+// Shared runtime for every client. Created with tokio's defaults on first use
+// unless the host configured it beforehand through init_runtime. Callers
+// clone it out, so the lock is never held while blocking on a future.
+static RUNTIME: RwLock<Option<Arc<Runtime>>> = RwLock::new(None);
+
+fn build(worker_threads: usize, thread_name_prefix: String, current_thread: bool) -> std::io::Result<Runtime> {
+    let mut builder = if current_thread {
+        Builder::new_current_thread()
+    } else {
+        Builder::new_multi_thread()
+    };
+
+    if !current_thread && worker_threads > 0 {
+        builder.worker_threads(worker_threads);
+    }
+
+    if !thread_name_prefix.is_empty() {
+        let next_id = AtomicUsize::new(0);
+        builder.thread_name_fn(move || format!("{}-{}", thread_name_prefix, next_id.fetch_add(1, Ordering::SeqCst)));
+    }
+
+    builder.enable_all().build()
+}
+
+fn runtime() -> Arc<Runtime> {
+    if let Some(runtime) = RUNTIME.read().unwrap().as_ref() {
+        return runtime.clone();
+    }
+
+    RUNTIME
+        .write()
+        .unwrap()
+        .get_or_insert_with(|| Arc::new(Runtime::new().expect("Failed to create Tokio runtime")))
+        .clone()
+}
+
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let runtime = runtime();
+
+    match runtime.handle().runtime_flavor() {
+        // Only Runtime::block_on drives the io and timer drivers of a
+        // current-thread runtime. A shutdown meanwhile leaves the runtime to
+        // be dropped once this call returns.
+        RuntimeFlavor::CurrentThread => runtime.block_on(future),
+        _ => {
+            let handle = runtime.handle().clone();
+            drop(runtime);
+            handle.block_on(future)
+        }
+    }
+}
+
+pub fn spawn<F>(future: F) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    runtime().spawn(future)
+}
+
+#[derive(Net)]
+pub struct NativeRuntime {}
+
+impl NativeRuntime {
+    // Has to be called before the first client is connected. Returns false
+    // when a runtime is already running. 0 worker threads and an empty prefix
+    // keep tokio's defaults. On a current-thread runtime background tasks
+    // (chain-sync streams, tx submission sessions) only make progress while
+    // another call is blocked in native code.
+    #[net]
+    pub fn init_runtime(worker_threads: u32, thread_name_prefix: String, enable_current_thread: bool) -> bool {
+        NativeRuntime::init_runtime(worker_threads, thread_name_prefix, enable_current_thread)
+    }
+
+    pub fn init_runtime(worker_threads: u32, thread_name_prefix: String, enable_current_thread: bool) -> bool {
+        let mut runtime = RUNTIME.write().unwrap();
+
+        if runtime.is_some() {
+            return false;
+        }
+
+        match build(worker_threads as usize, thread_name_prefix, enable_current_thread) {
+            Ok(built) => {
+                *runtime = Some(Arc::new(built));
+                true
+            }
+            Err(e) => {
+                println!("init_runtime error: {:?}", e);
+                false
+            }
+        }
+    }
+
+    // Stops all background tasks and waits up to `timeout_ms` for blocking
+    // work to finish. Clients connected before are unusable afterwards, the
+    // next call into the library starts a fresh default runtime.
+    #[net]
+    pub fn shutdown_runtime(timeout_ms: u64) {
+        NativeRuntime::shutdown_runtime(timeout_ms)
+    }
+
+    pub fn shutdown_runtime(timeout_ms: u64) {
+        let runtime = RUNTIME.write().unwrap().take();
+
+        // Still borrowed by a call blocked on a current-thread runtime, which
+        // drops it when it returns
+        if let Some(Ok(runtime)) = runtime.map(Arc::try_unwrap) {
+            runtime.shutdown_timeout(Duration::from_millis(timeout_ms));
+        }
+    }
+}
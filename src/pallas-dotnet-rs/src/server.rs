@@ -0,0 +1,618 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex},
+};
+
+use pallas::{
+    ledger::traverse::{MultiEraBlock, MultiEraHeader},
+    network::{
+        miniprotocols::{
+            blockfetch::{self, BlockRequest},
+            chainsync::{self, BlockContent, ClientRequest, HeaderContent, Tip},
+            handshake::{self, n2c, n2n, RefuseReason},
+            keepalive, Point as PallasPoint, PROTOCOL_N2C_CHAIN_SYNC, PROTOCOL_N2C_HANDSHAKE, PROTOCOL_N2N_BLOCK_FETCH,
+            PROTOCOL_N2N_CHAIN_SYNC, PROTOCOL_N2N_HANDSHAKE, PROTOCOL_N2N_KEEP_ALIVE,
+        },
+        multiplexer::{Bearer, Plexer},
+    },
+};
+use rnet::{net, Net};
+use tokio::{sync::watch, task::JoinHandle};
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    bearer::{BearerKind, ClientKind},
+    extends_tip, runtime,
+    versions::VersionInfo,
+    Point,
+};
+
+// Header kept in pieces, pallas' HeaderContent is not Clone
+#[derive(Clone)]
+struct StoredBlock {
+    // Insertion order, never reused. Blocks of the current chain with a lower
+    // seq than a consumer's block are ancestors of that block.
+    seq: u64,
+    point: PallasPoint,
+    number: u64,
+    variant: u8,
+    byron_prefix: Option<(u8, u64)>,
+    header_cbor: Vec<u8>,
+    block_cbor: Vec<u8>,
+}
+
+impl StoredBlock {
+    fn decode(seq: u64, block_cbor: Vec<u8>) -> Result<StoredBlock, String> {
+        let block = MultiEraBlock::decode(&block_cbor).map_err(|e| format!("invalid block cbor: {:?}", e))?;
+        let header = block.header();
+
+        let byron_prefix = match &header {
+            MultiEraHeader::EpochBoundary(_) => Some((0, block_cbor.len() as u64)),
+            MultiEraHeader::Byron(_) => Some((1, block_cbor.len() as u64)),
+            _ => None,
+        };
+
+        Ok(StoredBlock {
+            seq,
+            point: PallasPoint::Specific(block.slot(), block.hash().to_vec()),
+            number: block.number(),
+            variant: block.era() as u8,
+            byron_prefix,
+            header_cbor: header.cbor().to_vec(),
+            block_cbor,
+        })
+    }
+
+    fn slot(&self) -> u64 {
+        match self.point {
+            PallasPoint::Specific(slot, _) => slot,
+            PallasPoint::Origin => 0,
+        }
+    }
+
+    fn is_epoch_boundary(&self) -> bool {
+        matches!(self.byron_prefix, Some((0, _)))
+    }
+}
+
+enum Next {
+    Forward(StoredBlock),
+    Backward(PallasPoint, u64),
+    AtTip,
+    // The consumer's block and every ancestor left in the store were evicted
+    Evicted,
+}
+
+// Blocks handed in by the host, oldest first. Consumers keep the seq of the
+// last block they received as their cursor, 0 being origin.
+struct ChainStore {
+    blocks: VecDeque<StoredBlock>,
+    next_seq: u64,
+    // 0 keeps every block
+    capacity: usize,
+    // Seq of the newest block dropped for capacity since the chain last
+    // started from origin, 0 when none was
+    evicted: u64,
+}
+
+impl ChainStore {
+    fn tip(&self) -> Tip {
+        match self.blocks.back() {
+            Some(block) => Tip(block.point.clone(), block.number),
+            None => Tip(PallasPoint::Origin, 0),
+        }
+    }
+
+    fn push(&mut self, block_cbor: Vec<u8>) -> Result<(), String> {
+        let block = StoredBlock::decode(self.next_seq + 1, block_cbor)?;
+
+        if let Some(tip) = self.blocks.back() {
+            if !extends_tip(block.slot(), block.is_epoch_boundary(), tip.slot(), tip.is_epoch_boundary()) {
+                return Err(format!("block at slot {} does not extend the tip at slot {}", block.slot(), tip.slot()));
+            }
+        }
+
+        self.next_seq = block.seq;
+        self.blocks.push_back(block);
+
+        while self.capacity > 0 && self.blocks.len() > self.capacity {
+            if let Some(block) = self.blocks.pop_front() {
+                self.evicted = block.seq;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn roll_back(&mut self, point: &PallasPoint) -> bool {
+        match point {
+            PallasPoint::Origin => {
+                self.blocks.clear();
+                self.evicted = 0;
+            }
+            point => match self.blocks.iter().position(|b| b.point == *point) {
+                Some(i) => self.blocks.truncate(i + 1),
+                None => return false,
+            },
+        }
+
+        true
+    }
+
+    fn intersect(&self, points: &[PallasPoint]) -> Option<(PallasPoint, u64)> {
+        points.iter().find_map(|point| match point {
+            PallasPoint::Origin => Some((PallasPoint::Origin, 0)),
+            point => self
+                .blocks
+                .iter()
+                .find(|b| b.point == *point)
+                .map(|b| (b.point.clone(), b.seq)),
+        })
+    }
+
+    // A consumer whose block is gone is rolled back to its most recent
+    // ancestor still stored, or to origin when the chain was rolled back that
+    // far. Without an ancestor past an eviction nothing it could receive
+    // would connect. Consumers at origin continue with the oldest retained
+    // block.
+    fn next(&self, cursor: u64) -> Next {
+        if cursor == 0 {
+            return match self.blocks.front() {
+                Some(block) => Next::Forward(block.clone()),
+                None => Next::AtTip,
+            };
+        }
+
+        match self.blocks.iter().position(|b| b.seq == cursor) {
+            Some(i) => match self.blocks.get(i + 1) {
+                Some(block) => Next::Forward(block.clone()),
+                None => Next::AtTip,
+            },
+            None => match self.blocks.iter().rev().find(|b| b.seq < cursor) {
+                Some(block) => Next::Backward(block.point.clone(), block.seq),
+                None if self.evicted > 0 => Next::Evicted,
+                None => Next::Backward(PallasPoint::Origin, 0),
+            },
+        }
+    }
+
+    fn range(&self, from: &PallasPoint, to: &PallasPoint) -> Vec<Vec<u8>> {
+        let start = self.blocks.iter().position(|b| b.point == *from);
+        let end = self.blocks.iter().position(|b| b.point == *to);
+
+        match (start, end) {
+            (Some(start), Some(end)) if start <= end => self.blocks.range(start..=end).map(|b| b.block_cbor.clone()).collect(),
+            _ => vec![],
+        }
+    }
+}
+
+// State shared with the connection tasks
+struct Shared {
+    store: Mutex<ChainStore>,
+    // Bumped on every change of the store to wake consumers at the tip
+    changes: watch::Sender<()>,
+    network_magic: u64,
+    kind: ClientKind,
+}
+
+fn block_content(block: &StoredBlock) -> BlockContent {
+    BlockContent(block.block_cbor.clone())
+}
+
+fn header_content(block: &StoredBlock) -> HeaderContent {
+    HeaderContent {
+        variant: block.variant,
+        byron_prefix: block.byron_prefix,
+        cbor: block.header_cbor.clone(),
+    }
+}
+
+// Both chain-sync flavours only differ in the content of RollForward. Fails
+// when the consumer fell behind the retained blocks and has to be dropped.
+macro_rules! serve_chainsync {
+    ($server:expr, $shared:expr, $content:ident) => {{
+        let server = $server;
+        let shared: &Shared = $shared;
+        let mut changes = shared.changes.subscribe();
+        let mut cursor = 0;
+
+        while let Ok(Some(request)) = server.recv_while_idle().await {
+            let result = match request {
+                ClientRequest::Intersect(points) => {
+                    let (intersect, tip) = {
+                        let store = shared.store.lock().unwrap();
+                        (store.intersect(&points), store.tip())
+                    };
+
+                    match intersect {
+                        Some((point, seq)) => {
+                            cursor = seq;
+                            server.send_intersect_found(point, tip).await
+                        }
+                        None => server.send_intersect_not_found(tip).await,
+                    }
+                }
+                ClientRequest::RequestNext => {
+                    let mut awaited = false;
+
+                    loop {
+                        changes.borrow_and_update();
+
+                        let (next, tip) = {
+                            let store = shared.store.lock().unwrap();
+                            (store.next(cursor), store.tip())
+                        };
+
+                        match next {
+                            Next::Forward(block) => {
+                                cursor = block.seq;
+                                break server.send_roll_forward($content(&block), tip).await;
+                            }
+                            Next::Backward(point, seq) => {
+                                cursor = seq;
+                                break server.send_roll_backward(point, tip).await;
+                            }
+                            Next::AtTip => {
+                                if !awaited {
+                                    awaited = true;
+
+                                    if server.send_await_reply().await.is_err() {
+                                        return Ok(());
+                                    }
+                                }
+
+                                if changes.changed().await.is_err() {
+                                    return Ok(());
+                                }
+                            }
+                            Next::Evicted => return Err("consumer fell behind the retained blocks".to_string()),
+                        }
+                    }
+                }
+            };
+
+            if result.is_err() {
+                return Ok(());
+            }
+        }
+
+        Ok(())
+    }};
+}
+
+async fn serve_n2c_chainsync(server: &mut chainsync::N2CServer, shared: &Shared) -> Result<(), String> {
+    serve_chainsync!(server, shared, block_content)
+}
+
+async fn serve_n2n_chainsync(server: &mut chainsync::N2NServer, shared: &Shared) -> Result<(), String> {
+    serve_chainsync!(server, shared, header_content)
+}
+
+async fn serve_blockfetch(server: &mut blockfetch::Server, shared: &Shared) {
+    while let Ok(Some(BlockRequest((from, to)))) = server.recv_while_idle().await {
+        let blocks = shared.store.lock().unwrap().range(&from, &to);
+
+        if blocks.is_empty() {
+            if server.send_no_blocks().await.is_err() {
+                return;
+            }
+
+            continue;
+        }
+
+        if server.send_start_batch().await.is_err() {
+            return;
+        }
+
+        for block in blocks {
+            if server.send_block(block).await.is_err() {
+                return;
+            }
+        }
+
+        if server.send_batch_done().await.is_err() {
+            return;
+        }
+    }
+}
+
+async fn serve_keepalive(server: &mut keepalive::Server) {
+    while server.keepalive_roundtrip().await.is_ok() {}
+}
+
+// Highest proposed version on our network, answered with the client's own
+// version data.
+fn select_version<D: Clone>(values: &HashMap<u64, D>, magic: impl Fn(&D) -> u64, network_magic: u64) -> Option<(u64, D)> {
+    values
+        .iter()
+        .filter(|(_, data)| magic(data) == network_magic)
+        .max_by_key(|(version, _)| **version)
+        .map(|(version, data)| (*version, data.clone()))
+}
+
+async fn accept_n2c(server: &mut handshake::N2CServer, network_magic: u64) -> Result<(), String> {
+    let versions = server.receive_proposed_versions().await.map_err(|e| format!("{:?}", e))?;
+
+    match select_version(&versions.values, |data: &n2c::VersionData| VersionInfo::n2c(0, data).network_magic, network_magic) {
+        Some((version, data)) => server.accept_version(version, data).await.map_err(|e| format!("{:?}", e)),
+        None => {
+            let _ = server.refuse(RefuseReason::VersionMismatch(vec![])).await;
+            Err("no proposed version on this network".to_string())
+        }
+    }
+}
+
+pub async fn accept_n2n(server: &mut handshake::N2NServer, network_magic: u64) -> Result<(), String> {
+    let versions = server.receive_proposed_versions().await.map_err(|e| format!("{:?}", e))?;
+
+    match select_version(&versions.values, |data: &n2n::VersionData| VersionInfo::n2n(0, data).network_magic, network_magic) {
+        Some((version, data)) => server.accept_version(version, data).await.map_err(|e| format!("{:?}", e)),
+        None => {
+            let _ = server.refuse(RefuseReason::VersionMismatch(vec![])).await;
+            Err("no proposed version on this network".to_string())
+        }
+    }
+}
+
+// Serves one downstream connection until the consumer leaves or the server
+// is stopped.
+async fn serve(bearer: Bearer, shared: Arc<Shared>, cancel: CancellationToken) {
+    let mut plexer = Plexer::new(bearer);
+
+    match shared.kind {
+        ClientKind::N2C => {
+            let handshake_channel = plexer.subscribe_server(PROTOCOL_N2C_HANDSHAKE);
+            let chainsync_channel = plexer.subscribe_server(PROTOCOL_N2C_CHAIN_SYNC);
+            let plexer = plexer.spawn();
+
+            if let Err(e) = accept_n2c(&mut handshake::Server::new(handshake_channel), shared.network_magic).await {
+                println!("server handshake error: {}", e);
+                return plexer.abort().await;
+            }
+
+            let mut chainsync = chainsync::Server::new(chainsync_channel);
+
+            tokio::select! {
+                result = serve_n2c_chainsync(&mut chainsync, &shared) => {
+                    if let Err(e) = result {
+                        println!("server error: {}", e);
+                    }
+                },
+                _ = cancel.cancelled() => {},
+            }
+
+            plexer.abort().await
+        }
+        ClientKind::N2N => {
+            let handshake_channel = plexer.subscribe_server(PROTOCOL_N2N_HANDSHAKE);
+            let chainsync_channel = plexer.subscribe_server(PROTOCOL_N2N_CHAIN_SYNC);
+            let blockfetch_channel = plexer.subscribe_server(PROTOCOL_N2N_BLOCK_FETCH);
+            let keepalive_channel = plexer.subscribe_server(PROTOCOL_N2N_KEEP_ALIVE);
+            let plexer = plexer.spawn();
+
+            if let Err(e) = accept_n2n(&mut handshake::Server::new(handshake_channel), shared.network_magic).await {
+                println!("server handshake error: {}", e);
+                return plexer.abort().await;
+            }
+
+            let mut chainsync = chainsync::Server::new(chainsync_channel);
+            let mut blockfetch = blockfetch::Server::new(blockfetch_channel);
+            let mut keepalive = keepalive::Server::new(keepalive_channel);
+            let evicted = CancellationToken::new();
+
+            let chainsync = async {
+                if let Err(e) = serve_n2n_chainsync(&mut chainsync, &shared).await {
+                    println!("server error: {}", e);
+                    evicted.cancel();
+                }
+            };
+
+            // A consumer may only use block-fetch, so the connection stays up
+            // until both chain-facing protocols are done
+            tokio::select! {
+                _ = async { tokio::join!(chainsync, serve_blockfetch(&mut blockfetch, &shared)) } => {},
+                _ = evicted.cancelled() => {},
+                _ = async { serve_keepalive(&mut keepalive).await; std::future::pending::<()>().await } => {},
+                _ = cancel.cancelled() => {},
+            }
+
+            plexer.abort().await
+        }
+    }
+}
+
+// Listener serving the blocks pushed by the host to downstream chain-sync
+// and block-fetch clients.
+pub struct ChainServer {
+    shared: Arc<Shared>,
+    address: String,
+    socket: Option<String>,
+    cancel: CancellationToken,
+    task: JoinHandle<()>,
+}
+
+impl ChainServer {
+    pub async fn start(address: &str, network_magic: u64, kind: ClientKind, bearer: BearerKind, capacity: usize) -> Result<ChainServer, String> {
+        let listener = bearer.listen(address).await?;
+
+        let shared = Arc::new(Shared {
+            store: Mutex::new(ChainStore {
+                blocks: VecDeque::new(),
+                next_seq: 0,
+                capacity,
+                evicted: 0,
+            }),
+            changes: watch::channel(()).0,
+            network_magic,
+            kind,
+        });
+
+        let cancel = CancellationToken::new();
+        let address = listener.address();
+
+        let socket = match bearer {
+            BearerKind::Unix => Some(address.clone()),
+            _ => None,
+        };
+
+        let task = {
+            let shared = shared.clone();
+            let cancel = cancel.clone();
+
+            runtime::spawn(async move {
+                loop {
+                    let bearer = tokio::select! {
+                        bearer = listener.accept() => bearer,
+                        _ = cancel.cancelled() => break,
+                    };
+
+                    match bearer {
+                        Ok(bearer) => {
+                            tokio::spawn(serve(bearer, shared.clone(), cancel.clone()));
+                        }
+                        Err(e) => println!("server error: {}", e),
+                    }
+                }
+            })
+        };
+
+        Ok(ChainServer {
+            shared,
+            address,
+            socket,
+            cancel,
+            task,
+        })
+    }
+
+    fn changed(&self) {
+        self.shared.changes.send_replace(());
+    }
+
+    pub fn roll_forward(&self, block_cbor: Vec<u8>) -> Result<(), String> {
+        self.shared.store.lock().unwrap().push(block_cbor)?;
+        self.changed();
+        Ok(())
+    }
+
+    pub fn roll_backward(&self, point: &PallasPoint) -> Result<(), String> {
+        if !self.shared.store.lock().unwrap().roll_back(point) {
+            return Err(format!("{:?} is not in the store", point));
+        }
+
+        self.changed();
+        Ok(())
+    }
+
+    pub fn tip(&self) -> Tip {
+        self.shared.store.lock().unwrap().tip()
+    }
+
+    pub async fn stop(self) {
+        self.cancel.cancel();
+        let _ = self.task.await;
+
+        if let Some(socket) = &self.socket {
+            let _ = std::fs::remove_file(socket);
+        }
+    }
+}
+
+#[derive(Net, Clone)]
+pub struct ServerWrapper {
+    server_ptr: usize,
+}
+
+impl ServerWrapper {
+    pub fn server(&self) -> &ChainServer {
+        unsafe { &*(self.server_ptr as *const ChainServer) }
+    }
+
+    // `client` selects the protocols served (N2C chain-sync with full blocks,
+    // or N2N chain-sync with headers plus block-fetch). Only the last
+    // `capacity` blocks are kept, 0 keeps all of them. None when the address
+    // cannot be bound.
+    #[net]
+    pub fn start_server(address: String, network_magic: u64, client: u8, bearer: u8, capacity: u64) -> Option<ServerWrapper> {
+        ServerWrapper::start_server(address, network_magic, client, bearer, capacity)
+    }
+
+    pub fn start_server(address: String, network_magic: u64, client: u8, bearer: u8, capacity: u64) -> Option<ServerWrapper> {
+        let server = ClientKind::try_from(client)
+            .and_then(|kind| Ok((kind, BearerKind::select(bearer, kind)?)))
+            .and_then(|(kind, bearer)| runtime::block_on(ChainServer::start(&address, network_magic, kind, bearer, capacity as usize)));
+
+        match server {
+            Ok(server) => Some(ServerWrapper {
+                server_ptr: Box::into_raw(Box::new(server)) as usize,
+            }),
+            Err(e) => {
+                println!("start_server error: {}", e);
+                None
+            }
+        }
+    }
+
+    // Bound address, resolves the port when listening on port 0
+    #[net]
+    pub fn get_server_address(server_wrapper: ServerWrapper) -> String {
+        ServerWrapper::get_server_address(server_wrapper)
+    }
+
+    pub fn get_server_address(server_wrapper: ServerWrapper) -> String {
+        server_wrapper.server().address.clone()
+    }
+
+    // Appends a block in the `[era, block]` envelope chain-sync delivers
+    #[net]
+    pub fn server_roll_forward(server_wrapper: ServerWrapper, block_cbor: Vec<u8>) -> bool {
+        ServerWrapper::server_roll_forward(server_wrapper, block_cbor)
+    }
+
+    pub fn server_roll_forward(server_wrapper: ServerWrapper, block_cbor: Vec<u8>) -> bool {
+        match server_wrapper.server().roll_forward(block_cbor) {
+            Ok(()) => true,
+            Err(e) => {
+                println!("server_roll_forward error: {}", e);
+                false
+            }
+        }
+    }
+
+    // Drops every block after `point`, an empty hash rolls back to origin
+    #[net]
+    pub fn server_roll_backward(server_wrapper: ServerWrapper, point: Point) -> bool {
+        ServerWrapper::server_roll_backward(server_wrapper, point)
+    }
+
+    pub fn server_roll_backward(server_wrapper: ServerWrapper, point: Point) -> bool {
+        match server_wrapper.server().roll_backward(&point.into()) {
+            Ok(()) => true,
+            Err(e) => {
+                println!("server_roll_backward error: {}", e);
+                false
+            }
+        }
+    }
+
+    #[net]
+    pub fn get_server_tip(server_wrapper: ServerWrapper) -> Point {
+        ServerWrapper::get_server_tip(server_wrapper)
+    }
+
+    pub fn get_server_tip(server_wrapper: ServerWrapper) -> Point {
+        server_wrapper.server().tip().0.into()
+    }
+
+    // Closes the listener and every downstream connection
+    #[net]
+    pub fn stop_server(server_wrapper: ServerWrapper) {
+        ServerWrapper::stop_server(server_wrapper)
+    }
+
+    pub fn stop_server(server_wrapper: ServerWrapper) {
+        let server = unsafe { Box::from_raw(server_wrapper.server_ptr as *mut ChainServer) };
+        runtime::block_on(server.stop());
+    }
+}
@@ -0,0 +1,365 @@
+use std::{
+    collections::VecDeque,
+    ops::{Deref, DerefMut},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use pallas::network::miniprotocols::{chainsync::Tip, localstate::queries_v16, Point as PallasPoint};
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    bearer::ClientKind,
+    events::{ChainEvents, EventLog},
+    filter::TxFilter,
+    finality::FinalityBuffer,
+    options::{interruptible, with_timeout, ClientOptions, Interrupted},
+    peer_set::{PeerSet, PeerTable},
+    utxo::UtxoIndex,
+    versions::VersionInfo,
+    Client, NextResponse, ACTION_RECONNECTED,
+};
+
+// Number of delivered points offered to the node when resuming, so that a
+// rollback that happened while disconnected still finds an intersection.
+const RESUME_WINDOW: usize = 32;
+
+// Appends the consumer's new position to `recent`, oldest first. A rollback
+// drops every point past it, only the last RESUME_WINDOW points are kept.
+pub fn record(recent: &mut VecDeque<PallasPoint>, point: PallasPoint) {
+    match &point {
+        PallasPoint::Specific(slot, _) => recent.retain(|p| matches!(p, PallasPoint::Specific(s, _) if s < slot)),
+        PallasPoint::Origin => recent.clear(),
+    }
+
+    recent.push_back(point);
+
+    while recent.len() > RESUME_WINDOW {
+        recent.pop_front();
+    }
+}
+
+pub const RECONNECT_ATTEMPTS: u32 = 10;
+pub const RECONNECT_BACKOFF: Duration = Duration::from_secs(1);
+pub const RECONNECT_BACKOFF_MAX: Duration = Duration::from_secs(60);
+
+pub enum Target {
+    Single {
+        path_or_server: String,
+        network_magic: u64,
+        client: ClientKind,
+    },
+    PeerSet {
+        servers: Vec<String>,
+        network_magic: u64,
+        fetch_from_fastest: bool,
+    },
+}
+
+impl Target {
+    async fn connect(&self, options: &ClientOptions, peers: &Arc<Mutex<PeerTable>>) -> Result<(Client, VersionInfo), String> {
+        match self {
+            Target::Single {
+                path_or_server,
+                network_magic,
+                client,
+            } => {
+                let (client, version) = Client::connect(path_or_server.clone(), *network_magic, *client, options).await?;
+
+                *peers.lock().unwrap() = match &client {
+                    Client::N2N(peer) => PeerTable::single(path_or_server.clone(), peer.handles.clone()),
+                    _ => PeerTable::default(),
+                };
+
+                Ok((client, version))
+            }
+            Target::PeerSet {
+                servers,
+                network_magic,
+                fetch_from_fastest,
+            } => PeerSet::connect(servers.clone(), *network_magic, options, *fetch_from_fastest, peers.clone())
+                .await
+                .map(|(peer_set, version)| (Client::PeerSet(peer_set), version)),
+        }
+    }
+}
+
+// What the responses go through besides the consumer. Shared with
+// ClientState, so both can be replaced while a task holds the connection.
+#[derive(Default)]
+pub struct Followers {
+    // Follows every response delivered to the consumer
+    pub indexer: Option<Arc<Mutex<UtxoIndex>>>,
+    // Applied after the indexer, which needs the full blocks
+    pub filter: Option<TxFilter>,
+}
+
+// Connection that remembers the points delivered to the consumer. When the
+// node goes away it reconnects with exponential backoff, intersects at the
+// most recent of those points and reports ACTION_RECONNECTED. Failed
+// attempts are logged to stderr, stdout is left to the host application.
+pub struct Session {
+    client: Client,
+    target: Target,
+    peers: Arc<Mutex<PeerTable>>,
+    // Oldest first
+    recent: VecDeque<PallasPoint>,
+    followers: Arc<Mutex<Followers>>,
+    // Only fed by next_events
+    events: EventLog,
+    // Shared with ClientState, read without holding the connection
+    finality: Arc<Mutex<FinalityBuffer>>,
+    // Negotiated by the current connection, replaced on reconnect
+    version: Arc<Mutex<VersionInfo>>,
+}
+
+impl Deref for Session {
+    type Target = Client;
+
+    fn deref(&self) -> &Client {
+        &self.client
+    }
+}
+
+impl DerefMut for Session {
+    fn deref_mut(&mut self) -> &mut Client {
+        &mut self.client
+    }
+}
+
+impl Session {
+    pub async fn connect(target: Target, options: &ClientOptions, peers: Arc<Mutex<PeerTable>>) -> Result<Session, String> {
+        let (client, version) = target.connect(options, &peers).await?;
+
+        let session = Session {
+            client,
+            target,
+            peers,
+            recent: VecDeque::new(),
+            followers: Arc::new(Mutex::new(Followers::default())),
+            events: EventLog::default(),
+            finality: Arc::new(Mutex::new(FinalityBuffer::default())),
+            version: Arc::new(Mutex::new(version)),
+        };
+
+        Ok(session)
+    }
+
+    fn record(&mut self, point: PallasPoint) {
+        record(&mut self.recent, point);
+    }
+
+    // Point of the last response delivered, `None` before any
+    pub fn position(&self) -> Option<&PallasPoint> {
+        self.recent.back()
+    }
+
+    pub fn followers(&self) -> Arc<Mutex<Followers>> {
+        self.followers.clone()
+    }
+
+    pub fn finality(&self) -> Arc<Mutex<FinalityBuffer>> {
+        self.finality.clone()
+    }
+
+    pub fn version(&self) -> Arc<Mutex<VersionInfo>> {
+        self.version.clone()
+    }
+
+    fn intersected(&mut self, point: PallasPoint) {
+        if let Some(indexer) = &self.followers.lock().unwrap().indexer {
+            indexer.lock().unwrap().intersect(&point);
+        }
+
+        self.finality.lock().unwrap().roll_back(&point);
+
+        self.record(point);
+    }
+
+    pub async fn find_intersect(&mut self, points: Vec<PallasPoint>) -> Result<(Option<PallasPoint>, Tip), String> {
+        let (intersect, tip) = self.client.find_intersect(points).await?;
+
+        self.recent.clear();
+
+        if let Some(point) = &intersect {
+            self.intersected(point.clone());
+        }
+
+        Ok((intersect, tip))
+    }
+
+    // Like find_intersect, except a request cancelled at the tip does not
+    // hold it up: the node keeps the agency until its next block, so the
+    // connection is replaced by one intersected at `points` instead.
+    pub async fn rewind(&mut self, points: Vec<PallasPoint>, options: &ClientOptions) -> Result<PallasPoint, String> {
+        if self.client.is_idle() {
+            return match with_timeout(self.find_intersect(points), options.request_timeout()).await {
+                Ok(Ok((Some(point), _))) => Ok(point),
+                Ok(Ok((None, _))) => Err("none of the points is on the node's chain".to_string()),
+                Ok(Err(e)) => Err(e),
+                Err(e) => Err(format!("{:?}", e)),
+            };
+        }
+
+        self.recent.clear();
+
+        match self.replace(points, options).await? {
+            Some((point, _)) => Ok(point),
+            None => Err("no point to rewind to".to_string()),
+        }
+    }
+
+    // The node's tip. Node-to-node chain-sync only reports it while
+    // intersecting, so the consumer's position is intersected again right
+    // after and the node answers the next request with a rollback to it.
+    pub async fn tip(&mut self) -> Result<PallasPoint, String> {
+        let tip = match &mut self.client {
+            Client::N2C(client) => {
+                let client = client.statequery();
+
+                client.acquire(None).await.map_err(|e| format!("{:?}", e))?;
+
+                return queries_v16::get_chain_point(client).await.map_err(|e| format!("{:?}", e));
+            }
+            client => match client.peer() {
+                Some(peer) => peer.chainsync().intersect_tip().await.map_err(|e| format!("{:?}", e))?,
+                None => return Err("no peer to ask for the tip".to_string()),
+            },
+        };
+
+        let points = match self.recent.is_empty() {
+            true => vec![PallasPoint::Origin],
+            false => self.recent.iter().rev().cloned().collect(),
+        };
+
+        match self.client.find_intersect(points).await? {
+            (Some(_), _) => Ok(tip),
+            (None, _) => Err("the consumer's position is no longer on the node's chain".to_string()),
+        }
+    }
+
+    // Blocks filtered out entirely are skipped without reaching the consumer
+    pub async fn next_response(&mut self, options: &ClientOptions, cancel: &CancellationToken) -> NextResponse {
+        loop {
+            let (mut next_response, point) = self.client.next_response(options, cancel).await;
+
+            if next_response.is_error() {
+                return match options.max_reconnect_attempts() {
+                    0 => next_response,
+                    attempts => self.reconnect(attempts, options, cancel).await,
+                };
+            }
+
+            let retained = {
+                let followers = self.followers.lock().unwrap();
+
+                if let Some(indexer) = &followers.indexer {
+                    indexer.lock().unwrap().follow(&next_response, point.as_ref());
+                }
+
+                self.finality.lock().unwrap().follow(&next_response, point.as_ref(), followers.filter.as_ref());
+
+                followers.filter.as_ref().is_none_or(|filter| filter.retain(&mut next_response))
+            };
+
+            if let Some(point) = point {
+                self.record(point);
+            }
+
+            if retained {
+                return next_response;
+            }
+        }
+    }
+
+    pub async fn next_events(&mut self, options: &ClientOptions, cancel: &CancellationToken) -> ChainEvents {
+        let next_response = self.next_response(options, cancel).await;
+        let position = self.position().cloned();
+        let events = self.events.on_response(&next_response, position.as_ref());
+
+        ChainEvents { events, ..next_response.into() }
+    }
+
+    async fn reconnect(&mut self, attempts: u32, options: &ClientOptions, cancel: &CancellationToken) -> NextResponse {
+        let mut backoff = options.reconnect_backoff();
+
+        for attempt in 1..=attempts {
+            if interruptible(tokio::time::sleep(backoff), None, cancel).await.is_err() {
+                return NextResponse::cancelled();
+            }
+
+            backoff = (backoff * 2).min(RECONNECT_BACKOFF_MAX);
+
+            match interruptible(self.resume(options), None, cancel).await {
+                Ok(Ok(tip)) => {
+                    return NextResponse {
+                        action: ACTION_RECONNECTED,
+                        tip: tip.map(|tip| tip.0.into()),
+                        block_cbor: None,
+                    }
+                }
+                Ok(Err(e)) => eprintln!("reconnect attempt {} failed: {}", attempt, e),
+                Err(Interrupted::Cancelled) => return NextResponse::cancelled(),
+                Err(Interrupted::TimedOut) => eprintln!("reconnect attempt {} timed out", attempt),
+            }
+        }
+
+        eprintln!("chain_sync_next error: giving up after {} reconnect attempts", attempts);
+        NextResponse::error()
+    }
+
+    // Replaces the connection and intersects at the recent points, newest
+    // first. Without any delivered point chain-sync restarts from origin.
+    async fn resume(&mut self, options: &ClientOptions) -> Result<Option<Tip>, String> {
+        let points = self.recent.iter().rev().cloned().collect();
+
+        Ok(self.replace(points, options).await?.map(|(_, tip)| tip))
+    }
+
+    // The new connection only replaces the old one once it intersected at
+    // `points`, so a replacement dropped half way leaves the old one to fail
+    // and resume again. No points leaves chain-sync at origin.
+    async fn replace(&mut self, points: Vec<PallasPoint>, options: &ClientOptions) -> Result<Option<(PallasPoint, Tip)>, String> {
+        let (mut client, version) = self.target.connect(options, &self.peers).await?;
+
+        let intersect = match points.is_empty() {
+            true => Ok(None),
+            false => intersect_at(&mut client, points, options).await.map(Some),
+        };
+
+        let intersect = match intersect {
+            Ok(intersect) => intersect,
+            Err(e) => {
+                client.abort().await;
+                return Err(e);
+            }
+        };
+
+        let previous = std::mem::replace(&mut self.client, client);
+        previous.abort().await;
+
+        // The node may have been upgraded while it was away
+        *self.version.lock().unwrap() = version;
+
+        if let Some((point, _)) = &intersect {
+            self.intersected(point.clone());
+        }
+
+        Ok(intersect)
+    }
+
+    pub async fn abort(self) {
+        self.client.abort().await
+    }
+}
+
+async fn intersect_at(client: &mut Client, points: Vec<PallasPoint>, options: &ClientOptions) -> Result<(PallasPoint, Tip), String> {
+    let intersect = with_timeout(client.find_intersect(points), options.request_timeout())
+        .await
+        .map_err(|_| "intersect timed out".to_string())??;
+
+    match intersect {
+        (Some(point), tip) => Ok((point, tip)),
+        (None, _) => Err("none of the points is on the node's chain".to_string()),
+    }
+}
@@ -0,0 +1,560 @@
+use std::{
+    collections::VecDeque,
+    fs::{File, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use pallas::{codec::minicbor::Encoder, network::miniprotocols::Point as PallasPoint};
+use rnet::{net, Net};
+use serde_json::{json, Value};
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    options::{interruptible, ClientOptions},
+    runtime,
+    session::Session,
+    ClientWrapper, NextResponse, Point, ACTION_AWAIT, ACTION_CANCELLED, ACTION_ROLL_BACK, ACTION_ROLL_FORWARD,
+};
+
+// Blocks concatenated as served, rollbacks truncate the files
+pub const SINK_RAW_CBOR: u8 = 0;
+// One JSON object per forward or rollback, blocks as hex
+pub const SINK_NDJSON: u8 = 1;
+// One `[action, point, block?]` CBOR item per forward or rollback
+pub const SINK_CBOR_SEQ: u8 = 2;
+
+const CHECKPOINT_FILE: &str = "checkpoint.json";
+
+// Positions written to the checkpoint, offered to the node when resuming
+const CHECKPOINT_POINTS: usize = 32;
+
+// Forwards written between checkpoints. Rollbacks, reaching the tip and
+// stopping checkpoint right away.
+const CHECKPOINT_BLOCKS: u64 = 100;
+
+// Positions kept in memory for raw rollbacks, mainnet's k
+const ROLLBACK_WINDOW: usize = 2160;
+
+#[derive(Net, Clone)]
+pub struct SinkOptions {
+    pub(crate) directory: String,
+    pub(crate) format: u8,
+    // A new file is started once the current one reaches this size, 0 never rotates
+    pub(crate) max_file_bytes: u64,
+}
+
+#[derive(Net)]
+pub struct SinkStatus {
+    pub(crate) running: bool,
+    // Last point written, the checkpoint can be up to CHECKPOINT_BLOCKS behind
+    pub(crate) last: Option<Point>,
+    // Empty unless the sink stopped on an error
+    pub(crate) error: String,
+}
+
+// Where the record of a point ends
+#[derive(Clone)]
+struct Position {
+    point: PallasPoint,
+    file: u64,
+    offset: u64,
+}
+
+impl Position {
+    fn to_json(&self) -> Value {
+        let (slot, hash) = match &self.point {
+            PallasPoint::Origin => (0, vec![]),
+            PallasPoint::Specific(slot, hash) => (*slot, hash.clone()),
+        };
+
+        json!({ "slot": slot, "hash": hex::encode(hash), "file": self.file, "offset": self.offset })
+    }
+
+    fn from_json(value: &Value) -> Option<Position> {
+        let hash = hex::decode(value["hash"].as_str()?).ok()?;
+
+        Some(Position {
+            point: Point { slot: value["slot"].as_u64()?, hash }.into(),
+            file: value["file"].as_u64()?,
+            offset: value["offset"].as_u64()?,
+        })
+    }
+}
+
+// Rotated files `000000.<ext>`, `000001.<ext>`... in a directory, next to a
+// checkpoint holding the positions of the last points written. Reopening
+// cuts the files back to the newest checkpointed position that made it to
+// disk, so nothing is written twice after a restart. What was written past
+// it is fetched again.
+pub struct FileSink {
+    directory: PathBuf,
+    format: u8,
+    max_file_bytes: u64,
+    file: File,
+    position: Position,
+    // Oldest first
+    written: VecDeque<Position>,
+    // Forwards written since the last checkpoint
+    unsaved: u64,
+}
+
+impl FileSink {
+    pub fn open(options: &SinkOptions) -> Result<FileSink, String> {
+        if options.format > SINK_CBOR_SEQ {
+            return Err(format!("unknown sink format {}", options.format));
+        }
+
+        let directory = PathBuf::from(&options.directory);
+        std::fs::create_dir_all(&directory).map_err(|e| format!("{:?}", e))?;
+
+        let extension = extension(options.format);
+        let file_len = |file: u64| std::fs::metadata(file_path(&directory, file, extension)).map(|m| m.len()).ok();
+
+        let mut written: VecDeque<Position> = read_checkpoint(&directory)
+            .into_iter()
+            .take_while(|position| file_len(position.file).is_some_and(|len| len >= position.offset))
+            .collect();
+
+        // Without a checkpoint the files already there are left alone
+        let position = match written.back() {
+            Some(position) => position.clone(),
+            None => {
+                let file = existing_files(&directory, extension).last().map_or(0, |file| file + 1);
+
+                Position {
+                    point: PallasPoint::Origin,
+                    file,
+                    offset: 0,
+                }
+            }
+        };
+
+        if written.is_empty() {
+            written.push_back(position.clone());
+        }
+
+        let mut sink = FileSink {
+            file: open_file(&directory, position.file, extension).map_err(|e| format!("{:?}", e))?,
+            directory,
+            format: options.format,
+            max_file_bytes: options.max_file_bytes,
+            position,
+            written,
+            unsaved: 0,
+        };
+
+        sink.truncate().map_err(|e| format!("{:?}", e))?;
+
+        Ok(sink)
+    }
+
+    // Points to intersect at, newest first. Origin for a new sink.
+    pub fn resume_points(&self) -> Vec<PallasPoint> {
+        self.written.iter().rev().map(|position| position.point.clone()).collect()
+    }
+
+    pub fn last(&self) -> &PallasPoint {
+        &self.position.point
+    }
+
+    // Cuts the files back to the current position
+    fn truncate(&mut self) -> std::io::Result<()> {
+        for file in existing_files(&self.directory, extension(self.format)) {
+            if file > self.position.file {
+                std::fs::remove_file(file_path(&self.directory, file, extension(self.format)))?;
+            }
+        }
+
+        self.file = open_file(&self.directory, self.position.file, extension(self.format))?;
+        self.file.set_len(self.position.offset)
+    }
+
+    fn append(&mut self, record: &[u8]) -> std::io::Result<()> {
+        if self.max_file_bytes > 0 && self.position.offset > 0 && self.position.offset + record.len() as u64 > self.max_file_bytes {
+            // Only the current file is synced by a checkpoint
+            self.file.sync_data()?;
+            self.position.file += 1;
+            self.position.offset = 0;
+            self.file = open_file(&self.directory, self.position.file, extension(self.format))?;
+        }
+
+        self.file.write_all(record)?;
+        self.file.flush()?;
+        self.position.offset += record.len() as u64;
+
+        Ok(())
+    }
+
+    // `point` is where the consumer is after the response
+    pub fn write(&mut self, response: &NextResponse, point: Option<&PallasPoint>) -> Result<(), String> {
+        let point = match (response.action, point) {
+            (ACTION_ROLL_FORWARD | ACTION_ROLL_BACK, Some(point)) => point.clone(),
+            // At the tip the next block can be a while away
+            (ACTION_AWAIT, _) => return self.checkpoint(),
+            _ => return Ok(()),
+        };
+
+        // Resuming starts with a rollback to the position the sink is at
+        if response.action == ACTION_ROLL_BACK && point == self.position.point {
+            return Ok(());
+        }
+
+        match (response.action, self.format) {
+            (ACTION_ROLL_BACK, SINK_RAW_CBOR) => self.roll_back(&point),
+            (action, format) => {
+                let record = match format {
+                    SINK_RAW_CBOR => response.block_cbor.clone().unwrap_or_default(),
+                    SINK_NDJSON => ndjson_record(action, &point, response.block_cbor.as_deref()),
+                    _ => cbor_record(action, &point, response.block_cbor.as_deref()),
+                };
+
+                self.append(&record).map_err(|e| format!("{:?}", e))
+            }
+        }?;
+
+        self.position.point = point;
+        self.remember();
+        self.unsaved += 1;
+
+        if response.action == ACTION_ROLL_BACK || self.unsaved >= CHECKPOINT_BLOCKS {
+            self.checkpoint()?;
+        }
+
+        Ok(())
+    }
+
+    // Saves the positions written since the last checkpoint, if any
+    pub fn checkpoint(&mut self) -> Result<(), String> {
+        if self.unsaved == 0 {
+            return Ok(());
+        }
+
+        self.save_checkpoint().map_err(|e| format!("cannot write checkpoint: {:?}", e))?;
+        self.unsaved = 0;
+
+        Ok(())
+    }
+
+    // Blocks dropped by a tx filter were never written, a rollback to one of
+    // them goes back to the last block written before it
+    fn roll_back(&mut self, point: &PallasPoint) -> Result<(), String> {
+        let before = |position: &Position| match (&position.point, point) {
+            (PallasPoint::Specific(slot, _), PallasPoint::Specific(to, _)) => slot < to,
+            _ => false,
+        };
+
+        match self.written.iter().rposition(|position| position.point == *point || before(position)) {
+            Some(i) => {
+                self.written.truncate(i + 1);
+                self.position = self.written[i].clone();
+                self.truncate().map_err(|e| format!("{:?}", e))
+            }
+            None => Err(format!("rollback to {:?} is past the sink's rollback window", point)),
+        }
+    }
+
+    // A rollback drops the positions past its point. Origin stays as the
+    // position the sink started at.
+    fn remember(&mut self) {
+        let slot = |point: &PallasPoint| match point {
+            PallasPoint::Origin => 0,
+            PallasPoint::Specific(slot, _) => *slot,
+        };
+
+        let current = slot(&self.position.point);
+
+        match self.position.point {
+            PallasPoint::Origin => self.written.clear(),
+            _ => self.written.retain(|position| position.point == PallasPoint::Origin || slot(&position.point) < current),
+        }
+
+        self.written.push_back(self.position.clone());
+
+        while self.written.len() > ROLLBACK_WINDOW {
+            self.written.pop_front();
+        }
+    }
+
+    // The records are synced first, so the checkpoint never points past what
+    // is on disk. Written aside and renamed, a crash leaves either checkpoint
+    // whole.
+    fn save_checkpoint(&self) -> std::io::Result<()> {
+        self.file.sync_data()?;
+
+        let positions: Vec<Value> = self
+            .written
+            .iter()
+            .rev()
+            .take(CHECKPOINT_POINTS)
+            .map(Position::to_json)
+            .collect();
+
+        let path = self.directory.join(CHECKPOINT_FILE);
+        let temp = path.with_extension("tmp");
+
+        let mut file = File::create(&temp)?;
+        file.write_all(Value::Array(positions).to_string().as_bytes())?;
+        file.sync_all()?;
+
+        std::fs::rename(temp, path)
+    }
+}
+
+fn extension(format: u8) -> &'static str {
+    match format {
+        SINK_RAW_CBOR => "cbor",
+        SINK_NDJSON => "ndjson",
+        _ => "cborseq",
+    }
+}
+
+fn file_path(directory: &Path, file: u64, extension: &str) -> PathBuf {
+    directory.join(format!("{:06}.{}", file, extension))
+}
+
+fn open_file(directory: &Path, file: u64, extension: &str) -> std::io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(file_path(directory, file, extension))
+}
+
+// Sorted file numbers
+fn existing_files(directory: &Path, extension: &str) -> Vec<u64> {
+    let mut files: Vec<u64> = std::fs::read_dir(directory)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.ok()?.file_name().into_string().ok()?;
+            name.strip_suffix(extension)?.strip_suffix('.')?.parse().ok()
+        })
+        .collect();
+
+    files.sort();
+    files
+}
+
+// Newest last, empty when missing or unreadable
+fn read_checkpoint(directory: &Path) -> Vec<Position> {
+    let Ok(contents) = std::fs::read_to_string(directory.join(CHECKPOINT_FILE)) else {
+        return vec![];
+    };
+
+    match serde_json::from_str::<Value>(&contents) {
+        Ok(Value::Array(positions)) => positions.iter().rev().filter_map(Position::from_json).collect(),
+        _ => vec![],
+    }
+}
+
+fn point_json(point: &PallasPoint) -> Value {
+    match point {
+        PallasPoint::Origin => json!({ "slot": 0, "hash": "" }),
+        PallasPoint::Specific(slot, hash) => json!({ "slot": slot, "hash": hex::encode(hash) }),
+    }
+}
+
+fn ndjson_record(action: u8, point: &PallasPoint, block_cbor: Option<&[u8]>) -> Vec<u8> {
+    let record = match block_cbor {
+        Some(block_cbor) if action == ACTION_ROLL_FORWARD => {
+            json!({ "action": "roll_forward", "point": point_json(point), "block": hex::encode(block_cbor) })
+        }
+        _ => json!({ "action": "roll_back", "point": point_json(point) }),
+    };
+
+    let mut line = record.to_string().into_bytes();
+    line.push(b'\n');
+    line
+}
+
+// The block is embedded as the CBOR item it already is
+fn cbor_record(action: u8, point: &PallasPoint, block_cbor: Option<&[u8]>) -> Vec<u8> {
+    let mut e = Encoder::new(vec![]);
+
+    match block_cbor {
+        Some(block_cbor) if action == ACTION_ROLL_FORWARD => {
+            e.array(3).unwrap().u8(action).unwrap().encode(point).unwrap();
+            e.writer_mut().extend_from_slice(block_cbor);
+        }
+        _ => {
+            e.array(2).unwrap().u8(action).unwrap().encode(point).unwrap();
+        }
+    }
+
+    e.into_writer()
+}
+
+#[derive(Default)]
+struct SinkState {
+    last: Option<PallasPoint>,
+    error: Option<String>,
+}
+
+// Chain-sync driven by a task on the runtime, every response written to a
+// FileSink. Nothing is handed to .NET, the sink is the consumer.
+pub struct SinkTask {
+    cancel: CancellationToken,
+    task: JoinHandle<()>,
+    state: Arc<Mutex<SinkState>>,
+}
+
+impl SinkTask {
+    pub fn start(connection: Arc<tokio::sync::Mutex<Session>>, options: ClientOptions, mut sink: FileSink) -> SinkTask {
+        let cancel = CancellationToken::new();
+
+        let state = Arc::new(Mutex::new(SinkState {
+            last: Some(sink.last().clone()),
+            error: None,
+        }));
+
+        let task = {
+            let cancel = cancel.clone();
+            let state = state.clone();
+
+            runtime::spawn(async move {
+                loop {
+                    let (next_response, point) = {
+                        let mut connection = connection.lock().await;
+                        let next_response = connection.next_response(&options, &cancel).await;
+
+                        (next_response, connection.position().cloned())
+                    };
+
+                    if next_response.action == ACTION_CANCELLED {
+                        break;
+                    }
+
+                    if next_response.is_error() {
+                        state.lock().unwrap().error = Some("chain-sync failed".to_string());
+                        break;
+                    }
+
+                    // Written without the state lock, get_sink_status must
+                    // not wait for the disk
+                    let written = sink.write(&next_response, point.as_ref());
+                    let mut state = state.lock().unwrap();
+
+                    match written {
+                        Ok(()) => state.last = Some(sink.last().clone()),
+                        Err(e) => {
+                            println!("sink error: {}", e);
+                            state.error = Some(e);
+                            break;
+                        }
+                    }
+                }
+
+                // What the loop wrote since the last checkpoint
+                if let Err(e) = sink.checkpoint() {
+                    println!("sink error: {}", e);
+                    state.lock().unwrap().error.get_or_insert(e);
+                }
+            })
+        };
+
+        SinkTask { cancel, task, state }
+    }
+
+    pub fn status(&self) -> SinkStatus {
+        let state = self.state.lock().unwrap();
+
+        SinkStatus {
+            running: !self.task.is_finished(),
+            last: state.last.clone().map(Point::from),
+            error: state.error.clone().unwrap_or_default(),
+        }
+    }
+
+    pub async fn stop(self) {
+        self.cancel.cancel();
+        let _ = self.task.await;
+    }
+}
+
+impl ClientWrapper {
+    // Runs chain-sync in the background and writes it to rotated files in
+    // `options.directory`. A directory holding a checkpoint resumes after
+    // its last point, otherwise chain-sync goes on from where it is. Returns
+    // false when the sink cannot be opened or resumed, or chain-sync is
+    // already consumed by a stream or another sink.
+    #[net]
+    pub fn start_sink(client_wrapper: ClientWrapper, options: SinkOptions) -> bool {
+        ClientWrapper::start_sink(client_wrapper, options)
+    }
+
+    pub fn start_sink(client_wrapper: ClientWrapper, options: SinkOptions) -> bool {
+        let state = client_wrapper.state();
+        let cancel = state.cancel_token();
+
+        if state.is_streaming() {
+            println!("start_sink error: chain-sync stream or sink is running");
+            return false;
+        }
+
+        let sink = match FileSink::open(&options) {
+            Ok(sink) => sink,
+            Err(e) => {
+                println!("start_sink error: {}", e);
+                return false;
+            }
+        };
+
+        let points = sink.resume_points();
+
+        if points != vec![PallasPoint::Origin] {
+            let intersect = runtime::block_on(interruptible(
+                async { state.connection.lock().await.find_intersect(points).await },
+                state.options.request_timeout(),
+                &cancel,
+            ));
+
+            match intersect {
+                Ok(Ok((Some(_), _))) => {}
+                Ok(Ok((None, _))) => {
+                    println!("start_sink error: none of the checkpointed points is on the node's chain");
+                    return false;
+                }
+                Ok(Err(e)) => {
+                    println!("start_sink error: {}", e);
+                    return false;
+                }
+                Err(e) => {
+                    println!("start_sink error: {:?}", e);
+                    return false;
+                }
+            }
+        }
+
+        *state.sink.lock().unwrap() = Some(SinkTask::start(state.connection.clone(), state.options.clone(), sink));
+
+        true
+    }
+
+    #[net]
+    pub fn get_sink_status(client_wrapper: ClientWrapper) -> SinkStatus {
+        ClientWrapper::get_sink_status(client_wrapper)
+    }
+
+    pub fn get_sink_status(client_wrapper: ClientWrapper) -> SinkStatus {
+        match client_wrapper.state().sink.lock().unwrap().as_ref() {
+            Some(sink) => sink.status(),
+            None => SinkStatus {
+                running: false,
+                last: None,
+                error: String::new(),
+            },
+        }
+    }
+
+    #[net]
+    pub fn stop_sink(client_wrapper: ClientWrapper) {
+        ClientWrapper::stop_sink(client_wrapper)
+    }
+
+    pub fn stop_sink(client_wrapper: ClientWrapper) {
+        let sink = client_wrapper.state().sink.lock().unwrap().take();
+
+        if let Some(sink) = sink {
+            runtime::block_on(sink.stop());
+        }
+    }
+}
@@ -0,0 +1,385 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::{Read, Seek, SeekFrom, Write},
+    path::Path,
+    sync::Mutex,
+};
+
+use pallas::{ledger::traverse::MultiEraBlock, network::miniprotocols::Point as PallasPoint};
+use rnet::{net, Net};
+
+use crate::{extends_tip, is_epoch_boundary, Point};
+
+const DATA_FILE: &str = "blocks.dat";
+const INDEX_FILE: &str = "blocks.idx";
+
+// slot, block number, offset and length in the data file, hash
+const ENTRY_SIZE: usize = 8 + 8 + 8 + 4 + 32;
+
+#[derive(Clone)]
+struct IndexEntry {
+    slot: u64,
+    number: u64,
+    offset: u64,
+    len: u32,
+    hash: [u8; 32],
+}
+
+impl IndexEntry {
+    fn to_bytes(&self) -> [u8; ENTRY_SIZE] {
+        let mut bytes = [0; ENTRY_SIZE];
+
+        bytes[0..8].copy_from_slice(&self.slot.to_le_bytes());
+        bytes[8..16].copy_from_slice(&self.number.to_le_bytes());
+        bytes[16..24].copy_from_slice(&self.offset.to_le_bytes());
+        bytes[24..28].copy_from_slice(&self.len.to_le_bytes());
+        bytes[28..60].copy_from_slice(&self.hash);
+
+        bytes
+    }
+
+    fn from_bytes(bytes: &[u8]) -> IndexEntry {
+        IndexEntry {
+            slot: u64::from_le_bytes(bytes[0..8].try_into().unwrap()),
+            number: u64::from_le_bytes(bytes[8..16].try_into().unwrap()),
+            offset: u64::from_le_bytes(bytes[16..24].try_into().unwrap()),
+            len: u32::from_le_bytes(bytes[24..28].try_into().unwrap()),
+            hash: bytes[28..60].try_into().unwrap(),
+        }
+    }
+
+    fn end(&self) -> u64 {
+        self.offset + self.len as u64
+    }
+
+    fn point(&self) -> PallasPoint {
+        PallasPoint::Specific(self.slot, self.hash.to_vec())
+    }
+}
+
+// Append-only block storage in a directory: the blocks are concatenated in
+// `blocks.dat` and `blocks.idx` holds one fixed-size entry per block. Slots
+// and block numbers never decrease, so the index kept in memory is searched
+// by bisection. Only a Byron epoch boundary block repeats a key: its number
+// is the one of the block before and its slot may be the one of the block
+// after. A rollback truncates both files.
+pub struct BlockStore {
+    data: File,
+    index: File,
+    entries: Vec<IndexEntry>,
+}
+
+impl BlockStore {
+    pub fn open(path: &Path) -> std::io::Result<BlockStore> {
+        std::fs::create_dir_all(path)?;
+
+        let open = |name| OpenOptions::new().read(true).write(true).create(true).truncate(false).open(path.join(name));
+        let mut data = open(DATA_FILE)?;
+        let mut index = open(INDEX_FILE)?;
+
+        let mut bytes = vec![];
+        index.read_to_end(&mut bytes)?;
+
+        let data_len = data.seek(SeekFrom::End(0))?;
+
+        // A crash can leave a partial entry or block at the end of either
+        // file, both are cut back to the last complete block
+        let entries: Vec<IndexEntry> = bytes
+            .chunks_exact(ENTRY_SIZE)
+            .map(IndexEntry::from_bytes)
+            .take_while(|entry| entry.end() <= data_len)
+            .collect();
+
+        let mut store = BlockStore { data, index, entries };
+        store.truncate(store.entries.len())?;
+
+        Ok(store)
+    }
+
+    fn truncate(&mut self, len: usize) -> std::io::Result<()> {
+        self.entries.truncate(len);
+
+        let data_len = self.entries.last().map_or(0, |entry| entry.end());
+
+        self.data.set_len(data_len)?;
+        self.index.set_len((self.entries.len() * ENTRY_SIZE) as u64)?;
+
+        Ok(())
+    }
+
+    pub fn tip(&self) -> PallasPoint {
+        self.entries.last().map_or(PallasPoint::Origin, |entry| entry.point())
+    }
+
+    fn position(&self, point: &PallasPoint) -> Option<usize> {
+        match point {
+            PallasPoint::Origin => None,
+            PallasPoint::Specific(slot, hash) => {
+                let start = self.entries.partition_point(|entry| entry.slot < *slot);
+
+                self.entries[start..]
+                    .iter()
+                    .take_while(|entry| entry.slot == *slot)
+                    .position(|entry| entry.hash.as_slice() == hash.as_slice())
+                    .map(|i| start + i)
+            }
+        }
+    }
+
+    fn read(&mut self, entry: &IndexEntry) -> std::io::Result<Vec<u8>> {
+        let mut block = vec![0; entry.len as usize];
+
+        self.data.seek(SeekFrom::Start(entry.offset))?;
+        self.data.read_exact(&mut block)?;
+
+        Ok(block)
+    }
+
+    fn read_all(&mut self, entries: Vec<IndexEntry>) -> std::io::Result<Vec<Vec<u8>>> {
+        entries.iter().map(|entry| self.read(entry)).collect()
+    }
+
+    // Blocks are expected in the `[era, block]` envelope and have to extend
+    // the tip.
+    pub fn put(&mut self, block_cbor: &[u8]) -> Result<(), String> {
+        let block = MultiEraBlock::decode(block_cbor).map_err(|e| format!("invalid block cbor: {:?}", e))?;
+
+        if let Some(tip) = self.entries.last().cloned() {
+            // Only a shared slot needs to know whether the tip is a boundary block
+            let tip_is_boundary = block.slot() == tip.slot
+                && self
+                    .read(&tip)
+                    .is_ok_and(|cbor| MultiEraBlock::decode(&cbor).is_ok_and(|block| is_epoch_boundary(&block)));
+
+            if !extends_tip(block.slot(), is_epoch_boundary(&block), tip.slot, tip_is_boundary) {
+                return Err(format!("block at slot {} does not extend the tip at slot {}", block.slot(), tip.slot));
+            }
+        }
+
+        let entry = IndexEntry {
+            slot: block.slot(),
+            number: block.number(),
+            offset: self.entries.last().map_or(0, |entry| entry.end()),
+            len: block_cbor.len() as u32,
+            hash: *block.hash(),
+        };
+
+        let write = |store: &mut BlockStore| -> std::io::Result<()> {
+            store.data.seek(SeekFrom::Start(entry.offset))?;
+            store.data.write_all(block_cbor)?;
+            store.data.flush()?;
+
+            store.index.seek(SeekFrom::End(0))?;
+            store.index.write_all(&entry.to_bytes())?;
+            store.index.flush()
+        };
+
+        if let Err(e) = write(self) {
+            // Leave the files as they were before the failed append
+            let _ = self.truncate(self.entries.len());
+            return Err(format!("{:?}", e));
+        }
+
+        self.entries.push(entry);
+
+        Ok(())
+    }
+
+    pub fn get(&mut self, point: &PallasPoint) -> std::io::Result<Option<Vec<u8>>> {
+        match self.position(point) {
+            Some(i) => self.read(&self.entries[i].clone()).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    // A boundary block is only returned when the block with its number is
+    // not stored, it always comes after that block
+    pub fn get_by_number(&mut self, number: u64) -> std::io::Result<Option<Vec<u8>>> {
+        let i = self.entries.partition_point(|entry| entry.number < number);
+
+        match self.entries.get(i) {
+            Some(entry) if entry.number == number => self.read(&entry.clone()).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    // Both ends included, empty when either point is not stored
+    pub fn range(&mut self, from: &PallasPoint, to: &PallasPoint) -> std::io::Result<Vec<Vec<u8>>> {
+        match (self.position(from), self.position(to)) {
+            (Some(start), Some(end)) if start <= end => self.read_all(self.entries[start..=end].to_vec()),
+            _ => Ok(vec![]),
+        }
+    }
+
+    // Up to `max_count` blocks following `point`, from the first block for
+    // origin, with the point of the last one. `None` when the point is not
+    // stored.
+    pub fn after(&mut self, point: &PallasPoint, max_count: usize) -> std::io::Result<Option<(Vec<Vec<u8>>, PallasPoint)>> {
+        let start = match point {
+            PallasPoint::Origin => 0,
+            point => match self.position(point) {
+                Some(i) => i + 1,
+                None => return Ok(None),
+            },
+        };
+
+        let entries: Vec<IndexEntry> = self.entries.iter().skip(start).take(max_count).cloned().collect();
+        let last = entries.last().map_or(point.clone(), |entry| entry.point());
+
+        self.read_all(entries).map(|blocks| Some((blocks, last)))
+    }
+
+    // Drops every block after `point`, origin empties the store
+    pub fn rollback_to(&mut self, point: &PallasPoint) -> Result<(), String> {
+        let len = match point {
+            PallasPoint::Origin => 0,
+            point => self.position(point).ok_or_else(|| format!("{:?} is not in the store", point))? + 1,
+        };
+
+        self.truncate(len).map_err(|e| format!("{:?}", e))
+    }
+}
+
+#[derive(Net)]
+pub struct BlockPage {
+    pub blocks: Vec<Vec<u8>>,
+    // Point to pass for the next page
+    pub last: Point,
+}
+
+#[derive(Net, Clone)]
+pub struct BlockStoreWrapper {
+    store_ptr: usize,
+}
+
+impl BlockStoreWrapper {
+    pub fn store(&self) -> &Mutex<BlockStore> {
+        unsafe { &*(self.store_ptr as *const Mutex<BlockStore>) }
+    }
+
+    // Creates the directory and its files when they don't exist yet. None
+    // when they cannot be created or read.
+    #[net]
+    pub fn open_block_store(path: String) -> Option<BlockStoreWrapper> {
+        BlockStoreWrapper::open_block_store(path)
+    }
+
+    pub fn open_block_store(path: String) -> Option<BlockStoreWrapper> {
+        match BlockStore::open(Path::new(&path)) {
+            Ok(store) => Some(BlockStoreWrapper {
+                store_ptr: Box::into_raw(Box::new(Mutex::new(store))) as usize,
+            }),
+            Err(e) => {
+                println!("open_block_store error: {:?}", e);
+                None
+            }
+        }
+    }
+
+    #[net]
+    pub fn put_block(store_wrapper: BlockStoreWrapper, block_cbor: Vec<u8>) -> bool {
+        BlockStoreWrapper::put_block(store_wrapper, block_cbor)
+    }
+
+    pub fn put_block(store_wrapper: BlockStoreWrapper, block_cbor: Vec<u8>) -> bool {
+        match store_wrapper.store().lock().unwrap().put(&block_cbor) {
+            Ok(()) => true,
+            Err(e) => {
+                println!("put_block error: {}", e);
+                false
+            }
+        }
+    }
+
+    #[net]
+    pub fn get_block(store_wrapper: BlockStoreWrapper, point: Point) -> Option<Vec<u8>> {
+        BlockStoreWrapper::get_block(store_wrapper, point)
+    }
+
+    pub fn get_block(store_wrapper: BlockStoreWrapper, point: Point) -> Option<Vec<u8>> {
+        store_wrapper.store().lock().unwrap().get(&point.into()).unwrap_or_else(|e| {
+            println!("get_block error: {:?}", e);
+            None
+        })
+    }
+
+    #[net]
+    pub fn get_block_by_number(store_wrapper: BlockStoreWrapper, number: u64) -> Option<Vec<u8>> {
+        BlockStoreWrapper::get_block_by_number(store_wrapper, number)
+    }
+
+    pub fn get_block_by_number(store_wrapper: BlockStoreWrapper, number: u64) -> Option<Vec<u8>> {
+        store_wrapper.store().lock().unwrap().get_by_number(number).unwrap_or_else(|e| {
+            println!("get_block_by_number error: {:?}", e);
+            None
+        })
+    }
+
+    #[net]
+    pub fn get_block_range(store_wrapper: BlockStoreWrapper, from: Point, to: Point) -> Vec<Vec<u8>> {
+        BlockStoreWrapper::get_block_range(store_wrapper, from, to)
+    }
+
+    pub fn get_block_range(store_wrapper: BlockStoreWrapper, from: Point, to: Point) -> Vec<Vec<u8>> {
+        store_wrapper.store().lock().unwrap().range(&from.into(), &to.into()).unwrap_or_else(|e| {
+            println!("get_block_range error: {:?}", e);
+            vec![]
+        })
+    }
+
+    // Iterates the store page by page, a page shorter than `max_count` is
+    // the last one. An empty hash starts at the first block.
+    #[net]
+    pub fn get_blocks_after(store_wrapper: BlockStoreWrapper, point: Point, max_count: u64) -> BlockPage {
+        BlockStoreWrapper::get_blocks_after(store_wrapper, point, max_count)
+    }
+
+    pub fn get_blocks_after(store_wrapper: BlockStoreWrapper, point: Point, max_count: u64) -> BlockPage {
+        let empty = |point: Point| BlockPage { blocks: vec![], last: point };
+
+        match store_wrapper.store().lock().unwrap().after(&point.clone().into(), max_count as usize) {
+            Ok(Some((blocks, last))) => BlockPage { blocks, last: last.into() },
+            Ok(None) => {
+                println!("get_blocks_after error: point is not in the store");
+                empty(point)
+            }
+            Err(e) => {
+                println!("get_blocks_after error: {:?}", e);
+                empty(point)
+            }
+        }
+    }
+
+    #[net]
+    pub fn rollback_to(store_wrapper: BlockStoreWrapper, point: Point) -> bool {
+        BlockStoreWrapper::rollback_to(store_wrapper, point)
+    }
+
+    pub fn rollback_to(store_wrapper: BlockStoreWrapper, point: Point) -> bool {
+        match store_wrapper.store().lock().unwrap().rollback_to(&point.into()) {
+            Ok(()) => true,
+            Err(e) => {
+                println!("rollback_to error: {}", e);
+                false
+            }
+        }
+    }
+
+    #[net]
+    pub fn get_block_store_tip(store_wrapper: BlockStoreWrapper) -> Point {
+        BlockStoreWrapper::get_block_store_tip(store_wrapper)
+    }
+
+    pub fn get_block_store_tip(store_wrapper: BlockStoreWrapper) -> Point {
+        store_wrapper.store().lock().unwrap().tip().into()
+    }
+
+    #[net]
+    pub fn close_block_store(store_wrapper: BlockStoreWrapper) {
+        BlockStoreWrapper::close_block_store(store_wrapper)
+    }
+
+    pub fn close_block_store(store_wrapper: BlockStoreWrapper) {
+        drop(unsafe { Box::from_raw(store_wrapper.store_ptr as *mut Mutex<BlockStore>) });
+    }
+}
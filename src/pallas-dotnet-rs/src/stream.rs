@@ -0,0 +1,122 @@
+use std::sync::Arc;
+
+use rnet::net;
+use tokio::{
+    sync::{
+        mpsc::{self, error::TryRecvError},
+        Mutex,
+    },
+    task::JoinHandle,
+};
+use tokio_util::sync::CancellationToken;
+
+use crate::{options::ClientOptions, runtime, session::Session, ClientWrapper, NextResponse, ACTION_CANCELLED, ACTION_PENDING};
+
+// Chain-sync driven by a task on the runtime. Responses are pushed into a
+// bounded channel, so the task stops pulling blocks once the buffer is full
+// and no .NET thread has to wait inside native code.
+pub struct ChainSyncStream {
+    receiver: mpsc::Receiver<NextResponse>,
+    cancel: CancellationToken,
+    task: JoinHandle<()>,
+}
+
+impl ChainSyncStream {
+    pub fn start(connection: Arc<Mutex<Session>>, options: ClientOptions, buffer_size: usize) -> ChainSyncStream {
+        let (sender, receiver) = mpsc::channel(buffer_size.max(1));
+        let cancel = CancellationToken::new();
+
+        let task = {
+            let cancel = cancel.clone();
+
+            runtime::spawn(async move {
+                loop {
+                    let next_response = connection.lock().await.next_response(&options, &cancel).await;
+
+                    if next_response.action == ACTION_CANCELLED {
+                        break;
+                    }
+
+                    let is_error = next_response.is_error();
+
+                    if sender.send(next_response).await.is_err() || is_error {
+                        break;
+                    }
+                }
+            })
+        };
+
+        ChainSyncStream { receiver, cancel, task }
+    }
+
+    // `None` when no response is buffered yet. Once the task is gone an error
+    // response is returned so the consumer can reconnect.
+    pub fn try_next(&mut self) -> Option<NextResponse> {
+        match self.receiver.try_recv() {
+            Ok(next_response) => Some(next_response),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(NextResponse::error()),
+        }
+    }
+
+    pub async fn stop(self) {
+        self.cancel.cancel();
+        // The task may be parked on a full buffer rather than on the node
+        self.task.abort();
+        let _ = self.task.await;
+    }
+}
+
+impl ClientWrapper {
+    #[net]
+    pub fn start_chain_sync_stream(client_wrapper: ClientWrapper, buffer_size: u64) {
+        ClientWrapper::start_chain_sync_stream(client_wrapper, buffer_size)
+    }
+
+    pub fn start_chain_sync_stream(client_wrapper: ClientWrapper, buffer_size: u64) {
+        let state = client_wrapper.state();
+
+        if state.sink.lock().unwrap().is_some() || state.acked_stream.lock().unwrap().is_some() {
+            println!("start_chain_sync_stream error: sink or acked stream is running");
+            return;
+        }
+
+        let mut stream = state.chain_sync_stream.lock().unwrap();
+
+        if stream.is_none() {
+            *stream = Some(ChainSyncStream::start(state.connection.clone(), state.options.clone(), buffer_size as usize));
+        }
+    }
+
+    #[net]
+    pub fn chain_sync_try_next(client_wrapper: ClientWrapper) -> NextResponse {
+        ClientWrapper::chain_sync_try_next(client_wrapper)
+    }
+
+    pub fn chain_sync_try_next(client_wrapper: ClientWrapper) -> NextResponse {
+        match client_wrapper.state().chain_sync_stream.lock().unwrap().as_mut() {
+            Some(stream) => stream.try_next().unwrap_or(NextResponse {
+                action: ACTION_PENDING,
+                tip: None,
+                block_cbor: None,
+            }),
+            None => {
+                println!("chain_sync_try_next error: chain-sync stream not started");
+                NextResponse::error()
+            }
+        }
+    }
+
+    #[net]
+    pub fn stop_chain_sync_stream(client_wrapper: ClientWrapper) {
+        ClientWrapper::stop_chain_sync_stream(client_wrapper)
+    }
+
+    pub fn stop_chain_sync_stream(client_wrapper: ClientWrapper) {
+        let stream = client_wrapper.state().chain_sync_stream.lock().unwrap().take();
+
+        if let Some(stream) = stream {
+            runtime::block_on(stream.stop());
+        }
+    }
+}
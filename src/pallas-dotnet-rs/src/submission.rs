@@ -0,0 +1,353 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use pallas::{
+    ledger::traverse::MultiEraTx,
+    network::miniprotocols::txsubmission::{self, EraTxBody, EraTxId, TxIdAndSize},
+};
+use rnet::Net;
+use tokio::{sync::Notify, task::JoinHandle};
+
+pub const TX_SUBMISSION_TIMEOUT: Duration = Duration::from_secs(30);
+
+// Txs a background session holds, acknowledged ones are evicted to make
+// room for new ones
+pub const TX_SESSION_CAPACITY: usize = 1024;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum TxStatus {
+    Pending = 0,
+    Announced = 1,
+    BodyRequested = 2,
+    Acknowledged = 3,
+    Timeout = 4,
+    ProtocolError = 5,
+}
+
+#[derive(Net)]
+pub struct TxSubmissionResult {
+    pub(crate) tx_id: Vec<u8>,
+    pub(crate) status: u8,
+    // Whether the peer pulled the body, acknowledged txs may or may not have
+    // been
+    pub(crate) body_requested: bool,
+}
+
+#[derive(Net)]
+pub struct TxSubmissionOutcome {
+    pub(crate) results: Vec<TxSubmissionResult>,
+    pub(crate) error: Option<String>,
+}
+
+struct MempoolTx {
+    id: EraTxId,
+    body: EraTxBody,
+    status: TxStatus,
+    body_requested: bool,
+}
+
+impl MempoolTx {
+    fn is_done(&self) -> bool {
+        matches!(self.status, TxStatus::Acknowledged | TxStatus::Timeout | TxStatus::ProtocolError)
+    }
+}
+
+// Txs offered to a peer through the TxSubmission mini-protocol. Ids are
+// announced in FIFO order and stay unacknowledged until the peer acks them
+// in a later TxIds request. Txs are numbered in the order they are pushed,
+// `to_announce` and `unacked` hold these numbers.
+#[derive(Default)]
+pub struct Mempool {
+    // Oldest first, starting with tx number `evicted`
+    txs: VecDeque<MempoolTx>,
+    evicted: usize,
+    // 0 is unbounded
+    capacity: usize,
+    to_announce: VecDeque<usize>,
+    unacked: VecDeque<usize>,
+}
+
+impl Mempool {
+    pub fn with_capacity(capacity: usize) -> Mempool {
+        Mempool {
+            capacity,
+            ..Mempool::default()
+        }
+    }
+
+    fn tx_mut(&mut self, number: usize) -> &mut MempoolTx {
+        &mut self.txs[number - self.evicted]
+    }
+
+    // Txs are done with in the order they were pushed, so the done ones are
+    // at the front
+    fn evict(&mut self) {
+        while self.txs.front().is_some_and(MempoolTx::is_done) {
+            self.txs.pop_front();
+            self.evicted += 1;
+        }
+    }
+
+    pub fn push(&mut self, tx: Vec<u8>) -> Result<Vec<u8>, String> {
+        let multi_era_tx = MultiEraTx::decode(&tx).map_err(|e| format!("invalid tx cbor: {:?}", e))?;
+        let tx_era = multi_era_tx.era() as u16;
+        let tx_id = multi_era_tx.hash().to_vec();
+
+        if self.capacity > 0 && self.txs.len() >= self.capacity {
+            self.evict();
+
+            if self.txs.len() >= self.capacity {
+                return Err(format!("mempool is full, {} txs are waiting for the peer", self.txs.len()));
+            }
+        }
+
+        self.to_announce.push_back(self.evicted + self.txs.len());
+        self.txs.push_back(MempoolTx {
+            id: txsubmission::EraTxId(tx_era, tx_id.clone()),
+            body: EraTxBody(tx_era, tx),
+            status: TxStatus::Pending,
+            body_requested: false,
+        });
+
+        Ok(tx_id)
+    }
+
+    pub fn acknowledge(&mut self, count: u16) {
+        for _ in 0..count {
+            match self.unacked.pop_front() {
+                Some(i) => self.tx_mut(i).status = TxStatus::Acknowledged,
+                None => break,
+            }
+        }
+    }
+
+    pub fn announce(&mut self, count: u16) -> Vec<TxIdAndSize<EraTxId>> {
+        let mut ids_and_size = vec![];
+
+        while ids_and_size.len() < count as usize {
+            let Some(i) = self.to_announce.pop_front() else {
+                break;
+            };
+
+            let tx = self.tx_mut(i);
+            tx.status = TxStatus::Announced;
+            ids_and_size.push(TxIdAndSize(tx.id.clone(), tx.body.1.len() as u32));
+
+            self.unacked.push_back(i);
+        }
+
+        ids_and_size
+    }
+
+    pub fn bodies(&mut self, ids: &[EraTxId]) -> Vec<EraTxBody> {
+        ids.iter()
+            .filter_map(|id| {
+                self.txs.iter_mut().find(|tx| tx.id.1 == id.1).map(|tx| {
+                    if tx.status != TxStatus::Acknowledged {
+                        tx.status = TxStatus::BodyRequested;
+                    }
+                    tx.body_requested = true;
+                    tx.body.clone()
+                })
+            })
+            .collect()
+    }
+
+    pub fn has_announcements(&self) -> bool {
+        !self.to_announce.is_empty()
+    }
+
+    pub fn is_settled(&self) -> bool {
+        self.to_announce.is_empty() && self.unacked.is_empty()
+    }
+
+    // Txs whose body was requested keep that status, everything the peer
+    // never asked for is marked with the given failure.
+    pub fn fail_pending(&mut self, status: TxStatus) {
+        for tx in self.txs.iter_mut() {
+            if matches!(tx.status, TxStatus::Pending | TxStatus::Announced) {
+                tx.status = status;
+            }
+        }
+
+        self.to_announce.clear();
+        self.unacked.clear();
+    }
+
+    // Evicted txs are no longer reported
+    pub fn results(&self) -> Vec<TxSubmissionResult> {
+        self.txs
+            .iter()
+            .map(|tx| TxSubmissionResult {
+                tx_id: tx.id.1.clone(),
+                status: tx.status as u8,
+                body_requested: tx.body_requested,
+            })
+            .collect()
+    }
+
+    pub fn outcome(&self, error: Option<String>) -> TxSubmissionOutcome {
+        TxSubmissionOutcome {
+            results: self.results(),
+            error,
+        }
+    }
+}
+
+pub enum ServeError {
+    Timeout,
+    Protocol(String),
+}
+
+pub async fn next_request(
+    client: &mut txsubmission::Client,
+    timeout: Duration,
+) -> Result<txsubmission::Request<EraTxId>, ServeError> {
+    match tokio::time::timeout(timeout, client.next_request()).await {
+        Ok(Ok(request)) => Ok(request),
+        Ok(Err(e)) => Err(ServeError::Protocol(format!("{:?}", e))),
+        Err(_) => Err(ServeError::Timeout),
+    }
+}
+
+// Answers the peer's requests until every tx has been acknowledged, then
+// ends the session with MsgDone on the next blocking TxIds request.
+pub async fn submit_all(
+    client: &mut txsubmission::Client,
+    mempool: &mut Mempool,
+    timeout: Duration,
+) -> Result<(), ServeError> {
+    client
+        .send_init()
+        .await
+        .map_err(|e| ServeError::Protocol(format!("{:?}", e)))?;
+
+    loop {
+        let result = match next_request(client, timeout).await? {
+            txsubmission::Request::TxIds(ack, req) => {
+                mempool.acknowledge(ack);
+
+                if mempool.is_settled() {
+                    return client
+                        .send_done()
+                        .await
+                        .map_err(|e| ServeError::Protocol(format!("{:?}", e)));
+                }
+
+                client.reply_tx_ids(mempool.announce(req)).await
+            }
+            txsubmission::Request::TxIdsNonBlocking(ack, req) => {
+                mempool.acknowledge(ack);
+                client.reply_tx_ids(mempool.announce(req)).await
+            }
+            txsubmission::Request::Txs(ids) => client.reply_txs(mempool.bodies(&ids)).await,
+        };
+
+        result.map_err(|e| ServeError::Protocol(format!("{:?}", e)))?;
+    }
+}
+
+// Long-lived TxSubmission session on an existing N2N connection. Txs are
+// enqueued from the caller while a background task answers the peer's
+// TxIds/Txs requests from the queue.
+pub struct TxSubmissionSession {
+    mempool: Arc<Mutex<Mempool>>,
+    notify: Arc<Notify>,
+    error: Arc<Mutex<Option<String>>>,
+    task: JoinHandle<()>,
+}
+
+impl TxSubmissionSession {
+    pub fn start(mut client: txsubmission::Client) -> TxSubmissionSession {
+        let mempool = Arc::new(Mutex::new(Mempool::with_capacity(TX_SESSION_CAPACITY)));
+        let notify = Arc::new(Notify::new());
+        let error = Arc::new(Mutex::new(None));
+
+        let task = {
+            let mempool = mempool.clone();
+            let notify = notify.clone();
+            let error = error.clone();
+
+            crate::runtime::spawn(async move {
+                if let Err(e) = serve_forever(&mut client, &mempool, &notify).await {
+                    *error.lock().unwrap() = Some(e);
+                    mempool.lock().unwrap().fail_pending(TxStatus::ProtocolError);
+                }
+            })
+        };
+
+        TxSubmissionSession {
+            mempool,
+            notify,
+            error,
+            task,
+        }
+    }
+
+    pub fn enqueue(&self, tx: Vec<u8>) -> Result<Vec<u8>, String> {
+        if self.task.is_finished() {
+            return Err("tx submission session is not running".to_string());
+        }
+
+        let tx_id = self.mempool.lock().unwrap().push(tx)?;
+        self.notify.notify_one();
+
+        Ok(tx_id)
+    }
+
+    pub fn outcome(&self) -> TxSubmissionOutcome {
+        self.mempool
+            .lock()
+            .unwrap()
+            .outcome(self.error.lock().unwrap().clone())
+    }
+
+    pub fn stop(self) {
+        self.task.abort();
+    }
+}
+
+async fn serve_forever(
+    client: &mut txsubmission::Client,
+    mempool: &Mutex<Mempool>,
+    notify: &Notify,
+) -> Result<(), String> {
+    client.send_init().await.map_err(|e| format!("{:?}", e))?;
+
+    loop {
+        let request = client.next_request().await.map_err(|e| format!("{:?}", e))?;
+
+        let result = match request {
+            txsubmission::Request::TxIds(ack, req) => {
+                mempool.lock().unwrap().acknowledge(ack);
+
+                // A blocking request can only be answered with at least one
+                // id, so wait until something is enqueued.
+                while !mempool.lock().unwrap().has_announcements() {
+                    notify.notified().await;
+                }
+
+                let ids_and_size = mempool.lock().unwrap().announce(req);
+                client.reply_tx_ids(ids_and_size).await
+            }
+            txsubmission::Request::TxIdsNonBlocking(ack, req) => {
+                let ids_and_size = {
+                    let mut mempool = mempool.lock().unwrap();
+                    mempool.acknowledge(ack);
+                    mempool.announce(req)
+                };
+
+                client.reply_tx_ids(ids_and_size).await
+            }
+            txsubmission::Request::Txs(ids) => {
+                let bodies = mempool.lock().unwrap().bodies(&ids);
+                client.reply_txs(bodies).await
+            }
+        };
+
+        result.map_err(|e| format!("{:?}", e))?;
+    }
+}
@@ -0,0 +1,90 @@
+use std::{thread, time::Duration};
+
+use super::{connect, eventually, next, options, point, shared_runtime};
+use crate::{
+    acked::AckedResponse,
+    bearer::ClientKind,
+    mock::{MockChain, MockNode},
+    options::ClientOptions,
+    ClientWrapper, ACTION_ERROR, ACTION_PENDING, ACTION_ROLL_FORWARD,
+};
+
+fn delivered(client: &ClientWrapper) -> AckedResponse {
+    eventually(Duration::from_secs(5), || {
+        let response = ClientWrapper::acked_stream_try_next(client.clone());
+        (response.action != ACTION_PENDING).then_some(response)
+    })
+    .expect("no response delivered")
+}
+
+fn assert_delivered(response: AckedResponse, node: &MockNode, block: usize) {
+    assert_eq!(response.action, ACTION_ROLL_FORWARD);
+    assert_eq!(response.block_cbor.unwrap(), node.chain.blocks[block].block_cbor);
+    assert_eq!(response.point.unwrap().hash, node.chain.blocks[block].hash);
+}
+
+#[test]
+fn prefetching_stops_at_the_window() {
+    let _runtime = shared_runtime();
+    let node = MockNode::n2c(MockChain::new(4));
+    let client = connect(&node, ClientKind::N2C, options());
+
+    assert!(ClientWrapper::start_acked_stream(client.clone(), 2));
+
+    assert_delivered(delivered(&client), &node, 0);
+    assert_delivered(delivered(&client), &node, 1);
+
+    thread::sleep(Duration::from_millis(300));
+    assert_eq!(ClientWrapper::acked_stream_try_next(client.clone()).action, ACTION_PENDING);
+
+    // Acknowledging the second block frees both slots
+    assert!(ClientWrapper::ack_point(client.clone(), point(&node, 1)));
+    assert_delivered(delivered(&client), &node, 2);
+    assert_delivered(delivered(&client), &node, 3);
+
+    // Only delivered points can be acknowledged
+    assert!(!ClientWrapper::ack_point(client.clone(), point(&node, 1)));
+
+    ClientWrapper::stop_acked_stream(client.clone());
+    ClientWrapper::disconnect(client);
+}
+
+#[test]
+fn try_next_without_a_stream_is_an_error() {
+    let _runtime = shared_runtime();
+    let node = MockNode::n2c(MockChain::new(1));
+    let client = connect(&node, ClientKind::N2C, options());
+
+    assert_eq!(ClientWrapper::acked_stream_try_next(client.clone()).action, ACTION_ERROR);
+
+    ClientWrapper::disconnect(client);
+}
+
+#[test]
+fn unacknowledged_responses_are_delivered_again() {
+    let _runtime = shared_runtime();
+    let path = std::env::temp_dir().join(format!("pallas-dotnet-acked-{}", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let options = ClientOptions {
+        cursor_path: path.to_string_lossy().to_string(),
+        ..options()
+    };
+
+    let node = MockNode::n2c(MockChain::new(3));
+    let client = connect(&node, ClientKind::N2C, options);
+
+    assert!(ClientWrapper::start_acked_stream(client.clone(), 3));
+
+    assert_delivered(delivered(&client), &node, 0);
+    assert_delivered(delivered(&client), &node, 1);
+    assert!(ClientWrapper::ack_point(client.clone(), point(&node, 0)));
+
+    // Stopping goes back to the acknowledged block
+    ClientWrapper::stop_acked_stream(client.clone());
+    assert_eq!(next(&client).block_cbor.unwrap(), node.chain.blocks[1].block_cbor);
+
+    assert_eq!(ClientWrapper::get_cursor_points(client.clone())[0].hash, node.chain.blocks[0].hash);
+
+    ClientWrapper::disconnect(client);
+}
@@ -0,0 +1,103 @@
+use std::path::PathBuf;
+
+use pallas::network::miniprotocols::Point as PallasPoint;
+
+use super::{connect, next, options, point, shared_runtime};
+use crate::{
+    bearer::ClientKind,
+    cursor::CursorStore,
+    mock::{fixtures, MockChain, MockNode, MAGIC},
+    options::ClientOptions,
+    ClientWrapper, ACTION_ROLL_FORWARD,
+};
+
+fn empty_cursor(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("pallas-dotnet-cursor-{}-{}", std::process::id(), name));
+    let _ = std::fs::remove_file(&path);
+
+    path
+}
+
+#[test]
+fn acknowledged_points_survive_reopening() {
+    let blocks = fixtures::chain(4);
+    let path = empty_cursor("reopen");
+
+    let mut cursor = CursorStore::open(&path, 2).unwrap();
+    assert!(cursor.points().is_empty());
+
+    for block in &blocks[..3] {
+        cursor.ack(block.point()).unwrap();
+    }
+
+    // Only the 2 newest are kept
+    assert_eq!(cursor.points(), vec![blocks[2].point(), blocks[1].point()]);
+
+    // Going back drops the points past it
+    cursor.ack(blocks[1].point()).unwrap();
+    assert_eq!(cursor.points(), vec![blocks[1].point()]);
+
+    let cursor = CursorStore::open(&path, 2).unwrap();
+    assert_eq!(cursor.points(), vec![blocks[1].point()]);
+    assert!(!path.with_extension("tmp").exists());
+
+    let mut cursor = CursorStore::open(&path, 2).unwrap();
+    cursor.ack(PallasPoint::Origin).unwrap();
+    assert_eq!(CursorStore::open(&path, 2).unwrap().points(), vec![PallasPoint::Origin]);
+}
+
+#[test]
+fn connect_resumes_after_the_last_acknowledged_point() {
+    let _runtime = shared_runtime();
+    let path = empty_cursor("connect");
+
+    let options = ClientOptions {
+        cursor_path: path.to_string_lossy().to_string(),
+        ..options()
+    };
+
+    let node = MockNode::n2c(MockChain::new(4));
+
+    let client = connect(&node, ClientKind::N2C, options.clone());
+    next(&client);
+    next(&client);
+
+    // The second block was received but not processed
+    assert!(ClientWrapper::ack_point(client.clone(), point(&node, 0)));
+    ClientWrapper::disconnect(client);
+
+    let client = connect(&node, ClientKind::N2C, options);
+    assert_eq!(ClientWrapper::get_cursor_points(client.clone())[0].hash, node.chain.blocks[0].hash);
+
+    let forward = next(&client);
+    assert_eq!(forward.action, ACTION_ROLL_FORWARD);
+    assert_eq!(forward.block_cbor.unwrap(), node.chain.blocks[1].block_cbor);
+
+    ClientWrapper::disconnect(client);
+
+    // Without a cursor there is nothing to acknowledge to
+    let client = connect(&node, ClientKind::N2C, super::options());
+    assert!(!ClientWrapper::ack_point(client.clone(), point(&node, 0)));
+    ClientWrapper::disconnect(client);
+}
+
+#[test]
+fn connect_fails_when_the_cursor_cannot_be_used() {
+    let _runtime = shared_runtime();
+    let node = MockNode::n2c(MockChain::new(2));
+    let connect_with = |path: &PathBuf| {
+        let options = ClientOptions {
+            cursor_path: path.to_string_lossy().to_string(),
+            ..options()
+        };
+
+        ClientWrapper::connect(node.address(), MAGIC, ClientKind::N2C as u8, options)
+    };
+
+    // A directory cannot be read as a cursor
+    assert!(connect_with(&std::env::temp_dir()).is_none());
+
+    let path = empty_cursor("off-chain");
+    CursorStore::open(&path, 0).unwrap().ack(PallasPoint::Specific(1, vec![1; 32])).unwrap();
+    assert!(connect_with(&path).is_none());
+}
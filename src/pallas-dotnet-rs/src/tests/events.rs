@@ -0,0 +1,136 @@
+use pallas::{ledger::traverse::MultiEraBlock, network::miniprotocols::Point as PallasPoint};
+
+use super::{connect, options, shared_runtime};
+use crate::{
+    bearer::ClientKind,
+    events::{block_events, ChainEvent, EventLog, EVENT_BLOCK, EVENT_OUTPUT_CREATED, EVENT_OUTPUT_SPENT, EVENT_TX},
+    mock::{
+        fixtures::{self, MockTx},
+        MockChain, MockNode, Step,
+    },
+    ClientWrapper, NextResponse, ACTION_ROLL_BACK, ACTION_ROLL_FORWARD,
+};
+
+// Block 1: A pays key 1. Block 2: B spends A#0, C fails phase-2 and only
+// consumes its collateral A#1 for its collateral return.
+fn txs() -> Vec<Vec<MockTx>> {
+    let a = MockTx {
+        inputs: vec![(vec![0; 32], 0)],
+        outputs: vec![
+            fixtures::output(&fixtures::key_address(1), 5_000_000, &[]),
+            fixtures::output(&fixtures::key_address(1), 2_000_000, &[]),
+        ],
+        ..MockTx::default()
+    };
+
+    let b = MockTx {
+        inputs: vec![(a.hash(), 0)],
+        outputs: vec![fixtures::output(&fixtures::key_address(2), 4_800_000, &[])],
+        ..MockTx::default()
+    };
+
+    let c = MockTx {
+        inputs: vec![(b.hash(), 0)],
+        outputs: vec![fixtures::output(&fixtures::key_address(3), 4_600_000, &[])],
+        collateral: vec![(a.hash(), 1)],
+        collateral_return: Some(fixtures::output(&fixtures::key_address(1), 1_500_000, &[])),
+        invalid: true,
+        ..MockTx::default()
+    };
+
+    vec![vec![a], vec![b, c]]
+}
+
+// (kind, undo, tx hash, output tx hash, index)
+type EventSummary = (u8, bool, Vec<u8>, Vec<u8>, u64);
+
+fn summary(events: &[ChainEvent]) -> Vec<EventSummary> {
+    events
+        .iter()
+        .map(|event| (event.kind, event.undo, event.tx_hash.clone(), event.output_tx_hash.clone(), event.index))
+        .collect()
+}
+
+#[test]
+fn blocks_break_down_into_tx_and_output_events() {
+    let txs = txs();
+    let (a, b, c) = (txs[0][0].hash(), txs[1][0].hash(), txs[1][1].hash());
+    let blocks = fixtures::chain_with(txs);
+
+    let events = block_events(&MultiEraBlock::decode(&blocks[1].block_cbor).unwrap());
+
+    assert_eq!(
+        summary(&events),
+        vec![
+            (EVENT_BLOCK, false, vec![], vec![], 0),
+            (EVENT_TX, false, b.clone(), vec![], 0),
+            (EVENT_OUTPUT_SPENT, false, b.clone(), a.clone(), 0),
+            (EVENT_OUTPUT_CREATED, false, b.clone(), b.clone(), 0),
+            (EVENT_TX, false, c.clone(), vec![], 0),
+            (EVENT_OUTPUT_SPENT, false, c.clone(), a.clone(), 1),
+            (EVENT_OUTPUT_CREATED, false, c.clone(), c.clone(), 1),
+        ]
+    );
+
+    assert_eq!(events[0].block_hash, blocks[1].hash);
+    assert_eq!(events[0].cbor, blocks[1].header_cbor);
+    assert_eq!(events[4].tx_index, 1);
+    assert_eq!(events[6].cbor, fixtures::output(&fixtures::key_address(1), 1_500_000, &[]));
+}
+
+#[test]
+fn rollbacks_undo_the_events_in_reverse() {
+    let _runtime = shared_runtime();
+
+    let mut chain = MockChain::scripted(2, vec![Step::Forward(0), Step::Forward(1), Step::Backward(Some(0))]);
+    chain.blocks = fixtures::chain_with(txs());
+
+    let node = MockNode::n2c(chain);
+    let client = connect(&node, ClientKind::N2C, options());
+
+    let first = ClientWrapper::chain_sync_next_events(client.clone());
+    assert_eq!(first.action, ACTION_ROLL_FORWARD);
+    assert_eq!(first.events[0].block_hash, node.chain.blocks[0].hash);
+
+    let second = ClientWrapper::chain_sync_next_events(client.clone());
+    let applied = summary(&second.events);
+
+    let rollback = ClientWrapper::chain_sync_next_events(client.clone());
+    assert_eq!(rollback.action, ACTION_ROLL_BACK);
+
+    let undone: Vec<_> = applied.into_iter().rev().map(|(kind, _, tx, output, index)| (kind, true, tx, output, index)).collect();
+    assert_eq!(summary(&rollback.events), undone);
+
+    ClientWrapper::disconnect(client);
+}
+
+#[test]
+fn rollbacks_match_the_point_by_slot_and_hash() {
+    let blocks = fixtures::chain_with(txs());
+    let mut log = EventLog::default();
+
+    for block in &blocks {
+        let forward = NextResponse {
+            action: ACTION_ROLL_FORWARD,
+            tip: None,
+            block_cbor: Some(block.block_cbor.clone()),
+        };
+
+        assert!(!log.on_response(&forward, Some(&block.point())).is_empty());
+    }
+
+    let rollback = NextResponse {
+        action: ACTION_ROLL_BACK,
+        tip: None,
+        block_cbor: None,
+    };
+
+    // A block of another fork at the slot of the last one replaces it
+    let fork = PallasPoint::Specific(blocks[1].slot, vec![0xff; 32]);
+    let undone = log.on_response(&rollback, Some(&fork));
+    assert!(!undone.is_empty());
+    assert!(undone.iter().all(|event| event.undo && event.block_hash == blocks[1].hash));
+
+    // Rolling back to the first block leaves nothing more to undo
+    assert!(log.on_response(&rollback, Some(&blocks[0].point())).is_empty());
+}
@@ -0,0 +1,211 @@
+use pallas::{
+    codec::minicbor::{Decoder, Encoder},
+    ledger::{addresses::Address, traverse::MultiEraBlock},
+};
+
+use super::{connect, next, options, shared_runtime};
+use crate::{
+    bearer::ClientKind,
+    filter::{SyncFilter, TxFilter},
+    mock::{
+        fixtures::{self, MockBlock, MockTx},
+        MockChain, MockNode, Step,
+    },
+    ClientWrapper, ACTION_AWAIT, ACTION_ROLL_FORWARD,
+};
+
+const POLICY: [u8; 28] = [7; 28];
+const LABEL: u64 = 674;
+
+fn pay(key: u8) -> MockTx {
+    MockTx {
+        inputs: vec![(vec![key; 32], 0)],
+        outputs: vec![fixtures::output(&fixtures::key_address(key), 2_000_000, &[])],
+        ..MockTx::default()
+    }
+}
+
+// Only the second block holds txs involving key 2: one paying it a token
+// with metadata, one failing phase-2 whose collateral goes back to it
+fn txs() -> Vec<Vec<MockTx>> {
+    let token = MockTx {
+        outputs: vec![fixtures::output(&fixtures::key_address(2), 2_000_000, &[(&POLICY, b"tok")])],
+        metadata: vec![LABEL],
+        ..pay(2)
+    };
+
+    let failed = MockTx {
+        collateral: vec![(vec![9; 32], 0)],
+        collateral_return: Some(fixtures::output(&fixtures::key_address(2), 1_000_000, &[])),
+        invalid: true,
+        ..pay(3)
+    };
+
+    vec![vec![pay(1)], vec![pay(3), token, failed], vec![pay(1)]]
+}
+
+fn filter(spec: SyncFilter) -> TxFilter {
+    TxFilter::new(spec).unwrap()
+}
+
+fn tx_hashes(block_cbor: &[u8]) -> Vec<Vec<u8>> {
+    MultiEraBlock::decode(block_cbor).unwrap().txs().iter().map(|tx| tx.hash().to_vec()).collect()
+}
+
+#[test]
+fn chain_sync_only_delivers_matching_txs() {
+    let _runtime = shared_runtime();
+    let txs = txs();
+
+    let mut chain = MockChain::scripted(3, (0..3).map(Step::Forward).collect());
+    chain.blocks = fixtures::chain_with(txs.clone());
+
+    let node = MockNode::n2c(chain);
+    let client = connect(&node, ClientKind::N2C, options());
+
+    let spec = SyncFilter {
+        payment_credentials: vec![vec![2; 28]],
+        ..SyncFilter::default()
+    };
+    assert!(ClientWrapper::set_chain_sync_filter(client.clone(), spec));
+
+    // The first block is skipped, the second one keeps its header
+    let forward = next(&client);
+    assert_eq!(forward.action, ACTION_ROLL_FORWARD);
+
+    let block_cbor = forward.block_cbor.unwrap();
+    let block = MultiEraBlock::decode(&block_cbor).unwrap();
+    assert_eq!(block.hash().to_vec(), node.chain.blocks[1].hash);
+
+    let kept = block.txs();
+    assert_eq!(kept.len(), 2);
+    assert_eq!(kept[0].hash().to_vec(), txs[1][1].hash());
+    assert!(kept[0].metadata().find(LABEL).is_some());
+    assert_eq!(kept[1].hash().to_vec(), txs[1][2].hash());
+    assert!(!kept[1].is_valid());
+
+    // So is the third one
+    assert_eq!(next(&client).action, ACTION_AWAIT);
+
+    ClientWrapper::disconnect(client);
+}
+
+#[test]
+fn each_criterion_selects_its_txs() {
+    let txs = txs();
+    let (token, failed) = (&txs[1][1], &txs[1][2]);
+    let blocks: Vec<MockBlock> = fixtures::chain_with(txs.clone());
+    let block = &blocks[1].block_cbor;
+
+    let by_label = filter(SyncFilter {
+        metadata_labels: vec![LABEL],
+        ..SyncFilter::default()
+    });
+    assert_eq!(tx_hashes(&by_label.apply(block).unwrap().unwrap()), vec![token.hash()]);
+
+    let by_policy = filter(SyncFilter {
+        policy_ids: vec![POLICY.to_vec()],
+        ..SyncFilter::default()
+    });
+    assert_eq!(tx_hashes(&by_policy.apply(block).unwrap().unwrap()), vec![token.hash()]);
+
+    let by_asset = filter(SyncFilter {
+        asset_names: vec![b"tok".to_vec()],
+        ..SyncFilter::default()
+    });
+    assert_eq!(tx_hashes(&by_asset.apply(block).unwrap().unwrap()), vec![token.hash()]);
+
+    // Collateral returns are outputs too
+    let by_address = filter(SyncFilter {
+        addresses: vec![Address::from_bytes(&fixtures::key_address(2)).unwrap().to_bech32().unwrap()],
+        ..SyncFilter::default()
+    });
+    assert_eq!(tx_hashes(&by_address.apply(block).unwrap().unwrap()), vec![token.hash(), failed.hash()]);
+
+    let by_key_3 = filter(SyncFilter {
+        payment_credentials: vec![vec![3; 28]],
+        ..SyncFilter::default()
+    });
+    assert!(by_key_3.apply(&blocks[0].block_cbor).unwrap().is_none());
+}
+
+// Same block with the block array and its tx lists of indefinite length
+fn indefinite(block_cbor: &[u8]) -> Vec<u8> {
+    let mut d = Decoder::new(block_cbor);
+    let mut e = Encoder::new(vec![]);
+
+    d.array().unwrap();
+    e.array(2).unwrap().u16(d.u16().unwrap()).unwrap();
+
+    let fields = d.array().unwrap().unwrap();
+    e.begin_array().unwrap();
+
+    for field in 0..fields {
+        let start = d.position();
+
+        match field {
+            1 | 2 | 4 => {
+                let len = d.array().unwrap().unwrap();
+                e.begin_array().unwrap();
+
+                for _ in 0..len {
+                    let start = d.position();
+                    d.skip().unwrap();
+                    e.writer_mut().extend_from_slice(&block_cbor[start..d.position()]);
+                }
+
+                e.end().unwrap();
+            }
+            _ => {
+                d.skip().unwrap();
+                e.writer_mut().extend_from_slice(&block_cbor[start..d.position()]);
+            }
+        }
+    }
+
+    e.end().unwrap();
+    e.into_writer()
+}
+
+#[test]
+fn indefinite_length_blocks_keep_their_invalid_txs() {
+    let txs = txs();
+    let blocks = fixtures::chain_with(txs.clone());
+    let block = indefinite(&blocks[1].block_cbor);
+
+    let by_key_2 = filter(SyncFilter {
+        payment_credentials: vec![vec![2; 28]],
+        ..SyncFilter::default()
+    });
+
+    let filtered = by_key_2.apply(&block).unwrap().unwrap();
+    let filtered = MultiEraBlock::decode(&filtered).unwrap();
+    let kept = filtered.txs();
+
+    assert_eq!(kept.len(), 2);
+    assert_eq!(kept[1].hash().to_vec(), txs[1][2].hash());
+    assert!(kept[0].is_valid());
+    assert!(!kept[1].is_valid());
+}
+
+#[test]
+fn blocks_matching_entirely_are_left_untouched() {
+    let blocks = fixtures::chain_with(txs());
+    let by_key_1 = filter(SyncFilter {
+        payment_credentials: vec![vec![1; 28]],
+        ..SyncFilter::default()
+    });
+
+    assert_eq!(by_key_1.apply(&blocks[0].block_cbor).unwrap(), Some(blocks[0].block_cbor.clone()));
+}
+
+#[test]
+fn invalid_specs_are_rejected() {
+    let spec = SyncFilter {
+        addresses: vec!["not an address".to_string()],
+        ..SyncFilter::default()
+    };
+
+    assert!(TxFilter::new(spec).is_err());
+    assert!(filter(SyncFilter::default()).is_empty());
+}
@@ -0,0 +1,134 @@
+use pallas::network::miniprotocols::Point as PallasPoint;
+
+use super::{connect, next, options, shared_runtime};
+use crate::{
+    bearer::ClientKind,
+    finality::FinalityBuffer,
+    mock::{fixtures, MockChain, MockNode, Step},
+    ClientWrapper, NextResponse, ACTION_AWAIT, ACTION_ERROR, ACTION_ROLL_BACK, ACTION_ROLL_FORWARD,
+};
+
+fn confirmed(client: &ClientWrapper) -> NextResponse {
+    ClientWrapper::chain_sync_next_confirmed(client.clone())
+}
+
+fn assert_confirmed(response: NextResponse, node: &MockNode, block: usize) {
+    assert_eq!(response.action, ACTION_ROLL_FORWARD);
+    assert_eq!(response.block_cbor.unwrap(), node.chain.blocks[block].block_cbor);
+}
+
+#[test]
+fn blocks_are_confirmed_once_deep_enough() {
+    let _runtime = shared_runtime();
+
+    // Blocks 1 and 2 are rolled back before being 2 deep
+    let script = vec![
+        Step::Forward(0),
+        Step::Forward(1),
+        Step::Forward(2),
+        Step::Backward(Some(0)),
+        Step::Forward(1),
+        Step::Forward(2),
+        Step::Forward(3),
+    ];
+
+    let node = MockNode::n2c(MockChain::scripted(4, script));
+    let client = connect(&node, ClientKind::N2C, options());
+
+    assert_eq!(confirmed(&client).action, ACTION_ERROR);
+
+    ClientWrapper::set_finality_depth(client.clone(), 2);
+
+    assert_confirmed(confirmed(&client), &node, 0);
+    assert_confirmed(confirmed(&client), &node, 1);
+
+    // Blocks 2 and 3 stay tentative
+    assert_eq!(confirmed(&client).action, ACTION_AWAIT);
+
+    ClientWrapper::disconnect(client);
+}
+
+#[test]
+fn tentative_and_confirmed_chains_are_both_available() {
+    let _runtime = shared_runtime();
+
+    let node = MockNode::n2c(MockChain::new(3));
+    let client = connect(&node, ClientKind::N2C, options());
+
+    ClientWrapper::set_finality_depth(client.clone(), 1);
+
+    for block in 0..3 {
+        assert_eq!(next(&client).block_cbor.unwrap(), node.chain.blocks[block].block_cbor);
+    }
+
+    assert_confirmed(confirmed(&client), &node, 0);
+    assert_confirmed(confirmed(&client), &node, 1);
+    assert_eq!(confirmed(&client).action, ACTION_AWAIT);
+
+    ClientWrapper::disconnect(client);
+}
+
+#[test]
+fn rollbacks_past_a_confirmed_block_are_reported() {
+    let _runtime = shared_runtime();
+
+    let script = vec![
+        Step::Forward(0),
+        Step::Forward(1),
+        Step::Forward(2),
+        Step::Backward(Some(0)),
+        Step::Forward(1),
+        Step::Forward(2),
+    ];
+
+    let node = MockNode::n2c(MockChain::scripted(3, script));
+    let client = connect(&node, ClientKind::N2C, options());
+
+    ClientWrapper::set_finality_depth(client.clone(), 1);
+
+    assert_confirmed(confirmed(&client), &node, 0);
+    assert_confirmed(confirmed(&client), &node, 1);
+
+    // Block 1 was confirmed already
+    assert_eq!(confirmed(&client).action, ACTION_ERROR);
+    assert_confirmed(confirmed(&client), &node, 1);
+
+    ClientWrapper::disconnect(client);
+}
+
+#[test]
+fn rollbacks_match_the_point_by_slot_and_hash() {
+    let blocks = fixtures::chain(3);
+    let mut buffer = FinalityBuffer::default();
+    buffer.set_depth(1);
+
+    let forward = |block: usize| NextResponse {
+        action: ACTION_ROLL_FORWARD,
+        tip: None,
+        block_cbor: Some(blocks[block].block_cbor.clone()),
+    };
+
+    let rollback = NextResponse {
+        action: ACTION_ROLL_BACK,
+        tip: None,
+        block_cbor: None,
+    };
+
+    buffer.follow(&forward(0), Some(&blocks[0].point()), None);
+    buffer.follow(&forward(1), Some(&blocks[1].point()), None);
+
+    // Block 1 is replaced by a block of another fork at its slot, so block 2
+    // does not confirm it
+    let fork = PallasPoint::Specific(blocks[1].slot, vec![0xff; 32]);
+    buffer.follow(&rollback, Some(&fork), None);
+    buffer.follow(&forward(2), Some(&blocks[2].point()), None);
+
+    assert_eq!(buffer.next_confirmed().unwrap().block_cbor, Some(blocks[0].block_cbor.clone()));
+    assert!(buffer.next_confirmed().is_none());
+
+    // Another fork at the slot of the confirmed block is deeper than it
+    let fork = PallasPoint::Specific(blocks[0].slot, vec![0xff; 32]);
+    buffer.follow(&rollback, Some(&fork), None);
+
+    assert_eq!(buffer.next_confirmed().unwrap().action, ACTION_ERROR);
+}
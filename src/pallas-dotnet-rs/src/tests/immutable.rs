@@ -0,0 +1,61 @@
+use crate::{immutable::ImmutableDbWrapper, ACTION_AWAIT};
+
+fn empty_db(name: &str) -> String {
+    let path = std::env::temp_dir().join(format!("pallas-dotnet-immutable-{}-{}", std::process::id(), name));
+    std::fs::create_dir_all(&path).unwrap();
+
+    path.to_string_lossy().to_string()
+}
+
+#[test]
+fn tip_of_an_empty_db_is_origin() {
+    let tip = ImmutableDbWrapper::get_immutable_db_tip(empty_db("tip"));
+
+    assert_eq!(tip.slot, 0);
+    assert!(tip.hash.is_empty());
+}
+
+#[test]
+fn opening_a_missing_block_fails() {
+    let point = crate::Point {
+        slot: 100,
+        hash: vec![1; 32],
+    };
+
+    assert!(ImmutableDbWrapper::open_immutable_db(empty_db("missing"), point).is_none());
+}
+
+#[test]
+fn opening_a_missing_directory_fails() {
+    let path = std::env::temp_dir().join(format!("pallas-dotnet-immutable-{}-absent", std::process::id()));
+    let origin = crate::Point { slot: 0, hash: vec![] };
+
+    assert!(ImmutableDbWrapper::open_immutable_db(path.to_string_lossy().to_string(), origin).is_none());
+}
+
+#[test]
+fn next_keeps_awaiting_past_the_last_block() {
+    let origin = crate::Point { slot: 0, hash: vec![] };
+    let db = ImmutableDbWrapper::open_immutable_db(empty_db("past-end"), origin).unwrap();
+
+    for _ in 0..3 {
+        let response = ImmutableDbWrapper::immutable_db_next(db.clone());
+
+        assert_eq!(response.action, ACTION_AWAIT);
+        assert!(response.block_cbor.is_none());
+    }
+
+    ImmutableDbWrapper::close_immutable_db(db);
+}
+
+#[test]
+fn closed_dbs_can_be_reopened() {
+    let path = empty_db("reopen");
+    let origin = crate::Point { slot: 0, hash: vec![] };
+
+    for _ in 0..2 {
+        let db = ImmutableDbWrapper::open_immutable_db(path.clone(), origin.clone()).unwrap();
+        assert_eq!(ImmutableDbWrapper::immutable_db_next(db.clone()).action, ACTION_AWAIT);
+        ImmutableDbWrapper::close_immutable_db(db);
+    }
+}
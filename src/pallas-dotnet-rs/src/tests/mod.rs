@@ -0,0 +1,79 @@
+// Exercises the exports against the mock node. The `#[net]` functions only
+// exist for the .NET side, so the tests call the plain functions they
+// delegate to.
+
+use std::{
+    sync::{RwLock, RwLockReadGuard, RwLockWriteGuard},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    bearer::ClientKind,
+    mock::{MockNode, MAGIC},
+    options::ClientOptions,
+    ClientWrapper, NextResponse, Point,
+};
+
+mod acked;
+mod cursor;
+mod events;
+mod filter;
+mod finality;
+mod immutable;
+mod n2c;
+mod n2n;
+mod offline;
+mod server;
+mod sink;
+mod store;
+mod utxo;
+
+// Every test runs on the shared runtime, which the runtime tests replace
+static RUNTIME: RwLock<()> = RwLock::new(());
+
+pub fn shared_runtime() -> RwLockReadGuard<'static, ()> {
+    RUNTIME.read().unwrap_or_else(|e| e.into_inner())
+}
+
+pub fn exclusive_runtime() -> RwLockWriteGuard<'static, ()> {
+    RUNTIME.write().unwrap_or_else(|e| e.into_inner())
+}
+
+pub fn options() -> ClientOptions {
+    ClientOptions {
+        connect_timeout_ms: 5_000,
+        handshake_timeout_ms: 5_000,
+        request_timeout_ms: 5_000,
+        // Tests that reconnect ask for it
+        max_reconnect_attempts: 0,
+        ..ClientOptions::default()
+    }
+}
+
+pub fn connect(node: &MockNode, client: ClientKind, options: ClientOptions) -> ClientWrapper {
+    ClientWrapper::connect(node.address(), MAGIC, client as u8, options).expect("cannot connect to the mock node")
+}
+
+pub fn next(client: &ClientWrapper) -> NextResponse {
+    ClientWrapper::chain_sync_next(client.clone())
+}
+
+pub fn point(node: &MockNode, block: usize) -> Point {
+    node.chain.blocks[block].point().into()
+}
+
+// Polls `f` until it returns something or the timeout elapses
+pub fn eventually<T>(timeout: Duration, mut f: impl FnMut() -> Option<T>) -> Option<T> {
+    let start = Instant::now();
+
+    while start.elapsed() < timeout {
+        if let Some(value) = f() {
+            return Some(value);
+        }
+
+        thread::sleep(Duration::from_millis(20));
+    }
+
+    None
+}
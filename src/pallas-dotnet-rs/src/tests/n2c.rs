@@ -0,0 +1,345 @@
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+use pallas::ledger::addresses::Address;
+
+use super::{connect, eventually, next, options, point, shared_runtime};
+use crate::{
+    bearer::ClientKind,
+    mock::{fixtures, MockChain, MockNode, Step, MAGIC},
+    options::ClientOptions,
+    ClientWrapper, NextBatch, ACTION_AWAIT, ACTION_CANCELLED, ACTION_PENDING, ACTION_RECONNECTED, ACTION_ROLL_BACK,
+    ACTION_ROLL_FORWARD,
+};
+
+#[test]
+fn connect_negotiates_a_n2c_version() {
+    let _runtime = shared_runtime();
+    let node = MockNode::n2c(MockChain::new(3));
+
+    let client = ClientWrapper::connect_with_options(node.address(), MAGIC, ClientKind::N2C as u8, options()).unwrap();
+    let version = ClientWrapper::get_negotiated_version(client.clone());
+
+    assert_eq!(client.client, ClientKind::N2C as u8);
+    assert_eq!(version.network_magic, MAGIC);
+    assert!(version.version >= 10 && version.version <= 16);
+
+    ClientWrapper::disconnect(client);
+}
+
+#[test]
+fn get_tip_queries_the_chain_point() {
+    let _runtime = shared_runtime();
+    let node = MockNode::n2c(MockChain::new(3));
+    let client = connect(&node, ClientKind::N2C, options());
+
+    let tip = ClientWrapper::get_tip(client.clone());
+
+    assert_eq!(tip.slot, node.chain.blocks[2].slot);
+    assert_eq!(tip.hash, node.chain.blocks[2].hash);
+
+    ClientWrapper::disconnect(client);
+}
+
+#[test]
+fn get_utxo_by_address_cbor_returns_the_served_utxos() {
+    let _runtime = shared_runtime();
+    let node = MockNode::n2c(MockChain::new(1));
+    let client = connect(&node, ClientKind::N2C, options());
+
+    // The query re-acquires, so the state has to be acquired first
+    ClientWrapper::get_tip(client.clone());
+
+    let address = Address::from_bytes(&fixtures::address()).unwrap().to_bech32().unwrap();
+    let utxos = ClientWrapper::get_utxo_by_address_cbor(client.clone(), address);
+
+    assert_eq!(utxos, vec![fixtures::utxo()]);
+
+    ClientWrapper::disconnect(client);
+}
+
+#[test]
+fn chain_sync_next_follows_the_script() {
+    let _runtime = shared_runtime();
+    let script = vec![Step::Forward(0), Step::Forward(1), Step::Backward(Some(0)), Step::Await, Step::Forward(1)];
+    let node = MockNode::n2c(MockChain::scripted(2, script));
+    let client = connect(&node, ClientKind::N2C, options());
+
+    let forward = next(&client);
+    assert_eq!(forward.action, ACTION_ROLL_FORWARD);
+    assert_eq!(forward.block_cbor, Some(node.chain.blocks[0].block_cbor.clone()));
+    assert_eq!(forward.tip.unwrap().hash, node.chain.blocks[1].hash);
+
+    assert_eq!(next(&client).action, ACTION_ROLL_FORWARD);
+
+    let backward = next(&client);
+    assert_eq!(backward.action, ACTION_ROLL_BACK);
+    assert!(backward.block_cbor.is_none());
+
+    assert_eq!(next(&client).action, ACTION_AWAIT);
+
+    let forward = next(&client);
+    assert_eq!(forward.action, ACTION_ROLL_FORWARD);
+    assert_eq!(forward.block_cbor, Some(node.chain.blocks[1].block_cbor.clone()));
+
+    ClientWrapper::disconnect(client);
+}
+
+// Blocks of a batch, one per response
+fn batch_blocks(batch: &NextBatch) -> Vec<Vec<u8>> {
+    batch
+        .offsets
+        .windows(2)
+        .map(|range| batch.blocks[range[0] as usize..range[1] as usize].to_vec())
+        .collect()
+}
+
+fn batch_actions(batch: &NextBatch) -> Vec<u8> {
+    batch.responses.iter().map(|response| response.action).collect()
+}
+
+#[test]
+fn chain_sync_next_batch_stops_at_the_tip() {
+    let _runtime = shared_runtime();
+    let node = MockNode::n2c(MockChain::new(5));
+    let client = connect(&node, ClientKind::N2C, options());
+
+    let batch = ClientWrapper::chain_sync_next_batch(client.clone(), 3, 0);
+    assert_eq!(batch_blocks(&batch), node.chain.blocks[..3].iter().map(|block| block.block_cbor.clone()).collect::<Vec<_>>());
+
+    // The last blocks and the Await that follows them
+    let batch = ClientWrapper::chain_sync_next_batch(client.clone(), 10, 5_000);
+    assert_eq!(batch_actions(&batch), vec![ACTION_ROLL_FORWARD, ACTION_ROLL_FORWARD, ACTION_AWAIT]);
+    assert_eq!(batch_blocks(&batch)[1], node.chain.blocks[4].block_cbor);
+    assert!(batch_blocks(&batch)[2].is_empty());
+
+    ClientWrapper::disconnect(client);
+}
+
+#[test]
+fn chain_sync_next_batch_gives_up_waiting_at_the_deadline() {
+    let _runtime = shared_runtime();
+    let node = MockNode::n2c(MockChain::scripted(2, vec![Step::Forward(0), Step::Await, Step::Forward(1)]));
+    let client = connect(&node, ClientKind::N2C, options());
+
+    let batch = ClientWrapper::chain_sync_next_batch(client.clone(), 10, 0);
+    assert_eq!(batch_actions(&batch), vec![ACTION_ROLL_FORWARD, ACTION_AWAIT]);
+
+    // The node only sends the next block after a delay
+    let batch = ClientWrapper::chain_sync_next_batch(client.clone(), 10, 10);
+    assert!(batch.responses.is_empty());
+
+    // The next call picks up the request left waiting
+    let batch = ClientWrapper::chain_sync_next_batch(client.clone(), 1, 5_000);
+    assert_eq!(batch_actions(&batch), vec![ACTION_ROLL_FORWARD]);
+    assert_eq!(batch_blocks(&batch), vec![node.chain.blocks[1].block_cbor.clone()]);
+
+    ClientWrapper::disconnect(client);
+}
+
+#[test]
+fn find_intersect_moves_the_cursor() {
+    let _runtime = shared_runtime();
+    let node = MockNode::n2c(MockChain::new(3));
+    let client = connect(&node, ClientKind::N2C, options());
+
+    let intersect = ClientWrapper::find_intersect(client.clone(), point(&node, 1)).unwrap();
+    assert_eq!(intersect.hash, node.chain.blocks[1].hash);
+
+    let forward = next(&client);
+    assert_eq!(forward.block_cbor, Some(node.chain.blocks[2].block_cbor.clone()));
+
+    let unknown = crate::Point {
+        slot: 1,
+        hash: vec![1; 32],
+    };
+    assert!(ClientWrapper::find_intersect(client.clone(), unknown).is_none());
+
+    ClientWrapper::disconnect(client);
+}
+
+#[test]
+fn cancel_interrupts_a_waiting_chain_sync_next() {
+    let _runtime = shared_runtime();
+    let node = MockNode::n2c(MockChain::new(1));
+    let client = connect(&node, ClientKind::N2C, options());
+
+    assert_eq!(next(&client).action, ACTION_ROLL_FORWARD);
+    assert_eq!(next(&client).action, ACTION_AWAIT);
+
+    let canceller = {
+        let client = client.clone();
+
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(200));
+            ClientWrapper::cancel(client);
+        })
+    };
+
+    // The script is exhausted, the node never replies
+    assert_eq!(next(&client).action, ACTION_CANCELLED);
+    canceller.join().unwrap();
+
+    ClientWrapper::disconnect(client);
+}
+
+#[test]
+fn chain_sync_stream_buffers_responses() {
+    let _runtime = shared_runtime();
+    let node = MockNode::n2c(MockChain::new(3));
+    let client = connect(&node, ClientKind::N2C, options());
+
+    assert!(ClientWrapper::chain_sync_try_next(client.clone()).is_error());
+
+    ClientWrapper::start_chain_sync_stream(client.clone(), 2);
+
+    let mut blocks = vec![];
+
+    while blocks.len() < 3 {
+        let response = eventually(Duration::from_secs(5), || {
+            let response = ClientWrapper::chain_sync_try_next(client.clone());
+            (response.action != ACTION_PENDING).then_some(response)
+        })
+        .expect("stream produced no response");
+
+        assert_eq!(response.action, ACTION_ROLL_FORWARD);
+        blocks.push(response.block_cbor.unwrap());
+    }
+
+    let expected: Vec<Vec<u8>> = node.chain.blocks.iter().map(|b| b.block_cbor.clone()).collect();
+    assert_eq!(blocks, expected);
+
+    // chain_sync_next is refused while the stream owns chain-sync
+    assert!(next(&client).is_error());
+
+    ClientWrapper::stop_chain_sync_stream(client.clone());
+    ClientWrapper::disconnect(client);
+}
+
+#[test]
+fn calls_needing_the_connection_give_up_while_the_stream_waits_at_the_tip() {
+    let _runtime = shared_runtime();
+    let node = MockNode::n2c(MockChain::new(1));
+    let options = ClientOptions {
+        request_timeout_ms: 200,
+        ..options()
+    };
+    let client = connect(&node, ClientKind::N2C, options);
+
+    ClientWrapper::start_chain_sync_stream(client.clone(), 2);
+
+    eventually(Duration::from_secs(5), || {
+        (ClientWrapper::chain_sync_try_next(client.clone()).action == ACTION_AWAIT).then_some(())
+    })
+    .expect("stream did not reach the tip");
+
+    let start = Instant::now();
+    assert_eq!(ClientWrapper::get_tip(client.clone()).slot, 0);
+    assert!(ClientWrapper::find_intersect(client.clone(), point(&node, 0)).is_none());
+    assert!(start.elapsed() < Duration::from_secs(2));
+
+    ClientWrapper::stop_chain_sync_stream(client.clone());
+    ClientWrapper::disconnect(client);
+}
+
+#[test]
+fn chain_sync_next_reconnects_and_resumes() {
+    let _runtime = shared_runtime();
+    let script = vec![Step::Forward(0), Step::Forward(1), Step::Disconnect, Step::Forward(2)];
+    let node = MockNode::n2c(MockChain::scripted(3, script));
+
+    let options = ClientOptions {
+        max_reconnect_attempts: 3,
+        reconnect_backoff_ms: 50,
+        ..options()
+    };
+
+    let client = connect(&node, ClientKind::N2C, options);
+
+    assert_eq!(next(&client).action, ACTION_ROLL_FORWARD);
+    assert_eq!(next(&client).action, ACTION_ROLL_FORWARD);
+
+    let reconnected = next(&client);
+    assert_eq!(reconnected.action, ACTION_RECONNECTED);
+    assert_eq!(reconnected.tip.unwrap().hash, node.chain.blocks[2].hash);
+
+    // Resumed after the last delivered block
+    let forward = next(&client);
+    assert_eq!(forward.block_cbor, Some(node.chain.blocks[2].block_cbor.clone()));
+
+    ClientWrapper::disconnect(client);
+}
+
+#[test]
+fn chain_sync_next_fails_without_reconnecting() {
+    let _runtime = shared_runtime();
+    let node = MockNode::n2c(MockChain::scripted(1, vec![Step::Forward(0), Step::Disconnect]));
+    let client = connect(&node, ClientKind::N2C, options());
+
+    assert_eq!(next(&client).action, ACTION_ROLL_FORWARD);
+    assert!(next(&client).is_error());
+
+    ClientWrapper::disconnect(client);
+}
+
+#[test]
+fn tx_submission_needs_a_n2n_connection() {
+    let _runtime = shared_runtime();
+    let node = MockNode::n2c(MockChain::new(1));
+    let client = connect(&node, ClientKind::N2C, options());
+
+    assert!(!ClientWrapper::start_tx_submission(client.clone()));
+    assert!(ClientWrapper::enqueue_tx(client.clone(), fixtures::tx(0)).is_none());
+    assert!(ClientWrapper::get_tx_submission_status(client.clone()).error.is_some());
+
+    ClientWrapper::disconnect(client);
+}
+
+#[test]
+fn keep_alive_stats_are_empty_for_n2c() {
+    let _runtime = shared_runtime();
+    let node = MockNode::n2c(MockChain::new(1));
+    let client = connect(&node, ClientKind::N2C, options());
+
+    let stats = ClientWrapper::get_keep_alive_stats(client.clone());
+
+    assert_eq!(stats.roundtrips(), 0);
+    assert!(stats.error.is_none());
+    assert!(ClientWrapper::get_peer_set_status(client.clone()).is_empty());
+
+    ClientWrapper::disconnect(client);
+}
+
+#[test]
+fn share_peers_is_empty_for_n2c() {
+    let _runtime = shared_runtime();
+    let node = MockNode::n2c(MockChain::new(1));
+    let client = connect(&node, ClientKind::N2C, options());
+
+    assert!(ClientWrapper::share_peers(client.clone(), 5).is_empty());
+
+    ClientWrapper::disconnect(client);
+}
+
+#[test]
+fn query_versions_completes_against_the_node() {
+    let _runtime = shared_runtime();
+    let node = MockNode::n2c(MockChain::new(1));
+
+    // Depending on the node the query is answered with the supported
+    // versions or with a plain acceptance, which yields no versions.
+    let versions = ClientWrapper::query_versions(node.address(), MAGIC, ClientKind::N2C as u8, options());
+
+    assert!(versions.iter().all(|v| v.network_magic == MAGIC));
+}
+
+#[test]
+fn connect_fails_without_a_node() {
+    let _runtime = shared_runtime();
+    let socket = std::env::temp_dir().join("pallas-dotnet-mock-missing.socket");
+
+    let client = ClientWrapper::connect(socket.to_string_lossy().to_string(), MAGIC, ClientKind::N2C as u8, options());
+    assert!(client.is_none());
+}
@@ -0,0 +1,273 @@
+use std::time::Duration;
+
+use super::{connect, eventually, next, options, point, shared_runtime};
+use crate::{
+    bearer::ClientKind,
+    mock::{fixtures, MockChain, MockNode, Step, MAGIC},
+    options::ClientOptions,
+    submission::{Mempool, TxStatus},
+    versions::HandshakeOptions,
+    ClientWrapper, ACTION_AWAIT, ACTION_ROLL_BACK, ACTION_ROLL_FORWARD,
+};
+
+#[test]
+fn connect_negotiates_a_n2n_version() {
+    let _runtime = shared_runtime();
+    let node = MockNode::n2n(MockChain::new(1));
+    let client = connect(&node, ClientKind::N2N, options());
+
+    let version = ClientWrapper::get_negotiated_version(client.clone());
+
+    assert_eq!(client.client, ClientKind::N2N as u8);
+    assert_eq!(version.network_magic, MAGIC);
+    assert!(version.version >= 7 && version.version <= 13);
+
+    ClientWrapper::disconnect(client);
+}
+
+#[test]
+fn chain_sync_next_fetches_block_bodies() {
+    let _runtime = shared_runtime();
+    let script = vec![Step::Forward(0), Step::Forward(1), Step::Backward(Some(0)), Step::Await];
+    let node = MockNode::n2n(MockChain::scripted(2, script));
+    let client = connect(&node, ClientKind::N2N, options());
+
+    let forward = next(&client);
+    assert_eq!(forward.action, ACTION_ROLL_FORWARD);
+    assert_eq!(forward.block_cbor, Some(node.chain.blocks[0].block_cbor.clone()));
+
+    let forward = next(&client);
+    assert_eq!(forward.block_cbor, Some(node.chain.blocks[1].block_cbor.clone()));
+
+    // N2N rollbacks carry the block rolled back to
+    let backward = next(&client);
+    assert_eq!(backward.action, ACTION_ROLL_BACK);
+    assert_eq!(backward.block_cbor, Some(node.chain.blocks[0].block_cbor.clone()));
+
+    assert_eq!(next(&client).action, ACTION_AWAIT);
+
+    ClientWrapper::disconnect(client);
+}
+
+#[test]
+fn fetch_block_returns_the_block_at_a_point() {
+    let _runtime = shared_runtime();
+    let node = MockNode::n2n(MockChain::new(3));
+    let client = connect(&node, ClientKind::N2N, options());
+
+    let block = ClientWrapper::fetch_block(client.clone(), point(&node, 1));
+    assert_eq!(block, Some(node.chain.blocks[1].block_cbor.clone()));
+
+    let unknown = crate::Point {
+        slot: 1,
+        hash: vec![1; 32],
+    };
+    assert!(ClientWrapper::fetch_block(client.clone(), unknown).is_none());
+
+    ClientWrapper::disconnect(client);
+}
+
+#[test]
+fn get_tip_and_find_intersect_use_chain_sync() {
+    let _runtime = shared_runtime();
+    let node = MockNode::n2n(MockChain::new(3));
+    let client = connect(&node, ClientKind::N2N, options());
+
+    assert_eq!(next(&client).block_cbor, Some(node.chain.blocks[0].block_cbor.clone()));
+
+    let tip = ClientWrapper::get_tip(client.clone());
+    assert_eq!(tip.hash, node.chain.blocks[2].hash);
+
+    // Asking for the tip leaves chain-sync where the consumer is
+    assert_eq!(next(&client).block_cbor, Some(node.chain.blocks[1].block_cbor.clone()));
+
+    let intersect = ClientWrapper::find_intersect(client.clone(), point(&node, 0)).unwrap();
+    assert_eq!(intersect.slot, node.chain.blocks[0].slot);
+
+    let forward = next(&client);
+    assert_eq!(forward.block_cbor, Some(node.chain.blocks[1].block_cbor.clone()));
+
+    ClientWrapper::disconnect(client);
+}
+
+#[test]
+fn keep_alive_round_trips_in_the_background() {
+    let _runtime = shared_runtime();
+    let node = MockNode::n2n(MockChain::new(1));
+
+    let options = ClientOptions {
+        keepalive_interval_ms: 50,
+        ..options()
+    };
+
+    let client = connect(&node, ClientKind::N2N, options);
+
+    let stats = eventually(Duration::from_secs(5), || {
+        let stats = ClientWrapper::get_keep_alive_stats(client.clone());
+        (stats.roundtrips() > 1).then_some(stats)
+    })
+    .expect("no keepalive round-trip");
+
+    assert!(stats.error.is_none());
+
+    ClientWrapper::disconnect(client);
+}
+
+#[test]
+fn share_peers_returns_the_node_peers() {
+    let _runtime = shared_runtime();
+    let node = MockNode::n2n(MockChain::new(1));
+
+    let options = ClientOptions {
+        handshake: HandshakeOptions {
+            peer_sharing: true,
+            ..HandshakeOptions::default()
+        },
+        ..options()
+    };
+
+    let client = connect(&node, ClientKind::N2N, options);
+    assert!(ClientWrapper::get_negotiated_version(client.clone()).peer_sharing);
+
+    let peers = ClientWrapper::share_peers(client.clone(), 5);
+
+    assert_eq!(peers.len(), 2);
+    assert_eq!(peers[0].ip, "10.0.0.1");
+    assert_eq!(peers[0].port, 3001);
+
+    ClientWrapper::disconnect(client);
+}
+
+#[test]
+fn peer_set_fails_over_to_the_remaining_peer() {
+    let _runtime = shared_runtime();
+    let script = vec![Step::Forward(0), Step::Forward(1), Step::Disconnect, Step::Forward(2)];
+    let first = MockNode::n2n(MockChain::scripted(3, script.clone()));
+    let second = MockNode::n2n(MockChain::scripted(3, script));
+
+    let options = ClientOptions {
+        max_reconnect_attempts: 3,
+        reconnect_backoff_ms: 10,
+        ..options()
+    };
+
+    let client = ClientWrapper::connect_peer_set(vec![first.address(), second.address()], MAGIC, options, true).unwrap();
+
+    let status = ClientWrapper::get_peer_set_status(client.clone());
+    assert_eq!(status.len(), 2);
+    assert!(status.iter().all(|peer| peer.connected));
+    assert_eq!(status.iter().filter(|peer| peer.active).count(), 1);
+
+    assert_eq!(next(&client).action, ACTION_ROLL_FORWARD);
+    assert_eq!(next(&client).action, ACTION_ROLL_FORWARD);
+
+    // Each peer drops the connection once, chain-sync moves on without a gap
+    let forward = next(&client);
+    assert_eq!(forward.action, ACTION_ROLL_FORWARD);
+    assert_eq!(forward.block_cbor, Some(first.chain.blocks[2].block_cbor.clone()));
+
+    let status = ClientWrapper::get_peer_set_status(client.clone());
+    assert_eq!(status.iter().filter(|peer| peer.active).count(), 1);
+    assert!(status.iter().any(|peer| peer.server == first.address()));
+
+    ClientWrapper::disconnect(client);
+}
+
+#[test]
+fn submit_txs_delivers_every_tx() {
+    let _runtime = shared_runtime();
+    let node = MockNode::n2n(MockChain::new(1));
+
+    let txs = vec![fixtures::tx(0), fixtures::tx(1)];
+    let outcome = ClientWrapper::submit_txs(node.address(), MAGIC, txs.clone());
+
+    assert!(outcome.error.is_none());
+    assert_eq!(outcome.results.len(), 2);
+    assert!(outcome.results.iter().all(|r| r.status == TxStatus::Acknowledged as u8 && r.body_requested));
+    assert_eq!(node.chain.received_txs(), txs);
+
+    let outcome = ClientWrapper::submit_tx(node.address(), MAGIC, fixtures::tx(2));
+    assert_eq!(outcome.results.len(), 1);
+}
+
+#[test]
+fn submit_tx_rejects_invalid_cbor() {
+    let _runtime = shared_runtime();
+    let node = MockNode::n2n(MockChain::new(1));
+
+    let outcome = ClientWrapper::submit_tx(node.address(), MAGIC, vec![0xff]);
+
+    assert!(outcome.error.is_some());
+    assert!(node.chain.received_txs().is_empty());
+}
+
+#[test]
+fn tx_submission_session_acknowledges_enqueued_txs() {
+    let _runtime = shared_runtime();
+    let node = MockNode::n2n(MockChain::new(1));
+    let client = connect(&node, ClientKind::N2N, options());
+
+    assert!(ClientWrapper::enqueue_tx(client.clone(), fixtures::tx(0)).is_none());
+    assert!(ClientWrapper::start_tx_submission(client.clone()));
+
+    let tx_id = ClientWrapper::enqueue_tx(client.clone(), fixtures::tx(0)).expect("tx was not enqueued");
+    assert_eq!(tx_id.len(), 32);
+    assert!(ClientWrapper::enqueue_tx(client.clone(), vec![0xff]).is_none());
+
+    let outcome = eventually(Duration::from_secs(5), || {
+        let outcome = ClientWrapper::get_tx_submission_status(client.clone());
+        outcome
+            .results
+            .iter()
+            .all(|r| r.status == TxStatus::Acknowledged as u8)
+            .then_some(outcome)
+    })
+    .expect("tx was not acknowledged");
+
+    assert_eq!(outcome.results[0].tx_id, tx_id);
+    assert!(outcome.results[0].body_requested);
+    assert!(outcome.error.is_none());
+    assert_eq!(node.chain.received_txs(), vec![fixtures::tx(0)]);
+
+    ClientWrapper::stop_tx_submission(client.clone());
+    assert!(ClientWrapper::get_tx_submission_status(client.clone()).error.is_some());
+    // The stopped session consumed the mini-protocol client
+    assert!(!ClientWrapper::start_tx_submission(client.clone()));
+
+    ClientWrapper::disconnect(client);
+}
+
+#[test]
+fn mempool_evicts_acknowledged_txs_once_full() {
+    let mut mempool = Mempool::with_capacity(2);
+
+    mempool.push(fixtures::tx(0)).unwrap();
+    mempool.push(fixtures::tx(1)).unwrap();
+    assert!(mempool.push(fixtures::tx(2)).is_err());
+
+    assert_eq!(mempool.announce(2).len(), 2);
+    mempool.acknowledge(1);
+
+    let tx_id = mempool.push(fixtures::tx(2)).unwrap();
+    assert!(mempool.push(fixtures::tx(3)).is_err());
+
+    let results = mempool.results();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].status, TxStatus::Announced as u8);
+    assert_eq!(results[1].tx_id, tx_id);
+
+    // Numbers keep pointing at the right txs after the eviction
+    assert_eq!(mempool.announce(1).len(), 1);
+    mempool.acknowledge(2);
+    assert!(mempool.results().iter().all(|r| r.status == TxStatus::Acknowledged as u8));
+}
+
+#[test]
+fn query_versions_completes_against_the_node() {
+    let _runtime = shared_runtime();
+    let node = MockNode::n2n(MockChain::new(1));
+
+    let versions = ClientWrapper::query_versions(node.address(), MAGIC, ClientKind::N2N as u8, options());
+
+    assert!(versions.iter().all(|v| v.network_magic == MAGIC));
+}
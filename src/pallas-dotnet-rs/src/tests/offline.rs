@@ -0,0 +1,344 @@
+use std::time::{Duration, Instant};
+
+use pallas::network::miniprotocols::{
+    handshake::{n2c, n2n},
+    MAINNET_MAGIC, PREVIEW_MAGIC, PRE_PRODUCTION_MAGIC, TESTNET_MAGIC,
+};
+
+use super::{connect, exclusive_runtime, next, options};
+use crate::{
+    bearer::ClientKind,
+    evaluation::{ScriptEvaluator, SlotConfig},
+    mock::{fixtures, MockChain, MockNode},
+    runtime::{self, NativeRuntime},
+    validation::{ProtocolParameters, ResolvedInput, TxValidator, ERA_BABBAGE},
+    versions::VersionInfo,
+    ClientWrapper, NetworkMagic, PallasUtility, ACTION_ROLL_FORWARD,
+};
+
+#[test]
+fn network_magics_match_pallas() {
+    assert_eq!(NetworkMagic::mainnet_magic(), MAINNET_MAGIC);
+    assert_eq!(NetworkMagic::testnet_magic(), TESTNET_MAGIC);
+    assert_eq!(NetworkMagic::preview_magic(), PREVIEW_MAGIC);
+    assert_eq!(NetworkMagic::pre_production_magic(), PRE_PRODUCTION_MAGIC);
+}
+
+#[test]
+fn version_info_reads_the_handshake_version_data() {
+    let n2c = VersionInfo::n2c(32784, &n2c::VersionData::new(PREVIEW_MAGIC, Some(true)));
+    assert_eq!((n2c.version, n2c.network_magic, n2c.query), (16, PREVIEW_MAGIC, true));
+
+    let n2c = VersionInfo::n2c(32778, &n2c::VersionData::new(MAINNET_MAGIC, None));
+    assert_eq!((n2c.version, n2c.network_magic, n2c.query), (10, MAINNET_MAGIC, false));
+
+    let n2n = VersionInfo::n2n(13, &n2n::VersionData::new(PREVIEW_MAGIC, false, Some(1), Some(true)));
+    assert_eq!(n2n.network_magic, PREVIEW_MAGIC);
+    assert!(n2n.query && n2n.peer_sharing && !n2n.initiator_only_diffusion_mode);
+
+    let n2n = VersionInfo::n2n(10, &n2n::VersionData::new(MAINNET_MAGIC, true, None, None));
+    assert_eq!(n2n.network_magic, MAINNET_MAGIC);
+    assert!(!n2n.query && !n2n.peer_sharing && n2n.initiator_only_diffusion_mode);
+}
+
+#[test]
+fn address_bytes_to_bech32_encodes_shelley_addresses() {
+    let address = PallasUtility::address_bytes_to_bech32(fixtures::address());
+
+    assert!(address.starts_with("addr_test1"));
+}
+
+// Mainnet's Babbage parameters
+fn babbage_params() -> ProtocolParameters {
+    ProtocolParameters {
+        era: ERA_BABBAGE,
+        minfee_a: 44,
+        minfee_b: 155_381,
+        max_block_body_size: 90_112,
+        max_transaction_size: 16_384,
+        max_block_header_size: 1_100,
+        key_deposit: 2_000_000,
+        pool_deposit: 500_000_000,
+        protocol_major: 8,
+        protocol_minor: 0,
+        min_pool_cost: 170_000_000,
+        ada_per_utxo_byte: 4_310,
+        max_value_size: 5_000,
+        collateral_percentage: 150,
+        max_collateral_inputs: 3,
+        max_tx_ex_mem: 14_000_000,
+        max_tx_ex_steps: 10_000_000_000,
+        max_block_ex_mem: 62_000_000,
+        max_block_ex_steps: 20_000_000_000,
+        mem_price_numerator: 577,
+        mem_price_denominator: 10_000,
+        step_price_numerator: 721,
+        step_price_denominator: 10_000_000,
+        ..ProtocolParameters::default()
+    }
+}
+
+// The key locked input spent by `fixtures::signed_tx`
+fn signed_input(coin: u64) -> ResolvedInput {
+    ResolvedInput {
+        tx_hash: vec![0; 32],
+        index: 0,
+        output_cbor: fixtures::output(&fixtures::signed_address(), coin, &[]),
+    }
+}
+
+fn validate(tx: Vec<u8>, input: ResolvedInput, params: ProtocolParameters, magic: u64) -> Vec<String> {
+    TxValidator::validate_tx(tx, vec![input], params, magic, 1_000)
+}
+
+#[test]
+fn validate_tx_accepts_a_balanced_tx() {
+    let violations = validate(fixtures::signed_tx(0, 2_000_000, 200_000), signed_input(2_200_000), babbage_params(), PREVIEW_MAGIC);
+
+    assert_eq!(violations, Vec::<String>::new());
+}
+
+#[test]
+fn validate_tx_reports_invalid_cbor() {
+    let violations = TxValidator::validate_tx(vec![0xff], vec![], babbage_params(), PREVIEW_MAGIC, 0);
+
+    assert_eq!(violations.len(), 1);
+    assert!(violations[0].starts_with("the tx is not valid Babbage CBOR"));
+}
+
+#[test]
+fn validate_tx_rejects_unsupported_eras() {
+    let params = ProtocolParameters {
+        era: 1,
+        ..babbage_params()
+    };
+
+    let violations = validate(fixtures::signed_tx(0, 2_000_000, 200_000), signed_input(2_200_000), params, PREVIEW_MAGIC);
+
+    assert_eq!(violations, vec!["era 1 is not supported, only Alonzo and Babbage txs can be validated"]);
+}
+
+#[test]
+fn validate_tx_reports_unresolved_inputs() {
+    let violations = TxValidator::validate_tx(fixtures::signed_tx(0, 2_000_000, 200_000), vec![], babbage_params(), PREVIEW_MAGIC, 1_000);
+
+    assert_eq!(violations, vec!["an input is not among the resolved inputs"]);
+}
+
+#[test]
+fn validate_tx_rejects_malformed_input_hashes() {
+    let resolved = ResolvedInput {
+        tx_hash: vec![0; 4],
+        index: 0,
+        output_cbor: vec![],
+    };
+
+    let violations = validate(fixtures::tx(0), resolved, babbage_params(), PREVIEW_MAGIC);
+
+    assert_eq!(violations, vec![format!("input hash {} is not 32 bytes", hex::encode([0; 4]))]);
+}
+
+#[test]
+fn validate_tx_rejects_fees_below_the_minimum() {
+    let params = ProtocolParameters {
+        minfee_b: 1_000_000,
+        ..babbage_params()
+    };
+
+    let violations = validate(fixtures::signed_tx(0, 2_000_000, 200_000), signed_input(2_200_000), params, PREVIEW_MAGIC);
+
+    assert_eq!(violations, vec!["the fee is below the minimum fee"]);
+}
+
+#[test]
+fn validate_tx_rejects_unbalanced_txs() {
+    let violations = validate(fixtures::signed_tx(0, 2_000_000, 200_000), signed_input(3_000_000), babbage_params(), PREVIEW_MAGIC);
+
+    assert_eq!(violations, vec!["the inputs do not balance the outputs, fee and deposits"]);
+}
+
+#[test]
+fn validate_tx_rejects_outputs_below_the_minimum_ada() {
+    let params = ProtocolParameters {
+        ada_per_utxo_byte: 1_000_000,
+        ..babbage_params()
+    };
+
+    let violations = validate(fixtures::signed_tx(0, 2_000_000, 200_000), signed_input(2_200_000), params, PREVIEW_MAGIC);
+
+    assert_eq!(violations, vec!["an output holds less than the minimum ada"]);
+}
+
+#[test]
+fn validate_tx_reports_the_violations_after_a_parameter_limit() {
+    let params = ProtocolParameters {
+        minfee_b: 1_000_000,
+        ada_per_utxo_byte: 1_000_000,
+        ..babbage_params()
+    };
+
+    let violations = validate(fixtures::signed_tx(0, 2_000_000, 200_000), signed_input(2_200_000), params, PREVIEW_MAGIC);
+
+    assert_eq!(violations, vec!["the fee is below the minimum fee", "an output holds less than the minimum ada"]);
+}
+
+#[test]
+fn validate_tx_rejects_oversized_values() {
+    let params = ProtocolParameters {
+        max_value_size: 0,
+        ..babbage_params()
+    };
+
+    let violations = validate(fixtures::signed_tx(0, 2_000_000, 200_000), signed_input(2_200_000), params, PREVIEW_MAGIC);
+
+    assert_eq!(violations, vec!["an output value is larger than the maximum value size"]);
+}
+
+#[test]
+fn validate_tx_rejects_outputs_to_another_network() {
+    let violations = validate(fixtures::signed_tx(0, 2_000_000, 200_000), signed_input(2_200_000), babbage_params(), MAINNET_MAGIC);
+
+    assert_eq!(violations, vec!["an output address belongs to another network"]);
+}
+
+#[test]
+fn validate_tx_rejects_oversized_txs() {
+    let params = ProtocolParameters {
+        max_transaction_size: 10,
+        ..babbage_params()
+    };
+
+    let violations = validate(fixtures::signed_tx(0, 2_000_000, 200_000), signed_input(2_200_000), params, PREVIEW_MAGIC);
+
+    assert_eq!(violations, vec!["the tx is larger than the maximum tx size"]);
+}
+
+#[test]
+fn validate_tx_rejects_missing_key_witnesses() {
+    let input = ResolvedInput {
+        tx_hash: vec![0; 32],
+        index: 0,
+        output_cbor: fixtures::output(&fixtures::address(), 2_170_000, &[]),
+    };
+
+    let violations = validate(fixtures::tx(0), input, babbage_params(), PREVIEW_MAGIC);
+
+    assert_eq!(violations, vec!["an input or certificate is missing its key witness"]);
+}
+
+#[test]
+fn evaluate_tx_reports_malformed_input_hashes() {
+    let resolved = ResolvedInput {
+        tx_hash: vec![0; 4],
+        index: 0,
+        output_cbor: vec![],
+    };
+
+    let result = ScriptEvaluator::evaluate_tx(fixtures::tx(0), vec![resolved], babbage_params(), SlotConfig::default());
+
+    assert!(result.redeemers.is_empty());
+    assert!(result.error.unwrap().starts_with("InvalidInputHash"));
+}
+
+#[test]
+fn evaluate_tx_fails_on_invalid_cbor() {
+    let result = ScriptEvaluator::evaluate_tx(vec![0xff], vec![], babbage_params(), SlotConfig::default());
+
+    assert!(result.redeemers.is_empty());
+    assert!(result.error.is_some());
+}
+
+fn plutus_input() -> ResolvedInput {
+    ResolvedInput {
+        tx_hash: vec![0; 32],
+        index: 0,
+        output_cbor: fixtures::output(&fixtures::plutus_script_address(), 2_200_000, &[]),
+    }
+}
+
+#[test]
+fn evaluate_tx_returns_the_redeemer_ex_units() {
+    let params = babbage_params();
+    let (max_mem, max_steps) = (params.max_tx_ex_mem, params.max_tx_ex_steps);
+
+    let result = ScriptEvaluator::evaluate_tx(fixtures::plutus_tx(0), vec![plutus_input()], params, SlotConfig::default());
+
+    assert_eq!(result.error, None);
+    assert_eq!(result.logs, vec![fixtures::ALWAYS_SUCCEEDS_TRACE]);
+    assert_eq!(result.redeemers.len(), 1);
+
+    let redeemer = &result.redeemers[0];
+    assert_eq!((redeemer.tag, redeemer.index), (0, 0));
+    assert!(redeemer.mem > 0 && redeemer.mem <= max_mem);
+    assert!(redeemer.steps > 0 && redeemer.steps <= max_steps);
+}
+
+#[test]
+fn evaluate_tx_is_bounded_by_the_max_tx_ex_units() {
+    let result = ScriptEvaluator::evaluate_tx(fixtures::plutus_tx(0), vec![plutus_input()], babbage_params(), SlotConfig::default());
+    let needed = result.redeemers[0].mem;
+
+    let params = ProtocolParameters {
+        max_tx_ex_mem: needed,
+        ..babbage_params()
+    };
+    let result = ScriptEvaluator::evaluate_tx(fixtures::plutus_tx(0), vec![plutus_input()], params, SlotConfig::default());
+    assert_eq!(result.redeemers[0].mem, needed);
+
+    let params = ProtocolParameters {
+        max_tx_ex_mem: needed - 1,
+        ..babbage_params()
+    };
+    let result = ScriptEvaluator::evaluate_tx(fixtures::plutus_tx(0), vec![plutus_input()], params, SlotConfig::default());
+    assert!(result.redeemers.is_empty());
+    assert!(result.error.is_some());
+}
+
+#[test]
+fn runtime_can_be_configured_before_use_and_restarted() {
+    let _runtime = exclusive_runtime();
+
+    NativeRuntime::shutdown_runtime(1_000);
+
+    assert!(NativeRuntime::init_runtime(2, "pallas-test".to_string(), false));
+    assert!(!NativeRuntime::init_runtime(0, String::new(), false));
+
+    let node = MockNode::n2c(MockChain::new(1));
+    let client = connect(&node, ClientKind::N2C, options());
+    assert_eq!(next(&client).action, ACTION_ROLL_FORWARD);
+    ClientWrapper::disconnect(client);
+    drop(node);
+
+    NativeRuntime::shutdown_runtime(1_000);
+
+    // A current-thread runtime still serves blocking calls
+    assert!(NativeRuntime::init_runtime(0, String::new(), true));
+
+    let node = MockNode::n2c(MockChain::new(1));
+    let client = connect(&node, ClientKind::N2C, options());
+    assert_eq!(next(&client).action, ACTION_ROLL_FORWARD);
+    ClientWrapper::disconnect(client);
+    drop(node);
+
+    // Leave a default runtime behind for the other tests
+    NativeRuntime::shutdown_runtime(1_000);
+}
+
+#[test]
+fn shutdown_runtime_does_not_wait_for_blocked_calls() {
+    let _runtime = exclusive_runtime();
+
+    NativeRuntime::shutdown_runtime(1_000);
+    assert!(NativeRuntime::init_runtime(0, String::new(), true));
+
+    let blocked = std::thread::spawn(|| runtime::block_on(async { tokio::time::sleep(Duration::from_millis(500)).await }));
+    std::thread::sleep(Duration::from_millis(100));
+
+    let start = Instant::now();
+    NativeRuntime::shutdown_runtime(1_000);
+    assert!(start.elapsed() < Duration::from_millis(300));
+
+    // The blocked call still completes on the runtime it started on
+    blocked.join().unwrap();
+    NativeRuntime::shutdown_runtime(1_000);
+}
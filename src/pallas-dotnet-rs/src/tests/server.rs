@@ -0,0 +1,174 @@
+use std::{thread, time::Duration};
+
+use super::{next, options, shared_runtime};
+use crate::{
+    bearer::{BearerKind, ClientKind},
+    mock::{fixtures, MAGIC},
+    server::ServerWrapper,
+    extends_tip, ClientWrapper, Point, ACTION_AWAIT, ACTION_ERROR, ACTION_ROLL_BACK, ACTION_ROLL_FORWARD,
+};
+
+fn start(client: ClientKind, bearer: BearerKind, address: String, capacity: u64) -> ServerWrapper {
+    ServerWrapper::start_server(address, MAGIC, client as u8, bearer as u8, capacity).expect("cannot start the server")
+}
+
+fn socket(name: &str) -> String {
+    let socket = std::env::temp_dir().join(format!("pallas-dotnet-server-{}-{}.socket", std::process::id(), name));
+    let _ = std::fs::remove_file(&socket);
+
+    socket.to_string_lossy().to_string()
+}
+
+#[test]
+fn n2c_consumers_follow_the_pushed_blocks() {
+    let _runtime = shared_runtime();
+    let blocks = fixtures::chain(3);
+    let server = start(ClientKind::N2C, BearerKind::Unix, socket("n2c"), 0);
+
+    for block in &blocks[..2] {
+        assert!(ServerWrapper::server_roll_forward(server.clone(), block.block_cbor.clone()));
+    }
+
+    let address = ServerWrapper::get_server_address(server.clone());
+    let client = ClientWrapper::connect(address, MAGIC, ClientKind::N2C as u8, options()).unwrap();
+
+    for block in &blocks[..2] {
+        let forward = next(&client);
+        assert_eq!(forward.action, ACTION_ROLL_FORWARD);
+        assert_eq!(forward.block_cbor, Some(block.block_cbor.clone()));
+    }
+
+    assert_eq!(next(&client).action, ACTION_AWAIT);
+
+    // A consumer waiting at the tip is woken up by the next block
+    let pusher = {
+        let server = server.clone();
+        let block = blocks[2].block_cbor.clone();
+
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(100));
+            ServerWrapper::server_roll_forward(server, block);
+        })
+    };
+
+    let forward = next(&client);
+    assert_eq!(forward.block_cbor, Some(blocks[2].block_cbor.clone()));
+    assert_eq!(forward.tip.unwrap().hash, blocks[2].hash);
+    pusher.join().unwrap();
+
+    // Rolling the store back rolls the consumer back to the same point
+    assert!(ServerWrapper::server_roll_backward(server.clone(), blocks[0].point().into()));
+
+    let backward = next(&client);
+    assert_eq!(backward.action, ACTION_ROLL_BACK);
+    assert_eq!(backward.tip.unwrap().hash, blocks[0].hash);
+
+    ClientWrapper::disconnect(client);
+    ServerWrapper::stop_server(server);
+}
+
+#[test]
+fn n2n_consumers_fetch_blocks() {
+    let _runtime = shared_runtime();
+    let blocks = fixtures::chain(3);
+    let server = start(ClientKind::N2N, BearerKind::Tcp, "127.0.0.1:0".to_string(), 0);
+
+    for block in &blocks {
+        assert!(ServerWrapper::server_roll_forward(server.clone(), block.block_cbor.clone()));
+    }
+
+    let tip = ServerWrapper::get_server_tip(server.clone());
+    assert_eq!(tip.hash, blocks[2].hash);
+
+    let address = ServerWrapper::get_server_address(server.clone());
+    let client = ClientWrapper::connect(address, MAGIC, ClientKind::N2N as u8, options()).unwrap();
+
+    let intersect = ClientWrapper::find_intersect(client.clone(), blocks[0].point().into()).unwrap();
+    assert_eq!(intersect.hash, blocks[0].hash);
+
+    let forward = next(&client);
+    assert_eq!(forward.action, ACTION_ROLL_FORWARD);
+    assert_eq!(forward.block_cbor, Some(blocks[1].block_cbor.clone()));
+
+    let block = ClientWrapper::fetch_block(client.clone(), blocks[2].point().into());
+    assert_eq!(block, Some(blocks[2].block_cbor.clone()));
+
+    ClientWrapper::disconnect(client);
+    ServerWrapper::stop_server(server);
+}
+
+#[test]
+fn store_rejects_blocks_that_do_not_extend_the_tip() {
+    let _runtime = shared_runtime();
+    let blocks = fixtures::chain(2);
+    let server = start(ClientKind::N2N, BearerKind::Tcp, "127.0.0.1:0".to_string(), 0);
+
+    assert!(ServerWrapper::server_roll_forward(server.clone(), blocks[1].block_cbor.clone()));
+    assert!(!ServerWrapper::server_roll_forward(server.clone(), blocks[0].block_cbor.clone()));
+    assert!(!ServerWrapper::server_roll_forward(server.clone(), vec![0xff]));
+
+    // Unknown points are refused, origin empties the store
+    assert!(!ServerWrapper::server_roll_backward(server.clone(), blocks[0].point().into()));
+    assert!(ServerWrapper::server_roll_backward(server.clone(), Point { slot: 0, hash: vec![] }));
+    assert!(ServerWrapper::get_server_tip(server.clone()).hash.is_empty());
+
+    ServerWrapper::stop_server(server);
+}
+
+#[test]
+fn only_the_block_after_an_epoch_boundary_shares_its_slot() {
+    assert!(extends_tip(120, false, 100, false));
+    assert!(extends_tip(120, false, 120, true));
+    assert!(!extends_tip(120, false, 120, false));
+    assert!(!extends_tip(120, true, 120, true));
+    assert!(!extends_tip(100, false, 120, true));
+}
+
+#[test]
+fn store_keeps_only_the_last_blocks() {
+    let _runtime = shared_runtime();
+    let blocks = fixtures::chain(3);
+    let server = start(ClientKind::N2N, BearerKind::Tcp, "127.0.0.1:0".to_string(), 2);
+
+    for block in &blocks {
+        assert!(ServerWrapper::server_roll_forward(server.clone(), block.block_cbor.clone()));
+    }
+
+    // The oldest block was evicted and can no longer be rolled back to
+    assert!(!ServerWrapper::server_roll_backward(server.clone(), blocks[0].point().into()));
+    assert!(ServerWrapper::server_roll_backward(server.clone(), blocks[1].point().into()));
+
+    ServerWrapper::stop_server(server);
+}
+
+#[test]
+fn consumers_behind_the_retained_blocks_are_dropped() {
+    let _runtime = shared_runtime();
+    let blocks = fixtures::chain(3);
+    let server = start(ClientKind::N2C, BearerKind::Unix, socket("evicted"), 1);
+
+    assert!(ServerWrapper::server_roll_forward(server.clone(), blocks[0].block_cbor.clone()));
+
+    let address = ServerWrapper::get_server_address(server.clone());
+    let client = ClientWrapper::connect(address, MAGIC, ClientKind::N2C as u8, options()).unwrap();
+    assert_eq!(next(&client).block_cbor, Some(blocks[0].block_cbor.clone()));
+
+    // Blocks 0 and 1 are evicted before the consumer asks for block 1, it is
+    // disconnected instead of being handed block 2 after a rollback to origin
+    for block in &blocks[1..] {
+        assert!(ServerWrapper::server_roll_forward(server.clone(), block.block_cbor.clone()));
+    }
+
+    assert_eq!(next(&client).action, ACTION_ERROR);
+
+    ClientWrapper::disconnect(client);
+    ServerWrapper::stop_server(server);
+}
+
+#[test]
+fn start_server_fails_on_an_unusable_address() {
+    let _runtime = shared_runtime();
+
+    let server = ServerWrapper::start_server("not an address".to_string(), MAGIC, ClientKind::N2N as u8, BearerKind::Tcp as u8, 0);
+    assert!(server.is_none());
+}
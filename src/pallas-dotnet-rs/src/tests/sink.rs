@@ -0,0 +1,183 @@
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use pallas::network::miniprotocols::Point as PallasPoint;
+use serde_json::Value;
+
+use super::{connect, eventually, options, shared_runtime};
+use crate::{
+    bearer::ClientKind,
+    mock::{fixtures, MockBlock, MockChain, MockNode},
+    sink::{FileSink, SinkOptions, SINK_NDJSON, SINK_RAW_CBOR},
+    ClientWrapper, NextResponse, ACTION_ROLL_BACK, ACTION_ROLL_FORWARD,
+};
+
+fn empty_directory(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("pallas-dotnet-sink-{}-{}", std::process::id(), name));
+    let _ = std::fs::remove_dir_all(&path);
+
+    path
+}
+
+fn sink_options(path: &Path, format: u8, max_file_bytes: u64) -> SinkOptions {
+    SinkOptions {
+        directory: path.to_string_lossy().to_string(),
+        format,
+        max_file_bytes,
+    }
+}
+
+fn forward(block: &MockBlock) -> NextResponse {
+    NextResponse {
+        action: ACTION_ROLL_FORWARD,
+        tip: None,
+        block_cbor: Some(block.block_cbor.clone()),
+    }
+}
+
+fn backward() -> NextResponse {
+    NextResponse {
+        action: ACTION_ROLL_BACK,
+        tip: None,
+        block_cbor: None,
+    }
+}
+
+// Contents of the sink files in order
+fn files(path: &Path, extension: &str) -> Vec<Vec<u8>> {
+    let mut names: Vec<PathBuf> = std::fs::read_dir(path)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|e| e == extension))
+        .collect();
+
+    names.sort();
+    names.into_iter().map(|name| std::fs::read(name).unwrap()).collect()
+}
+
+#[test]
+fn raw_files_follow_rollbacks_and_rotate() {
+    let blocks = fixtures::chain(3);
+    let path = empty_directory("raw");
+    let mut sink = FileSink::open(&sink_options(&path, SINK_RAW_CBOR, 1)).unwrap();
+
+    for block in &blocks {
+        sink.write(&forward(block), Some(&block.point())).unwrap();
+    }
+
+    // One block per file
+    assert_eq!(files(&path, "cbor").len(), 3);
+
+    sink.write(&backward(), Some(&blocks[0].point())).unwrap();
+    assert_eq!(files(&path, "cbor"), vec![blocks[0].block_cbor.clone()]);
+    assert_eq!(sink.last(), &blocks[0].point());
+
+    // Too deep for the positions kept
+    let unknown = PallasPoint::Specific(1, vec![1; 32]);
+    assert!(sink.write(&backward(), Some(&unknown)).is_err());
+}
+
+#[test]
+fn raw_rollbacks_to_unwritten_blocks_keep_the_blocks_before() {
+    let blocks = fixtures::chain(3);
+    let path = empty_directory("raw-filtered");
+    let mut sink = FileSink::open(&sink_options(&path, SINK_RAW_CBOR, 0)).unwrap();
+
+    // Block 1 was dropped by a tx filter
+    sink.write(&forward(&blocks[0]), Some(&blocks[0].point())).unwrap();
+    sink.write(&forward(&blocks[2]), Some(&blocks[2].point())).unwrap();
+
+    sink.write(&backward(), Some(&blocks[1].point())).unwrap();
+    assert_eq!(files(&path, "cbor"), vec![blocks[0].block_cbor.clone()]);
+    assert_eq!(sink.last(), &blocks[1].point());
+
+    sink.write(&forward(&blocks[2]), Some(&blocks[2].point())).unwrap();
+    let mut expected = blocks[0].block_cbor.clone();
+    expected.extend_from_slice(&blocks[2].block_cbor);
+    assert_eq!(files(&path, "cbor"), vec![expected]);
+}
+
+#[test]
+fn ndjson_records_forwards_and_rollbacks() {
+    let blocks = fixtures::chain(2);
+    let path = empty_directory("ndjson");
+    let mut sink = FileSink::open(&sink_options(&path, SINK_NDJSON, 0)).unwrap();
+
+    // The rollback to origin a new chain-sync starts with is not recorded
+    sink.write(&backward(), Some(&PallasPoint::Origin)).unwrap();
+
+    for block in &blocks {
+        sink.write(&forward(block), Some(&block.point())).unwrap();
+    }
+
+    sink.write(&backward(), Some(&blocks[0].point())).unwrap();
+
+    let contents = String::from_utf8(files(&path, "ndjson").concat()).unwrap();
+    let records: Vec<Value> = contents.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+
+    assert_eq!(records.len(), 3);
+    assert_eq!(records[1]["action"], "roll_forward");
+    assert_eq!(records[1]["block"], hex::encode(&blocks[1].block_cbor));
+    assert_eq!(records[2]["action"], "roll_back");
+    assert_eq!(records[2]["point"]["hash"], hex::encode(&blocks[0].hash));
+
+    // Reopening resumes after the rollback
+    drop(sink);
+    let sink = FileSink::open(&sink_options(&path, SINK_NDJSON, 0)).unwrap();
+    assert_eq!(sink.resume_points()[0], blocks[0].point());
+}
+
+#[test]
+fn forwards_are_checkpointed_in_batches() {
+    let blocks = fixtures::chain(3);
+    let path = empty_directory("batches");
+    let mut sink = FileSink::open(&sink_options(&path, SINK_RAW_CBOR, 0)).unwrap();
+
+    sink.write(&forward(&blocks[0]), Some(&blocks[0].point())).unwrap();
+    sink.checkpoint().unwrap();
+
+    for block in &blocks[1..] {
+        sink.write(&forward(block), Some(&block.point())).unwrap();
+    }
+
+    // A crash before the next checkpoint cuts the files back to the last one
+    drop(sink);
+    let sink = FileSink::open(&sink_options(&path, SINK_RAW_CBOR, 0)).unwrap();
+
+    assert_eq!(sink.resume_points()[0], blocks[0].point());
+    assert_eq!(files(&path, "cbor"), vec![blocks[0].block_cbor.clone()]);
+}
+
+#[test]
+fn restarted_sink_resumes_from_its_checkpoint() {
+    let _runtime = shared_runtime();
+    let path = empty_directory("resume");
+
+    let run = |count: u64| {
+        let node = MockNode::n2c(MockChain::new(count));
+        let client = connect(&node, ClientKind::N2C, options());
+
+        assert!(ClientWrapper::start_sink(client.clone(), sink_options(&path, SINK_RAW_CBOR, 0)));
+
+        let tip = node.chain.blocks.last().unwrap().hash.clone();
+        let status = eventually(Duration::from_secs(5), || {
+            let status = ClientWrapper::get_sink_status(client.clone());
+            status.last.as_ref().is_some_and(|last| last.hash == tip).then_some(status)
+        });
+        assert!(status.is_some_and(|status| status.running && status.error.is_empty()));
+
+        ClientWrapper::stop_sink(client.clone());
+        ClientWrapper::disconnect(client);
+
+        node.chain.blocks.clone()
+    };
+
+    run(2);
+    let blocks = run(4);
+
+    // Blocks 0 and 1 are not written again
+    let expected: Vec<u8> = blocks.iter().flat_map(|block| block.block_cbor.clone()).collect();
+    assert_eq!(files(&path, "cbor").concat(), expected);
+}
@@ -0,0 +1,128 @@
+use std::{fs::OpenOptions, io::Write, path::{Path, PathBuf}};
+
+use crate::{mock::fixtures, store::BlockStoreWrapper, Point};
+
+fn empty_store(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("pallas-dotnet-store-{}-{}", std::process::id(), name));
+    let _ = std::fs::remove_dir_all(&path);
+
+    path
+}
+
+fn open(path: &Path) -> BlockStoreWrapper {
+    BlockStoreWrapper::open_block_store(path.to_string_lossy().to_string()).expect("cannot open the block store")
+}
+
+fn origin() -> Point {
+    Point { slot: 0, hash: vec![] }
+}
+
+#[test]
+fn blocks_are_found_by_point_number_and_range() {
+    let blocks = fixtures::chain(4);
+    let store = open(&empty_store("lookup"));
+
+    for block in &blocks {
+        assert!(BlockStoreWrapper::put_block(store.clone(), block.block_cbor.clone()));
+    }
+
+    let tip = BlockStoreWrapper::get_block_store_tip(store.clone());
+    assert_eq!((tip.slot, tip.hash), (blocks[3].slot, blocks[3].hash.clone()));
+
+    let block = BlockStoreWrapper::get_block(store.clone(), blocks[1].point().into());
+    assert_eq!(block, Some(blocks[1].block_cbor.clone()));
+
+    let block = BlockStoreWrapper::get_block_by_number(store.clone(), blocks[2].number);
+    assert_eq!(block, Some(blocks[2].block_cbor.clone()));
+
+    // Same slot, different hash
+    let unknown = Point {
+        slot: blocks[1].slot,
+        hash: vec![0; 32],
+    };
+    assert_eq!(BlockStoreWrapper::get_block(store.clone(), unknown), None);
+
+    let range = BlockStoreWrapper::get_block_range(store.clone(), blocks[1].point().into(), blocks[2].point().into());
+    assert_eq!(range, vec![blocks[1].block_cbor.clone(), blocks[2].block_cbor.clone()]);
+
+    // Paging from origin walks the whole store
+    let page = BlockStoreWrapper::get_blocks_after(store.clone(), origin(), 3);
+    assert_eq!(page.blocks.len(), 3);
+    assert_eq!(page.last.hash, blocks[2].hash);
+    let page = BlockStoreWrapper::get_blocks_after(store.clone(), page.last, 3);
+    assert_eq!(page.blocks, vec![blocks[3].block_cbor.clone()]);
+
+    BlockStoreWrapper::close_block_store(store);
+}
+
+#[test]
+fn blocks_that_do_not_extend_the_tip_are_rejected() {
+    let blocks = fixtures::chain(2);
+    let store = open(&empty_store("extend"));
+
+    assert!(BlockStoreWrapper::put_block(store.clone(), blocks[1].block_cbor.clone()));
+    assert!(!BlockStoreWrapper::put_block(store.clone(), blocks[0].block_cbor.clone()));
+    assert!(!BlockStoreWrapper::put_block(store.clone(), blocks[1].block_cbor.clone()));
+    assert!(!BlockStoreWrapper::put_block(store.clone(), vec![0xff]));
+
+    BlockStoreWrapper::close_block_store(store);
+}
+
+#[test]
+fn rollback_drops_the_following_blocks() {
+    let blocks = fixtures::chain(3);
+    let store = open(&empty_store("rollback"));
+
+    for block in &blocks {
+        assert!(BlockStoreWrapper::put_block(store.clone(), block.block_cbor.clone()));
+    }
+
+    assert!(BlockStoreWrapper::rollback_to(store.clone(), blocks[0].point().into()));
+    assert_eq!(BlockStoreWrapper::get_block(store.clone(), blocks[1].point().into()), None);
+    assert_eq!(BlockStoreWrapper::get_block_store_tip(store.clone()).hash, blocks[0].hash);
+
+    // The dropped blocks can be stored again
+    assert!(BlockStoreWrapper::put_block(store.clone(), blocks[1].block_cbor.clone()));
+
+    // Unknown points are refused, origin empties the store
+    assert!(!BlockStoreWrapper::rollback_to(store.clone(), blocks[2].point().into()));
+    assert!(BlockStoreWrapper::rollback_to(store.clone(), origin()));
+    assert!(BlockStoreWrapper::get_block_store_tip(store.clone()).hash.is_empty());
+
+    BlockStoreWrapper::close_block_store(store);
+}
+
+#[test]
+fn blocks_survive_reopening() {
+    let blocks = fixtures::chain(3);
+    let path = empty_store("reopen");
+    let store = open(&path);
+
+    for block in &blocks {
+        assert!(BlockStoreWrapper::put_block(store.clone(), block.block_cbor.clone()));
+    }
+
+    BlockStoreWrapper::close_block_store(store);
+
+    // A block cut short by a crash is dropped on open
+    let mut data = OpenOptions::new().append(true).open(path.join("blocks.dat")).unwrap();
+    data.write_all(&blocks[0].block_cbor[..10]).unwrap();
+    let mut index = OpenOptions::new().append(true).open(path.join("blocks.idx")).unwrap();
+    index.write_all(&[0; 20]).unwrap();
+
+    let store = open(&path);
+
+    assert_eq!(BlockStoreWrapper::get_block_store_tip(store.clone()).hash, blocks[2].hash);
+    assert_eq!(BlockStoreWrapper::get_block_by_number(store.clone(), 1), Some(blocks[0].block_cbor.clone()));
+    assert_eq!(std::fs::metadata(path.join("blocks.idx")).unwrap().len(), 3 * 60);
+
+    BlockStoreWrapper::close_block_store(store);
+}
+
+#[test]
+fn opening_a_file_as_a_store_fails() {
+    let path = empty_store("file");
+    std::fs::write(&path, b"not a directory").unwrap();
+
+    assert!(BlockStoreWrapper::open_block_store(path.to_string_lossy().to_string()).is_none());
+}
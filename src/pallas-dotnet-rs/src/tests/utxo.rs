@@ -0,0 +1,246 @@
+use pallas::ledger::addresses::Address;
+
+use super::{connect, next, options, shared_runtime};
+use crate::{
+    bearer::ClientKind,
+    mock::{
+        fixtures::{self, MockBlock, MockTx},
+        MockChain, MockNode, Step,
+    },
+    utxo::{UtxoIndexerWrapper, UtxoRef},
+    ClientWrapper, Point, ACTION_ROLL_BACK,
+};
+
+const POLICY: [u8; 28] = [7; 28];
+
+struct Scenario {
+    blocks: Vec<MockBlock>,
+    a: Vec<u8>,
+    b: Vec<u8>,
+    c: Vec<u8>,
+}
+
+// Block 1: A pays key 1 and key 2, the latter with a token.
+// Block 2: B spends A#0 to key 2 and key 1. C spends B#0 with B#1 as
+// collateral but fails phase-2, so only B#1 is consumed and C#1, the
+// collateral return to key 1, is created.
+fn scenario() -> Scenario {
+    let a = MockTx {
+        inputs: vec![(vec![0; 32], 0)],
+        outputs: vec![
+            fixtures::output(&fixtures::key_address(1), 5_000_000, &[]),
+            fixtures::output(&fixtures::key_address(2), 2_000_000, &[(&POLICY, b"tok")]),
+        ],
+        ..MockTx::default()
+    };
+
+    let b = MockTx {
+        inputs: vec![(a.hash(), 0)],
+        outputs: vec![
+            fixtures::output(&fixtures::key_address(2), 3_000_000, &[]),
+            fixtures::output(&fixtures::key_address(1), 1_800_000, &[]),
+        ],
+        ..MockTx::default()
+    };
+
+    let c = MockTx {
+        inputs: vec![(b.hash(), 0)],
+        outputs: vec![fixtures::output(&fixtures::key_address(3), 2_800_000, &[])],
+        collateral: vec![(b.hash(), 1)],
+        collateral_return: Some(fixtures::output(&fixtures::key_address(1), 1_500_000, &[])),
+        invalid: true,
+        ..MockTx::default()
+    };
+
+    Scenario {
+        blocks: fixtures::chain_with(vec![vec![a.clone()], vec![b.clone(), c.clone()]]),
+        a: a.hash(),
+        b: b.hash(),
+        c: c.hash(),
+    }
+}
+
+fn tip() -> Point {
+    Point { slot: 0, hash: vec![] }
+}
+
+fn bech32(key: u8) -> String {
+    Address::from_bytes(&fixtures::key_address(key)).unwrap().to_bech32().unwrap()
+}
+
+fn refs(utxos: Vec<crate::validation::ResolvedInput>) -> Vec<(Vec<u8>, u64)> {
+    utxos.into_iter().map(|utxo| (utxo.tx_hash, utxo.index)).collect()
+}
+
+fn sorted(mut refs: Vec<(Vec<u8>, u64)>) -> Vec<(Vec<u8>, u64)> {
+    refs.sort();
+    refs
+}
+
+#[test]
+fn blocks_spend_inputs_and_create_outputs() {
+    let s = scenario();
+    let indexer = UtxoIndexerWrapper::create_utxo_indexer(0);
+
+    for block in &s.blocks {
+        assert!(UtxoIndexerWrapper::utxo_indexer_roll_forward(indexer.clone(), block.block_cbor.clone()));
+    }
+
+    let by_address = UtxoIndexerWrapper::get_indexed_utxos_by_address(indexer.clone(), bech32(2), tip());
+    assert_eq!(refs(by_address), sorted(vec![(s.a.clone(), 1), (s.b.clone(), 0)]));
+
+    // B#1 went to collateral, C#0 was never created
+    let by_payment = UtxoIndexerWrapper::get_indexed_utxos_by_payment_credential(indexer.clone(), vec![1; 28], tip());
+    assert_eq!(refs(by_payment), vec![(s.c.clone(), 1)]);
+    let by_payment = UtxoIndexerWrapper::get_indexed_utxos_by_payment_credential(indexer.clone(), vec![3; 28], tip());
+    assert!(by_payment.is_empty());
+
+    let by_policy = UtxoIndexerWrapper::get_indexed_utxos_by_policy(indexer.clone(), POLICY.to_vec(), tip());
+    assert_eq!(refs(by_policy), vec![(s.a.clone(), 1)]);
+    let by_asset = UtxoIndexerWrapper::get_indexed_utxos_by_asset(indexer.clone(), POLICY.to_vec(), b"tok".to_vec(), tip());
+    assert_eq!(by_asset[0].output_cbor, fixtures::output(&fixtures::key_address(2), 2_000_000, &[(&POLICY, b"tok")]));
+    let by_asset = UtxoIndexerWrapper::get_indexed_utxos_by_asset(indexer.clone(), POLICY.to_vec(), b"other".to_vec(), tip());
+    assert!(by_asset.is_empty());
+
+    let by_refs = UtxoIndexerWrapper::get_indexed_utxos_by_refs(
+        indexer.clone(),
+        vec![
+            UtxoRef { tx_hash: s.a.clone(), index: 0 },
+            UtxoRef { tx_hash: s.a.clone(), index: 1 },
+        ],
+        tip(),
+    );
+    assert_eq!(refs(by_refs), vec![(s.a.clone(), 1)]);
+
+    // Blocks have to extend the tip
+    assert!(!UtxoIndexerWrapper::utxo_indexer_roll_forward(indexer.clone(), s.blocks[1].block_cbor.clone()));
+
+    UtxoIndexerWrapper::close_utxo_indexer(indexer);
+}
+
+#[test]
+fn recent_points_can_be_queried_and_rolled_back_to() {
+    let s = scenario();
+    let indexer = UtxoIndexerWrapper::create_utxo_indexer(0);
+
+    for block in &s.blocks {
+        assert!(UtxoIndexerWrapper::utxo_indexer_roll_forward(indexer.clone(), block.block_cbor.clone()));
+    }
+
+    let at_first: Point = s.blocks[0].point().into();
+    let by_payment = UtxoIndexerWrapper::get_indexed_utxos_by_payment_credential(indexer.clone(), vec![1; 28], at_first.clone());
+    assert_eq!(refs(by_payment), vec![(s.a.clone(), 0)]);
+
+    // The query did not touch the current set
+    let by_payment = UtxoIndexerWrapper::get_indexed_utxos_by_payment_credential(indexer.clone(), vec![1; 28], tip());
+    assert_eq!(refs(by_payment), vec![(s.c.clone(), 1)]);
+
+    assert!(UtxoIndexerWrapper::utxo_indexer_roll_backward(indexer.clone(), at_first.clone()));
+    assert_eq!(UtxoIndexerWrapper::get_utxo_indexer_tip(indexer.clone()).hash, s.blocks[0].hash);
+
+    let by_address = UtxoIndexerWrapper::get_indexed_utxos_by_address(indexer.clone(), bech32(2), tip());
+    assert_eq!(refs(by_address), vec![(s.a.clone(), 1)]);
+
+    // Origin empties the set
+    assert!(UtxoIndexerWrapper::utxo_indexer_roll_backward(indexer.clone(), Point { slot: 0, hash: vec![] }));
+    assert!(UtxoIndexerWrapper::get_indexed_utxos_by_address(indexer.clone(), bech32(2), tip()).is_empty());
+
+    UtxoIndexerWrapper::close_utxo_indexer(indexer);
+}
+
+#[test]
+fn rollbacks_past_the_window_are_refused() {
+    let blocks = fixtures::chain(3);
+    let indexer = UtxoIndexerWrapper::create_utxo_indexer(1);
+
+    for block in &blocks {
+        assert!(UtxoIndexerWrapper::utxo_indexer_roll_forward(indexer.clone(), block.block_cbor.clone()));
+    }
+
+    let first: Point = blocks[0].point().into();
+    assert!(UtxoIndexerWrapper::get_indexed_utxos_by_address(indexer.clone(), bech32(2), first.clone()).is_empty());
+    assert!(!UtxoIndexerWrapper::utxo_indexer_roll_backward(indexer.clone(), first));
+    assert!(UtxoIndexerWrapper::utxo_indexer_roll_backward(indexer.clone(), blocks[1].point().into()));
+
+    UtxoIndexerWrapper::close_utxo_indexer(indexer);
+}
+
+#[test]
+fn attached_indexer_follows_chain_sync() {
+    let _runtime = shared_runtime();
+    let s = scenario();
+
+    let mut chain = MockChain::scripted(2, vec![Step::Forward(0), Step::Forward(1), Step::Backward(Some(0))]);
+    chain.blocks = s.blocks.clone();
+
+    let node = MockNode::n2c(chain);
+    let client = connect(&node, ClientKind::N2C, options());
+    let indexer = UtxoIndexerWrapper::create_utxo_indexer(0);
+
+    ClientWrapper::attach_utxo_indexer(client.clone(), indexer.clone());
+
+    next(&client);
+    next(&client);
+    assert_eq!(UtxoIndexerWrapper::get_utxo_indexer_tip(indexer.clone()).hash, s.blocks[1].hash);
+
+    assert_eq!(next(&client).action, ACTION_ROLL_BACK);
+    assert_eq!(UtxoIndexerWrapper::get_utxo_indexer_tip(indexer.clone()).hash, s.blocks[0].hash);
+
+    let by_address = UtxoIndexerWrapper::get_indexed_utxos_by_address(indexer.clone(), bech32(1), tip());
+    assert_eq!(refs(by_address), vec![(s.a.clone(), 0)]);
+
+    ClientWrapper::disconnect(client);
+    UtxoIndexerWrapper::close_utxo_indexer(indexer);
+}
+
+#[test]
+fn detached_indexer_stops_following() {
+    let _runtime = shared_runtime();
+    let s = scenario();
+
+    let mut chain = MockChain::scripted(2, vec![Step::Forward(0), Step::Forward(1)]);
+    chain.blocks = s.blocks.clone();
+
+    let node = MockNode::n2c(chain);
+    let client = connect(&node, ClientKind::N2C, options());
+    let indexer = UtxoIndexerWrapper::create_utxo_indexer(0);
+
+    ClientWrapper::attach_utxo_indexer(client.clone(), indexer.clone());
+    next(&client);
+    ClientWrapper::detach_utxo_indexer(client.clone());
+    next(&client);
+
+    assert_eq!(UtxoIndexerWrapper::get_utxo_indexer_tip(indexer.clone()).hash, s.blocks[0].hash);
+
+    // The indexer outlives the client it was attached to
+    ClientWrapper::disconnect(client);
+    let by_address = UtxoIndexerWrapper::get_indexed_utxos_by_address(indexer.clone(), bech32(1), tip());
+    assert_eq!(refs(by_address), vec![(s.a.clone(), 0)]);
+
+    UtxoIndexerWrapper::close_utxo_indexer(indexer);
+}
+
+#[test]
+fn responses_the_attached_indexer_cannot_apply_are_reported() {
+    let _runtime = shared_runtime();
+    let blocks = fixtures::chain(2);
+    let node = MockNode::n2c(MockChain::new(2));
+    let client = connect(&node, ClientKind::N2C, options());
+    let indexer = UtxoIndexerWrapper::create_utxo_indexer(0);
+
+    assert!(UtxoIndexerWrapper::utxo_indexer_roll_forward(indexer.clone(), blocks[1].block_cbor.clone()));
+    assert!(UtxoIndexerWrapper::get_utxo_indexer_error(indexer.clone()).is_none());
+
+    // The first block does not extend the indexer's tip
+    ClientWrapper::attach_utxo_indexer(client.clone(), indexer.clone());
+    next(&client);
+
+    let error = UtxoIndexerWrapper::get_utxo_indexer_error(indexer.clone()).expect("no error reported");
+    assert!(error.contains("does not extend the tip"));
+
+    assert!(UtxoIndexerWrapper::utxo_indexer_roll_backward(indexer.clone(), Point { slot: 0, hash: vec![] }));
+    assert!(UtxoIndexerWrapper::get_utxo_indexer_error(indexer.clone()).is_none());
+
+    ClientWrapper::disconnect(client);
+    UtxoIndexerWrapper::close_utxo_indexer(indexer);
+}
@@ -0,0 +1,548 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    sync::{Arc, Mutex},
+};
+
+use pallas::{
+    crypto::hash::Hash,
+    ledger::{
+        addresses::{Address, ByronAddress},
+        traverse::{MultiEraBlock, MultiEraInput, MultiEraOutput, MultiEraTx},
+    },
+    network::miniprotocols::Point as PallasPoint,
+};
+use rnet::{net, Net};
+
+use crate::{extends_tip, is_epoch_boundary, validation::ResolvedInput, ClientWrapper, NextResponse, Point, ACTION_ROLL_BACK, ACTION_ROLL_FORWARD};
+
+// Security parameter k of mainnet, no rollback goes deeper
+const DEFAULT_MAX_ROLLBACK: usize = 2160;
+
+type TxIn = (Hash<32>, u64);
+
+#[derive(Clone)]
+pub struct IndexedOutput {
+    output_cbor: Vec<u8>,
+    address: Vec<u8>,
+    // Key or script hash, Byron and stake addresses have none
+    payment: Option<Vec<u8>>,
+    // (policy id, asset name)
+    assets: Vec<(Vec<u8>, Vec<u8>)>,
+}
+
+impl IndexedOutput {
+    fn new(output: &MultiEraOutput) -> IndexedOutput {
+        let address = output.address().ok();
+
+        let payment = match &address {
+            Some(Address::Shelley(address)) => Some(address.payment().as_hash().to_vec()),
+            _ => None,
+        };
+
+        let assets = output
+            .non_ada_assets()
+            .iter()
+            .flat_map(|policy| {
+                policy
+                    .assets()
+                    .iter()
+                    .map(|asset| (policy.policy().to_vec(), asset.name().to_vec()))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+
+        IndexedOutput {
+            output_cbor: output.encode(),
+            address: address.map(|address| address.to_vec()).unwrap_or_default(),
+            payment,
+            assets,
+        }
+    }
+}
+
+pub enum UtxoQuery {
+    Address(Vec<u8>),
+    PaymentCredential(Vec<u8>),
+    Policy(Vec<u8>),
+    Asset(Vec<u8>, Vec<u8>),
+    TxIns(HashSet<TxIn>),
+}
+
+impl UtxoQuery {
+    fn matches(&self, tx_in: &TxIn, output: &IndexedOutput) -> bool {
+        match self {
+            UtxoQuery::Address(address) => output.address == *address,
+            UtxoQuery::PaymentCredential(credential) => output.payment.as_ref() == Some(credential),
+            UtxoQuery::Policy(policy) => output.assets.iter().any(|(p, _)| p == policy),
+            UtxoQuery::Asset(policy, name) => output.assets.iter().any(|(p, n)| p == policy && n == name),
+            UtxoQuery::TxIns(tx_ins) => tx_ins.contains(tx_in),
+        }
+    }
+}
+
+// Inputs consumed and (index, output) produced by a tx. A tx failing phase-2
+// validation only consumes its collateral, its collateral return takes the
+// index following the outputs.
+pub fn ledger_effects<'a>(tx: &'a MultiEraTx<'_>) -> (Vec<MultiEraInput<'a>>, Vec<(usize, MultiEraOutput<'a>)>) {
+    match tx.is_valid() {
+        true => (tx.inputs(), tx.outputs().into_iter().enumerate().collect()),
+        false => (
+            tx.collateral(),
+            tx.collateral_return().into_iter().map(|output| (tx.outputs().len(), output)).collect(),
+        ),
+    }
+}
+
+// What a block changed, to revert it on rollback
+struct BlockUndo {
+    // Tip before the block was applied
+    previous: PallasPoint,
+    previous_is_boundary: bool,
+    spent: Vec<(TxIn, IndexedOutput)>,
+    created: Vec<TxIn>,
+}
+
+fn unindex(index: &mut HashMap<Vec<u8>, HashSet<TxIn>>, key: &[u8], tx_in: &TxIn) {
+    if let Some(tx_ins) = index.get_mut(key) {
+        tx_ins.remove(tx_in);
+
+        if tx_ins.is_empty() {
+            index.remove(key);
+        }
+    }
+}
+
+// Unspent outputs of the blocks applied so far. Outputs created before the
+// first applied block are unknown, so a complete set has to be built from
+// origin. The changes of the last `max_rollback` blocks are kept to roll
+// back and to answer queries at those points.
+pub struct UtxoIndex {
+    utxos: HashMap<TxIn, IndexedOutput>,
+    by_address: HashMap<Vec<u8>, HashSet<TxIn>>,
+    by_payment: HashMap<Vec<u8>, HashSet<TxIn>>,
+    by_policy: HashMap<Vec<u8>, HashSet<TxIn>>,
+    // Oldest first
+    undo: VecDeque<BlockUndo>,
+    max_rollback: usize,
+    tip: PallasPoint,
+    tip_is_boundary: bool,
+    // Why the first followed response that could not be applied failed. The
+    // set may be off until the next rollback.
+    error: Option<String>,
+}
+
+impl UtxoIndex {
+    pub fn new(max_rollback: usize) -> UtxoIndex {
+        UtxoIndex {
+            utxos: HashMap::new(),
+            by_address: HashMap::new(),
+            by_payment: HashMap::new(),
+            by_policy: HashMap::new(),
+            undo: VecDeque::new(),
+            max_rollback,
+            tip: PallasPoint::Origin,
+            tip_is_boundary: false,
+            error: None,
+        }
+    }
+
+    pub fn tip(&self) -> PallasPoint {
+        self.tip.clone()
+    }
+
+    pub fn error(&self) -> Option<String> {
+        self.error.clone()
+    }
+
+    fn insert(&mut self, tx_in: TxIn, output: IndexedOutput) {
+        self.by_address.entry(output.address.clone()).or_default().insert(tx_in);
+
+        if let Some(payment) = &output.payment {
+            self.by_payment.entry(payment.clone()).or_default().insert(tx_in);
+        }
+
+        for (policy, _) in &output.assets {
+            self.by_policy.entry(policy.clone()).or_default().insert(tx_in);
+        }
+
+        self.utxos.insert(tx_in, output);
+    }
+
+    fn remove(&mut self, tx_in: &TxIn) -> Option<IndexedOutput> {
+        let output = self.utxos.remove(tx_in)?;
+
+        unindex(&mut self.by_address, &output.address, tx_in);
+
+        if let Some(payment) = &output.payment {
+            unindex(&mut self.by_payment, payment, tx_in);
+        }
+
+        for (policy, _) in &output.assets {
+            unindex(&mut self.by_policy, policy, tx_in);
+        }
+
+        Some(output)
+    }
+
+    // Blocks are expected in the `[era, block]` envelope and have to extend
+    // the tip.
+    pub fn roll_forward(&mut self, block_cbor: &[u8]) -> Result<(), String> {
+        let block = MultiEraBlock::decode(block_cbor).map_err(|e| format!("invalid block cbor: {:?}", e))?;
+
+        if let PallasPoint::Specific(slot, _) = &self.tip {
+            if !extends_tip(block.slot(), is_epoch_boundary(&block), *slot, self.tip_is_boundary) {
+                return Err(format!("block at slot {} does not extend the tip at slot {}", block.slot(), slot));
+            }
+        }
+
+        let mut undo = BlockUndo {
+            previous: self.tip.clone(),
+            previous_is_boundary: self.tip_is_boundary,
+            spent: vec![],
+            created: vec![],
+        };
+
+        for tx in block.txs() {
+            let hash = tx.hash();
+            let (consumed, produced) = ledger_effects(&tx);
+
+            for input in consumed {
+                let tx_in = (*input.hash(), input.index());
+
+                if let Some(output) = self.remove(&tx_in) {
+                    undo.spent.push((tx_in, output));
+                }
+            }
+
+            for (index, output) in produced {
+                let tx_in = (hash, index as u64);
+
+                self.insert(tx_in, IndexedOutput::new(&output));
+                undo.created.push(tx_in);
+            }
+        }
+
+        self.tip = PallasPoint::Specific(block.slot(), block.hash().to_vec());
+        self.tip_is_boundary = is_epoch_boundary(&block);
+        self.undo.push_back(undo);
+
+        while self.undo.len() > self.max_rollback {
+            self.undo.pop_front();
+        }
+
+        Ok(())
+    }
+
+    // Blocks to revert to get back to `point`, `None` past the rollback window
+    fn depth(&self, point: &PallasPoint) -> Option<usize> {
+        if *point == self.tip {
+            return Some(0);
+        }
+
+        self.undo.iter().rev().position(|undo| undo.previous == *point).map(|i| i + 1)
+    }
+
+    pub fn roll_backward(&mut self, point: &PallasPoint) -> Result<(), String> {
+        let depth = self.depth(point).ok_or_else(|| format!("{:?} is past the rollback window", point))?;
+
+        for _ in 0..depth {
+            let undo = self.undo.pop_back().unwrap();
+
+            // Restored first, an output created and spent by the same block
+            // is then removed again
+            for (tx_in, output) in undo.spent {
+                self.insert(tx_in, output);
+            }
+
+            for tx_in in undo.created {
+                self.remove(&tx_in);
+            }
+
+            self.tip = undo.previous;
+            self.tip_is_boundary = undo.previous_is_boundary;
+        }
+
+        self.error = None;
+
+        Ok(())
+    }
+
+    // Chain-sync continues after an intersection, so the blocks past it are
+    // reverted. An intersection ahead of the tip is where indexing starts.
+    pub fn intersect(&mut self, point: &PallasPoint) {
+        let behind = match (point, &self.tip) {
+            (_, PallasPoint::Origin) => false,
+            (PallasPoint::Origin, _) => true,
+            (PallasPoint::Specific(slot, _), PallasPoint::Specific(tip, _)) => slot <= tip,
+        };
+
+        if behind {
+            if let Err(e) = self.roll_backward(point) {
+                self.error.get_or_insert(e);
+            }
+        }
+    }
+
+    // Applies a response delivered to the consumer, `point` being where it
+    // is after the response
+    pub fn follow(&mut self, response: &NextResponse, point: Option<&PallasPoint>) {
+        let result = match (response.action, &response.block_cbor, point) {
+            (ACTION_ROLL_FORWARD, Some(block_cbor), _) => self.roll_forward(block_cbor),
+            (ACTION_ROLL_BACK, _, Some(point)) => self.roll_backward(point),
+            _ => Ok(()),
+        };
+
+        if let Err(e) = result {
+            self.error.get_or_insert(e);
+        }
+    }
+
+    // Outputs matching `query` at the tip or at a point of the rollback
+    // window, sorted by tx hash and index
+    pub fn query(&self, query: &UtxoQuery, at: Option<&PallasPoint>) -> Option<Vec<(TxIn, IndexedOutput)>> {
+        let depth = match at {
+            Some(point) => self.depth(point)?,
+            None => 0,
+        };
+
+        let candidates: Vec<TxIn> = match query {
+            UtxoQuery::Address(address) => self.by_address.get(address).into_iter().flatten().cloned().collect(),
+            UtxoQuery::PaymentCredential(credential) => self.by_payment.get(credential).into_iter().flatten().cloned().collect(),
+            UtxoQuery::Policy(policy) | UtxoQuery::Asset(policy, _) => self.by_policy.get(policy).into_iter().flatten().cloned().collect(),
+            UtxoQuery::TxIns(tx_ins) => tx_ins.iter().cloned().collect(),
+        };
+
+        let mut utxos: HashMap<TxIn, IndexedOutput> = candidates
+            .into_iter()
+            .filter_map(|tx_in| self.utxos.get(&tx_in).map(|output| (tx_in, output)))
+            .filter(|(tx_in, output)| query.matches(tx_in, output))
+            .map(|(tx_in, output)| (tx_in, output.clone()))
+            .collect();
+
+        // Reverts the newest blocks on the result only, same order as a rollback
+        for undo in self.undo.iter().rev().take(depth) {
+            for (tx_in, output) in &undo.spent {
+                if query.matches(tx_in, output) {
+                    utxos.insert(*tx_in, output.clone());
+                }
+            }
+
+            for tx_in in &undo.created {
+                utxos.remove(tx_in);
+            }
+        }
+
+        let mut utxos: Vec<(TxIn, IndexedOutput)> = utxos.into_iter().collect();
+        utxos.sort_by_key(|(tx_in, _)| *tx_in);
+
+        Some(utxos)
+    }
+}
+
+#[derive(Net)]
+pub struct UtxoRef {
+    pub(crate) tx_hash: Vec<u8>,
+    pub(crate) index: u64,
+}
+
+#[derive(Net, Clone)]
+pub struct UtxoIndexerWrapper {
+    indexer_ptr: usize,
+}
+
+impl UtxoIndexerWrapper {
+    pub fn indexer(&self) -> &Arc<Mutex<UtxoIndex>> {
+        unsafe { &*(self.indexer_ptr as *const Arc<Mutex<UtxoIndex>>) }
+    }
+
+    // An empty hash queries the tip
+    fn query(&self, name: &str, query: UtxoQuery, at: Point) -> Vec<ResolvedInput> {
+        let at: Option<PallasPoint> = match at.hash.is_empty() {
+            true => None,
+            false => Some(at.into()),
+        };
+
+        match self.indexer().lock().unwrap().query(&query, at.as_ref()) {
+            Some(utxos) => utxos
+                .into_iter()
+                .map(|((hash, index), output)| ResolvedInput {
+                    tx_hash: hash.to_vec(),
+                    index,
+                    output_cbor: output.output_cbor,
+                })
+                .collect(),
+            None => {
+                println!("{} error: point is past the rollback window", name);
+                vec![]
+            }
+        }
+    }
+
+    // Rollbacks deeper than `max_rollback` blocks are refused, 0 keeps k
+    // blocks
+    #[net]
+    pub fn create_utxo_indexer(max_rollback: u64) -> UtxoIndexerWrapper {
+        UtxoIndexerWrapper::create_utxo_indexer(max_rollback)
+    }
+
+    pub fn create_utxo_indexer(max_rollback: u64) -> UtxoIndexerWrapper {
+        let max_rollback = match max_rollback {
+            0 => DEFAULT_MAX_ROLLBACK,
+            max_rollback => max_rollback as usize,
+        };
+
+        UtxoIndexerWrapper {
+            indexer_ptr: Box::into_raw(Box::new(Arc::new(Mutex::new(UtxoIndex::new(max_rollback))))) as usize,
+        }
+    }
+
+    #[net]
+    pub fn utxo_indexer_roll_forward(indexer_wrapper: UtxoIndexerWrapper, block_cbor: Vec<u8>) -> bool {
+        UtxoIndexerWrapper::utxo_indexer_roll_forward(indexer_wrapper, block_cbor)
+    }
+
+    pub fn utxo_indexer_roll_forward(indexer_wrapper: UtxoIndexerWrapper, block_cbor: Vec<u8>) -> bool {
+        match indexer_wrapper.indexer().lock().unwrap().roll_forward(&block_cbor) {
+            Ok(()) => true,
+            Err(e) => {
+                println!("utxo_indexer_roll_forward error: {}", e);
+                false
+            }
+        }
+    }
+
+    // An empty hash rolls back to origin
+    #[net]
+    pub fn utxo_indexer_roll_backward(indexer_wrapper: UtxoIndexerWrapper, point: Point) -> bool {
+        UtxoIndexerWrapper::utxo_indexer_roll_backward(indexer_wrapper, point)
+    }
+
+    pub fn utxo_indexer_roll_backward(indexer_wrapper: UtxoIndexerWrapper, point: Point) -> bool {
+        match indexer_wrapper.indexer().lock().unwrap().roll_backward(&point.into()) {
+            Ok(()) => true,
+            Err(e) => {
+                println!("utxo_indexer_roll_backward error: {}", e);
+                false
+            }
+        }
+    }
+
+    // Set when a block or rollback delivered to the client the indexer is
+    // attached to could not be applied. Cleared by the next rollback.
+    #[net]
+    pub fn get_utxo_indexer_error(indexer_wrapper: UtxoIndexerWrapper) -> Option<String> {
+        UtxoIndexerWrapper::get_utxo_indexer_error(indexer_wrapper)
+    }
+
+    pub fn get_utxo_indexer_error(indexer_wrapper: UtxoIndexerWrapper) -> Option<String> {
+        indexer_wrapper.indexer().lock().unwrap().error()
+    }
+
+    #[net]
+    pub fn get_utxo_indexer_tip(indexer_wrapper: UtxoIndexerWrapper) -> Point {
+        UtxoIndexerWrapper::get_utxo_indexer_tip(indexer_wrapper)
+    }
+
+    pub fn get_utxo_indexer_tip(indexer_wrapper: UtxoIndexerWrapper) -> Point {
+        indexer_wrapper.indexer().lock().unwrap().tip().into()
+    }
+
+    // Bech32 or base58 (Byron) address
+    #[net]
+    pub fn get_indexed_utxos_by_address(indexer_wrapper: UtxoIndexerWrapper, address: String, at: Point) -> Vec<ResolvedInput> {
+        UtxoIndexerWrapper::get_indexed_utxos_by_address(indexer_wrapper, address, at)
+    }
+
+    pub fn get_indexed_utxos_by_address(indexer_wrapper: UtxoIndexerWrapper, address: String, at: Point) -> Vec<ResolvedInput> {
+        let address = match Address::from_bech32(&address).or_else(|_| ByronAddress::from_base58(&address).map(Address::Byron)) {
+            Ok(address) => address,
+            Err(e) => {
+                println!("get_indexed_utxos_by_address error: {:?}", e);
+                return vec![];
+            }
+        };
+
+        indexer_wrapper.query("get_indexed_utxos_by_address", UtxoQuery::Address(address.to_vec()), at)
+    }
+
+    // Key or script hash of the payment part, whatever the stake part is
+    #[net]
+    pub fn get_indexed_utxos_by_payment_credential(indexer_wrapper: UtxoIndexerWrapper, credential: Vec<u8>, at: Point) -> Vec<ResolvedInput> {
+        UtxoIndexerWrapper::get_indexed_utxos_by_payment_credential(indexer_wrapper, credential, at)
+    }
+
+    pub fn get_indexed_utxos_by_payment_credential(indexer_wrapper: UtxoIndexerWrapper, credential: Vec<u8>, at: Point) -> Vec<ResolvedInput> {
+        indexer_wrapper.query("get_indexed_utxos_by_payment_credential", UtxoQuery::PaymentCredential(credential), at)
+    }
+
+    // Outputs holding any asset of the policy
+    #[net]
+    pub fn get_indexed_utxos_by_policy(indexer_wrapper: UtxoIndexerWrapper, policy_id: Vec<u8>, at: Point) -> Vec<ResolvedInput> {
+        UtxoIndexerWrapper::get_indexed_utxos_by_policy(indexer_wrapper, policy_id, at)
+    }
+
+    pub fn get_indexed_utxos_by_policy(indexer_wrapper: UtxoIndexerWrapper, policy_id: Vec<u8>, at: Point) -> Vec<ResolvedInput> {
+        indexer_wrapper.query("get_indexed_utxos_by_policy", UtxoQuery::Policy(policy_id), at)
+    }
+
+    #[net]
+    pub fn get_indexed_utxos_by_asset(indexer_wrapper: UtxoIndexerWrapper, policy_id: Vec<u8>, asset_name: Vec<u8>, at: Point) -> Vec<ResolvedInput> {
+        UtxoIndexerWrapper::get_indexed_utxos_by_asset(indexer_wrapper, policy_id, asset_name, at)
+    }
+
+    pub fn get_indexed_utxos_by_asset(indexer_wrapper: UtxoIndexerWrapper, policy_id: Vec<u8>, asset_name: Vec<u8>, at: Point) -> Vec<ResolvedInput> {
+        indexer_wrapper.query("get_indexed_utxos_by_asset", UtxoQuery::Asset(policy_id, asset_name), at)
+    }
+
+    // Only the unspent ones are returned, ready to be passed to validate_tx
+    #[net]
+    pub fn get_indexed_utxos_by_refs(indexer_wrapper: UtxoIndexerWrapper, refs: Vec<UtxoRef>, at: Point) -> Vec<ResolvedInput> {
+        UtxoIndexerWrapper::get_indexed_utxos_by_refs(indexer_wrapper, refs, at)
+    }
+
+    pub fn get_indexed_utxos_by_refs(indexer_wrapper: UtxoIndexerWrapper, refs: Vec<UtxoRef>, at: Point) -> Vec<ResolvedInput> {
+        let tx_ins = refs
+            .into_iter()
+            .filter_map(|utxo_ref| {
+                let hash: [u8; 32] = utxo_ref.tx_hash.as_slice().try_into().ok()?;
+                Some((Hash::new(hash), utxo_ref.index))
+            })
+            .collect();
+
+        indexer_wrapper.query("get_indexed_utxos_by_refs", UtxoQuery::TxIns(tx_ins), at)
+    }
+
+    // An indexer attached to a client keeps its own reference, closing it
+    // only releases the caller's one
+    #[net]
+    pub fn close_utxo_indexer(indexer_wrapper: UtxoIndexerWrapper) {
+        UtxoIndexerWrapper::close_utxo_indexer(indexer_wrapper)
+    }
+
+    pub fn close_utxo_indexer(indexer_wrapper: UtxoIndexerWrapper) {
+        drop(unsafe { Box::from_raw(indexer_wrapper.indexer_ptr as *mut Arc<Mutex<UtxoIndex>>) });
+    }
+}
+
+impl ClientWrapper {
+    // From now on every response delivered by chain_sync_next or the
+    // chain-sync stream is applied to the indexer, rollbacks and
+    // intersections included
+    #[net]
+    pub fn attach_utxo_indexer(client_wrapper: ClientWrapper, indexer_wrapper: UtxoIndexerWrapper) {
+        ClientWrapper::attach_utxo_indexer(client_wrapper, indexer_wrapper)
+    }
+
+    pub fn attach_utxo_indexer(client_wrapper: ClientWrapper, indexer_wrapper: UtxoIndexerWrapper) {
+        client_wrapper.state().followers.lock().unwrap().indexer = Some(indexer_wrapper.indexer().clone());
+    }
+
+    #[net]
+    pub fn detach_utxo_indexer(client_wrapper: ClientWrapper) {
+        ClientWrapper::detach_utxo_indexer(client_wrapper)
+    }
+
+    pub fn detach_utxo_indexer(client_wrapper: ClientWrapper) {
+        client_wrapper.state().followers.lock().unwrap().indexer = None;
+    }
+}
@@ -0,0 +1,378 @@
+use std::{borrow::Cow, collections::HashMap};
+
+use pallas::{
+    codec::utils::KeyValuePairs,
+    crypto::hash::Hash,
+    ledger::{
+        primitives::{
+            alonzo::{Language, Nonce, NonceVariant, RationalNumber, TransactionInput},
+            babbage::{self, ExUnitPrices, ExUnits},
+        },
+        traverse::{Era, MultiEraInput, MultiEraOutput, MultiEraTx},
+    },
+    network::miniprotocols::MAINNET_MAGIC,
+};
+use pallas_applying::{
+    utils::{
+        AlonzoProtParams, BabbageProtParams, Environment, MultiEraProtocolParameters, UTxOs,
+        ValidationError,
+    },
+    validate,
+};
+use rnet::{net, Net};
+
+#[derive(Net)]
+pub struct ResolvedInput {
+    pub(crate) tx_hash: Vec<u8>,
+    pub(crate) index: u64,
+    pub(crate) output_cbor: Vec<u8>,
+}
+
+// Ledger eras whose rules validate_tx applies, numbered as the hard fork
+// combinator does. Pallas has no Conway rules yet.
+pub const ERA_ALONZO: u8 = 4;
+pub const ERA_BABBAGE: u8 = 5;
+
+// Messages for the rules the Alonzo and Babbage validators share,
+// keyed by the name pallas gives the violation
+const RULE_MESSAGES: &[(&str, &str)] = &[
+    ("TxInsEmpty", "the tx has no inputs"),
+    ("InputNotInUTxO", "an input is not among the resolved inputs"),
+    ("CollateralNotInUTxO", "a collateral input is not among the resolved inputs"),
+    ("ReferenceInputNotInUTxO", "a reference input is not among the resolved inputs"),
+    ("RefInputNotInUTxO", "a reference input is not among the resolved inputs"),
+    ("BlockPrecedesValInt", "the slot is before the tx validity interval"),
+    ("BlockExceedsValInt", "the slot is past the tx validity interval"),
+    ("ValIntUpperBoundMissing", "the tx runs scripts but its validity interval has no upper bound"),
+    ("FeeBelowMin", "the fee is below the minimum fee"),
+    ("CollateralMissing", "the tx runs scripts but has no collateral inputs"),
+    ("TooManyCollaterals", "the tx has more collateral inputs than allowed"),
+    ("CollateralNotVKeyLocked", "a collateral input is not locked by a key"),
+    ("CollateralMinLovelace", "the collateral is below the required percentage of the fee"),
+    ("NonLovelaceCollateral", "the collateral holds native assets"),
+    ("CollateralWrongAssets", "the collateral return does not balance the collateral assets"),
+    ("NegativeValue", "an output holds a negative value"),
+    ("PreservationOfValue", "the inputs do not balance the outputs, fee and deposits"),
+    ("MinLovelaceUnreached", "an output holds less than the minimum ada"),
+    ("MaxValSizeExceeded", "an output value is larger than the maximum value size"),
+    ("OutputWrongNetworkID", "an output address belongs to another network"),
+    ("TxWrongNetworkID", "the tx network id does not match the network"),
+    ("TxExUnitsExceeded", "the redeemers exceed the maximum tx execution units"),
+    ("MaxTxSizeExceeded", "the tx is larger than the maximum tx size"),
+    ("ReqSignerMissing", "a required signer did not sign the tx"),
+    ("ReqSignerWrongSig", "a required signer signature is invalid"),
+    ("VKWitnessMissing", "an input or certificate is missing its key witness"),
+    ("VKWrongSignature", "a key witness signature is invalid"),
+    ("ScriptWitnessMissing", "a script needed by the tx is not provided"),
+    ("UnneededNativeScript", "a native script is provided but not needed"),
+    ("UnneededPlutusScript", "a Plutus script is provided but not needed"),
+    ("UnneededPlutusV1Script", "a Plutus V1 script is provided but not needed"),
+    ("UnneededPlutusV2Script", "a Plutus V2 script is provided but not needed"),
+    ("RedeemerMissing", "a script purpose has no redeemer"),
+    ("UnneededRedeemer", "a redeemer has no script purpose"),
+    ("DatumMissing", "a datum needed by a script is not provided"),
+    ("UnneededDatum", "a datum is provided but not needed"),
+    ("ScriptIntegrityHash", "the script data hash does not match the redeemers, datums and cost models"),
+    ("MetadataHash", "the auxiliary data hash does not match the auxiliary data"),
+    ("MintingLacksPolicy", "a minted policy has no script"),
+    ("UnsupportedPlutusLanguage", "a script is in a Plutus version the era does not support"),
+    ("TxAndProtParamsDiffer", "the tx was not built for the era of the protocol parameters"),
+];
+
+#[derive(Net, Clone, Default)]
+pub struct ProtocolParameters {
+    // One of the ERA_* constants, selects the rules the tx is checked against
+    pub(crate) era: u8,
+    pub(crate) minfee_a: u32,
+    pub(crate) minfee_b: u32,
+    pub(crate) max_block_body_size: u32,
+    pub(crate) max_transaction_size: u32,
+    pub(crate) max_block_header_size: u32,
+    pub(crate) key_deposit: u64,
+    pub(crate) pool_deposit: u64,
+    pub(crate) protocol_major: u64,
+    pub(crate) protocol_minor: u64,
+    pub(crate) min_pool_cost: u64,
+    pub(crate) ada_per_utxo_byte: u64,
+    pub(crate) max_value_size: u32,
+    pub(crate) collateral_percentage: u32,
+    pub(crate) max_collateral_inputs: u32,
+    pub(crate) max_tx_ex_mem: u64,
+    pub(crate) max_tx_ex_steps: u64,
+    pub(crate) max_block_ex_mem: u64,
+    pub(crate) max_block_ex_steps: u64,
+    pub(crate) mem_price_numerator: u64,
+    pub(crate) mem_price_denominator: u64,
+    pub(crate) step_price_numerator: u64,
+    pub(crate) step_price_denominator: u64,
+    pub(crate) plutus_v1_cost_model: Vec<i64>,
+    pub(crate) plutus_v2_cost_model: Vec<i64>,
+    pub(crate) plutus_v3_cost_model: Vec<i64>,
+}
+
+fn zero() -> RationalNumber {
+    RationalNumber { numerator: 0, denominator: 1 }
+}
+
+// Only the fields consulted by the phase-1 rules come from the caller, the
+// remaining ones are not used by tx validation.
+impl ProtocolParameters {
+    // The era txs are decoded for along with its parameters
+    pub fn to_multi_era(&self) -> Result<(Era, MultiEraProtocolParameters), String> {
+        match self.era {
+            ERA_ALONZO => Ok((Era::Alonzo, MultiEraProtocolParameters::Alonzo(self.to_alonzo()))),
+            ERA_BABBAGE => Ok((Era::Babbage, MultiEraProtocolParameters::Babbage(self.to_babbage()))),
+            era => Err(format!("era {} is not supported, only Alonzo and Babbage txs can be validated", era)),
+        }
+    }
+
+    fn cost_model(cost_model: &[i64]) -> Option<Vec<i64>> {
+        (!cost_model.is_empty()).then(|| cost_model.to_vec())
+    }
+
+    fn execution_costs(&self) -> ExUnitPrices {
+        ExUnitPrices {
+            mem_price: RationalNumber {
+                numerator: self.mem_price_numerator,
+                denominator: self.mem_price_denominator,
+            },
+            step_price: RationalNumber {
+                numerator: self.step_price_numerator,
+                denominator: self.step_price_denominator,
+            },
+        }
+    }
+
+    fn max_tx_ex_units(&self) -> ExUnits {
+        ExUnits {
+            mem: self.max_tx_ex_mem,
+            steps: self.max_tx_ex_steps,
+        }
+    }
+
+    fn max_block_ex_units(&self) -> ExUnits {
+        ExUnits {
+            mem: self.max_block_ex_mem,
+            steps: self.max_block_ex_steps,
+        }
+    }
+
+    fn to_alonzo(&self) -> AlonzoProtParams {
+        let cost_models = match ProtocolParameters::cost_model(&self.plutus_v1_cost_model) {
+            Some(cost_model) => vec![(Language::PlutusV1, cost_model)],
+            None => vec![],
+        };
+
+        AlonzoProtParams {
+            minfee_a: self.minfee_a,
+            minfee_b: self.minfee_b,
+            max_block_body_size: self.max_block_body_size,
+            max_transaction_size: self.max_transaction_size,
+            max_block_header_size: self.max_block_header_size,
+            key_deposit: self.key_deposit,
+            pool_deposit: self.pool_deposit,
+            maximum_epoch: 0,
+            desired_number_of_stake_pools: 0,
+            pool_pledge_influence: zero(),
+            expansion_rate: zero(),
+            treasury_growth_rate: zero(),
+            decentralization_constant: zero(),
+            extra_entropy: Nonce {
+                variant: NonceVariant::NeutralNonce,
+                hash: None,
+            },
+            protocol_version: (self.protocol_major, self.protocol_minor),
+            min_pool_cost: self.min_pool_cost,
+            // Alonzo prices outputs per word, the same field carries it
+            ada_per_utxo_byte: self.ada_per_utxo_byte,
+            cost_models_for_script_languages: KeyValuePairs::from(cost_models),
+            execution_costs: self.execution_costs(),
+            max_tx_ex_units: self.max_tx_ex_units(),
+            max_block_ex_units: self.max_block_ex_units(),
+            max_value_size: self.max_value_size,
+            collateral_percentage: self.collateral_percentage,
+            max_collateral_inputs: self.max_collateral_inputs,
+        }
+    }
+
+    fn to_babbage(&self) -> BabbageProtParams {
+        BabbageProtParams {
+            minfee_a: self.minfee_a,
+            minfee_b: self.minfee_b,
+            max_block_body_size: self.max_block_body_size,
+            max_transaction_size: self.max_transaction_size,
+            max_block_header_size: self.max_block_header_size,
+            key_deposit: self.key_deposit,
+            pool_deposit: self.pool_deposit,
+            maximum_epoch: 0,
+            desired_number_of_stake_pools: 0,
+            pool_pledge_influence: zero(),
+            expansion_rate: zero(),
+            treasury_growth_rate: zero(),
+            decentralization_constant: zero(),
+            extra_entropy: Nonce {
+                variant: NonceVariant::NeutralNonce,
+                hash: None,
+            },
+            protocol_version: (self.protocol_major, self.protocol_minor),
+            min_pool_cost: self.min_pool_cost,
+            ada_per_utxo_byte: self.ada_per_utxo_byte,
+            cost_models_for_script_languages: babbage::CostMdls {
+                plutus_v1: ProtocolParameters::cost_model(&self.plutus_v1_cost_model),
+                plutus_v2: ProtocolParameters::cost_model(&self.plutus_v2_cost_model),
+            },
+            execution_costs: self.execution_costs(),
+            max_tx_ex_units: self.max_tx_ex_units(),
+            max_block_ex_units: self.max_block_ex_units(),
+            max_value_size: self.max_value_size,
+            collateral_percentage: self.collateral_percentage,
+            max_collateral_inputs: self.max_collateral_inputs,
+        }
+    }
+}
+
+// Pallas reports a violation as `Era(Rule)`
+fn rule(error: &ValidationError) -> String {
+    let debug = format!("{:?}", error);
+    let names: Vec<&str> = debug.split(['(', ')', '{', ' ']).filter(|name| !name.is_empty()).collect();
+
+    names.get(1).or(names.first()).copied().unwrap_or_default().to_string()
+}
+
+// The rule is looked up in RULE_MESSAGES and otherwise spelled out from its
+// name
+fn describe(rule: &str) -> String {
+    match RULE_MESSAGES.iter().find(|(name, _)| *name == rule) {
+        Some((_, message)) => message.to_string(),
+        None => {
+            let mut message = String::new();
+
+            for (i, c) in rule.char_indices() {
+                if c.is_uppercase() && i > 0 && !rule[..i].ends_with(char::is_uppercase) {
+                    message.push(' ');
+                }
+
+                message.push(c.to_ascii_lowercase());
+            }
+
+            message
+        }
+    }
+}
+
+// Lifts the limit a rule checks the tx against, so that validating again
+// gets past it. False for rules that do not depend on a parameter.
+fn lift(params: &mut ProtocolParameters, rule: &str) -> bool {
+    match rule {
+        "FeeBelowMin" => {
+            params.minfee_a = 0;
+            params.minfee_b = 0;
+        }
+        "MaxTxSizeExceeded" => params.max_transaction_size = u32::MAX,
+        "MaxValSizeExceeded" => params.max_value_size = u32::MAX,
+        "MinLovelaceUnreached" => params.ada_per_utxo_byte = 0,
+        "TxExUnitsExceeded" => {
+            params.max_tx_ex_mem = u64::MAX;
+            params.max_tx_ex_steps = u64::MAX;
+        }
+        "TooManyCollaterals" => params.max_collateral_inputs = u32::MAX,
+        "CollateralMinLovelace" => params.collateral_percentage = 0,
+        _ => return false,
+    }
+
+    true
+}
+
+#[derive(Net)]
+pub struct TxValidator {}
+
+impl TxValidator {
+    // Runs the phase-1 ledger rules of `protocol_params.era` against the tx
+    // and returns the violated rules, an empty list means the tx is valid.
+    // Pallas stops at the first failing rule. When that rule checks the tx
+    // against a parameter, such as the fee or size limits, the limit is
+    // lifted and the tx validated again to find the violations after it. A
+    // violation of any other rule ends the list.
+    #[net]
+    pub fn validate_tx(
+        tx_cbor: Vec<u8>,
+        resolved_inputs: Vec<ResolvedInput>,
+        protocol_params: ProtocolParameters,
+        network_magic: u64,
+        slot: u64,
+    ) -> Vec<String> {
+        TxValidator::validate_tx(tx_cbor, resolved_inputs, protocol_params, network_magic, slot)
+    }
+
+    pub fn validate_tx(
+        tx_cbor: Vec<u8>,
+        resolved_inputs: Vec<ResolvedInput>,
+        protocol_params: ProtocolParameters,
+        network_magic: u64,
+        slot: u64,
+    ) -> Vec<String> {
+        let era = match protocol_params.to_multi_era() {
+            Ok((era, _)) => era,
+            Err(e) => return vec![e],
+        };
+
+        let tx = match MultiEraTx::decode_for_era(era, &tx_cbor) {
+            Ok(tx) => tx,
+            Err(e) => return vec![format!("the tx is not valid {:?} CBOR: {}", era, e)],
+        };
+
+        let mut utxos: UTxOs = HashMap::new();
+
+        for resolved in resolved_inputs.iter() {
+            let tx_hash: [u8; 32] = match resolved.tx_hash.as_slice().try_into() {
+                Ok(tx_hash) => tx_hash,
+                Err(_) => return vec![format!("input hash {} is not 32 bytes", hex::encode(&resolved.tx_hash))],
+            };
+
+            let input = MultiEraInput::AlonzoCompatible(Box::new(Cow::Owned(TransactionInput {
+                transaction_id: Hash::new(tx_hash),
+                index: resolved.index,
+            })));
+
+            let output = match MultiEraOutput::decode(tx.era(), &resolved.output_cbor) {
+                Ok(output) => output,
+                Err(e) => {
+                    return vec![format!(
+                        "output {}#{} is not valid CBOR: {}",
+                        hex::encode(&resolved.tx_hash),
+                        resolved.index,
+                        e
+                    )]
+                }
+            };
+
+            utxos.insert(input, output);
+        }
+
+        let mut params = protocol_params;
+        let mut violations = vec![];
+
+        loop {
+            let env = Environment {
+                prot_params: match params.to_multi_era() {
+                    Ok((_, prot_params)) => prot_params,
+                    Err(e) => return vec![e],
+                },
+                prot_magic: network_magic as u32,
+                block_slot: slot,
+                network_id: if network_magic == MAINNET_MAGIC { 1 } else { 0 },
+            };
+
+            let Err(e) = validate(&tx, &utxos, &env) else {
+                return violations;
+            };
+
+            let rule = rule(&e);
+            violations.push(describe(&rule));
+
+            if !lift(&mut params, &rule) {
+                return violations;
+            }
+        }
+    }
+}
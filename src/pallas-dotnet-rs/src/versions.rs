@@ -0,0 +1,217 @@
+use std::{fmt::Debug, ops::RangeInclusive};
+
+use pallas::{
+    codec::minicbor::{self, Decoder},
+    network::{
+    miniprotocols::{
+        handshake::{self, n2c, n2n, Confirmation, VersionTable},
+        PROTOCOL_N2N_HANDSHAKE,
+    },
+    multiplexer::Plexer,
+    },
+};
+use rnet::{net, Net};
+
+use crate::{
+    bearer::ClientKind,
+    options::{with_timeout, ClientOptions, Interrupted},
+    runtime, ClientWrapper,
+};
+
+// Node-to-client version numbers carry this bit on the wire (V16 is 32784)
+const N2C_VERSION_MASK: u64 = 0x8000;
+
+const N2C_DEFAULT_VERSIONS: RangeInclusive<u64> = 10..=16;
+const N2C_QUERY_SINCE: u64 = 15;
+
+const N2N_DEFAULT_VERSIONS: RangeInclusive<u64> = 7..=13;
+// Both the query flag and peer sharing were introduced with V11
+const N2N_QUERY_SINCE: u64 = 11;
+
+// Versions are plain numbers for both protocols. An empty list proposes every
+// version supported by the client.
+#[derive(Net, Clone, Default)]
+pub struct HandshakeOptions {
+    pub(crate) versions: Vec<u64>,
+    pub(crate) query: bool,
+    pub(crate) initiator_and_responder: bool,
+    pub(crate) peer_sharing: bool,
+}
+
+impl HandshakeOptions {
+    fn versions(&self, defaults: RangeInclusive<u64>, since: u64) -> Vec<u64> {
+        let versions: Vec<u64> = match self.versions.is_empty() {
+            true => defaults.collect(),
+            false => self.versions.clone(),
+        };
+
+        // A query can only be sent with versions that know the flag
+        versions.into_iter().filter(|v| !self.query || *v >= since).collect()
+    }
+
+    pub fn n2c_versions(&self, network_magic: u64) -> n2c::VersionTable {
+        let values = self
+            .versions(N2C_DEFAULT_VERSIONS, N2C_QUERY_SINCE)
+            .into_iter()
+            .map(|v| {
+                let query = (v >= N2C_QUERY_SINCE).then_some(self.query);
+                (v | N2C_VERSION_MASK, n2c::VersionData::new(network_magic, query))
+            })
+            .collect();
+
+        VersionTable { values }
+    }
+
+    pub fn n2n_versions(&self, network_magic: u64) -> n2n::VersionTable {
+        let values = self
+            .versions(N2N_DEFAULT_VERSIONS, N2N_QUERY_SINCE)
+            .into_iter()
+            .map(|v| {
+                let peer_sharing = (v >= N2N_QUERY_SINCE).then_some(self.peer_sharing as u8);
+                let query = (v >= N2N_QUERY_SINCE).then_some(self.query);
+                let data = n2n::VersionData::new(network_magic, !self.initiator_and_responder, peer_sharing, query);
+                (v, data)
+            })
+            .collect();
+
+        VersionTable { values }
+    }
+
+    fn with_query(&self) -> HandshakeOptions {
+        HandshakeOptions {
+            query: true,
+            ..self.clone()
+        }
+    }
+}
+
+#[derive(Net, Clone, Default)]
+pub struct VersionInfo {
+    pub(crate) version: u64,
+    pub(crate) network_magic: u64,
+    pub(crate) query: bool,
+    pub(crate) initiator_only_diffusion_mode: bool,
+    pub(crate) peer_sharing: bool,
+}
+
+// pallas keeps the fields of the handshake version data private, so they
+// are read back from its CBOR encoding
+impl VersionInfo {
+    pub fn n2c(version: u64, data: &n2c::VersionData) -> VersionInfo {
+        let cbor = minicbor::to_vec(data).unwrap_or_default();
+
+        // Before the query flag the data is the bare network magic
+        let mut d = Decoder::new(&cbor);
+        let (network_magic, query) = match d.array() {
+            Ok(_) => (d.u64().unwrap_or_default(), d.bool().ok()),
+            Err(_) => (Decoder::new(&cbor).u64().unwrap_or_default(), None),
+        };
+
+        VersionInfo {
+            version: version & !N2C_VERSION_MASK,
+            network_magic,
+            query: query.unwrap_or(false),
+            initiator_only_diffusion_mode: true,
+            peer_sharing: false,
+        }
+    }
+
+    pub fn n2n(version: u64, data: &n2n::VersionData) -> VersionInfo {
+        let cbor = minicbor::to_vec(data).unwrap_or_default();
+
+        // [magic, initiator only, peer sharing, query], the last two since V11
+        let mut d = Decoder::new(&cbor);
+        let len = d.array().ok().flatten();
+        let network_magic = d.u64().unwrap_or_default();
+        let initiator_only_diffusion_mode = d.bool().unwrap_or(true);
+        let (peer_sharing, query) = match len {
+            Some(4) => (d.u8().unwrap_or_default(), d.bool().unwrap_or(false)),
+            _ => (0, false),
+        };
+
+        VersionInfo {
+            version,
+            network_magic,
+            query,
+            initiator_only_diffusion_mode,
+            peer_sharing: peer_sharing > 0,
+        }
+    }
+}
+
+pub fn accepted<D: Debug + Clone>(
+    result: Result<Result<Confirmation<D>, handshake::Error>, Interrupted>,
+) -> Result<(u64, D), String> {
+    match result {
+        Ok(Ok(Confirmation::Accepted(version, data))) => Ok((version, data)),
+        Ok(Ok(Confirmation::Rejected(reason))) => Err(format!("handshake rejected: {:?}", reason)),
+        Ok(Ok(Confirmation::QueryReply(_))) => Err("handshake answered with a version query reply".to_string()),
+        Ok(Err(e)) => Err(format!("handshake failed: {:?}", e)),
+        Err(_) => Err("handshake timed out".to_string()),
+    }
+}
+
+// Handshake-only connection asking the node which versions it supports. The
+// node closes the connection after replying.
+async fn query_versions(path_or_server: &str, network_magic: u64, client: u8, options: &ClientOptions) -> Result<Vec<VersionInfo>, String> {
+    let client = ClientKind::try_from(client)?;
+    let bearer = options.bearer(client)?.connect(path_or_server, options).await?;
+
+    // Handshake runs on protocol 0 for both N2C and N2N
+    let mut plexer = Plexer::new(bearer);
+    let channel = plexer.subscribe_client(PROTOCOL_N2N_HANDSHAKE);
+    let plexer = plexer.spawn();
+
+    let handshake_options = options.handshake().with_query();
+
+    let versions = match client {
+        ClientKind::N2C => {
+            let versions = handshake_options.n2c_versions(network_magic);
+            let result = with_timeout(handshake::Client::new(channel).handshake(versions), options.handshake_timeout()).await;
+
+            match result {
+                Ok(Ok(Confirmation::QueryReply(table))) => Ok(table.values.iter().map(|(v, data)| VersionInfo::n2c(*v, data)).collect()),
+                result => accepted(result).map(|_| vec![]),
+            }
+        }
+        ClientKind::N2N => {
+            let versions = handshake_options.n2n_versions(network_magic);
+            let result = with_timeout(handshake::Client::new(channel).handshake(versions), options.handshake_timeout()).await;
+
+            match result {
+                Ok(Ok(Confirmation::QueryReply(table))) => Ok(table.values.iter().map(|(v, data)| VersionInfo::n2n(*v, data)).collect()),
+                result => accepted(result).map(|_| vec![]),
+            }
+        }
+    };
+
+    plexer.abort().await;
+
+    versions.map(|mut versions: Vec<VersionInfo>| {
+        versions.sort_by_key(|v| v.version);
+        versions
+    })
+}
+
+impl ClientWrapper {
+    #[net]
+    pub fn get_negotiated_version(client_wrapper: ClientWrapper) -> VersionInfo {
+        ClientWrapper::get_negotiated_version(client_wrapper)
+    }
+
+    pub fn get_negotiated_version(client_wrapper: ClientWrapper) -> VersionInfo {
+        client_wrapper.state().version.lock().unwrap().clone()
+    }
+
+    #[net]
+    pub fn query_versions(path_or_server: String, network_magic: u64, client: u8, options: ClientOptions) -> Vec<VersionInfo> {
+        ClientWrapper::query_versions(path_or_server, network_magic, client, options)
+    }
+
+    pub fn query_versions(path_or_server: String, network_magic: u64, client: u8, options: ClientOptions) -> Vec<VersionInfo> {
+        runtime::block_on(query_versions(&path_or_server, network_magic, client, &options)).unwrap_or_else(|e| {
+            println!("query_versions error: {}", e);
+            vec![]
+        })
+    }
+}
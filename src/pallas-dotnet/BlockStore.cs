@@ -0,0 +1,135 @@
+using PallasDotnet.Models;
+
+namespace PallasDotnet;
+
+// Append-only block storage in a local directory, indexed by point and block number.
+// Blocks are kept in the [era, block] CBOR envelope chain-sync delivers.
+public class BlockStore
+{
+    private PallasDotnetRs.PallasDotnetRs.BlockStoreWrapper? _store;
+
+    public bool IsOpen => _store != null;
+
+    // Creates the directory when it does not exist yet
+    public void Open(string path)
+    {
+        _store = PallasDotnetRs.PallasDotnetRs.OpenBlockStore(path);
+
+        if (_store is null)
+        {
+            throw new Exception("Failed to open block store");
+        }
+    }
+
+    public Point Tip
+    {
+        get
+        {
+            if (_store is null)
+            {
+                throw new Exception("Block store not open");
+            }
+
+            return Utils.MapPallasPoint(PallasDotnetRs.PallasDotnetRs.GetBlockStoreTip(_store.Value));
+        }
+    }
+
+    // Returns false when the block cannot be decoded or does not extend the tip
+    public bool PutBlock(byte[] blockCbor)
+    {
+        if (_store is null)
+        {
+            throw new Exception("Block store not open");
+        }
+
+        return PallasDotnetRs.PallasDotnetRs.PutBlock(_store.Value, blockCbor);
+    }
+
+    public byte[]? GetBlock(Point point)
+    {
+        if (_store is null)
+        {
+            throw new Exception("Block store not open");
+        }
+
+        return PallasDotnetRs.PallasDotnetRs.GetBlock(_store.Value, MapPoint(point))?.ToArray();
+    }
+
+    public byte[]? GetBlockByNumber(ulong number)
+    {
+        if (_store is null)
+        {
+            throw new Exception("Block store not open");
+        }
+
+        return PallasDotnetRs.PallasDotnetRs.GetBlockByNumber(_store.Value, number)?.ToArray();
+    }
+
+    // Both ends included, empty when either point is not stored
+    public IEnumerable<byte[]> GetRange(Point from, Point to)
+    {
+        if (_store is null)
+        {
+            throw new Exception("Block store not open");
+        }
+
+        return PallasDotnetRs.PallasDotnetRs.GetBlockRange(_store.Value, MapPoint(from), MapPoint(to))
+            .Select(block => block.ToArray());
+    }
+
+    // Walks the stored blocks after `from`, or from the first block when no point is given
+    public IEnumerable<byte[]> GetBlocks(Point? from = null, ulong pageSize = 100)
+    {
+        if (_store is null)
+        {
+            throw new Exception("Block store not open");
+        }
+
+        var point = from is null ? new PallasDotnetRs.PallasDotnetRs.Point { slot = 0, hash = [] } : MapPoint(from);
+
+        while (true)
+        {
+            var page = PallasDotnetRs.PallasDotnetRs.GetBlocksAfter(_store.Value, point, pageSize);
+
+            foreach (var block in page.blocks)
+            {
+                yield return [.. block];
+            }
+
+            if ((ulong)page.blocks.Count < pageSize)
+            {
+                yield break;
+            }
+
+            point = page.last;
+        }
+    }
+
+    // Drops every block after the point. An empty hash empties the store.
+    public bool RollbackTo(Point point)
+    {
+        if (_store is null)
+        {
+            throw new Exception("Block store not open");
+        }
+
+        return PallasDotnetRs.PallasDotnetRs.RollbackTo(_store.Value, MapPoint(point));
+    }
+
+    public void Close()
+    {
+        if (_store is null)
+        {
+            return;
+        }
+
+        PallasDotnetRs.PallasDotnetRs.CloseBlockStore(_store.Value);
+        _store = null;
+    }
+
+    private static PallasDotnetRs.PallasDotnetRs.Point MapPoint(Point point) => new()
+    {
+        slot = point.Slot,
+        hash = new List<byte>(Convert.FromHexString(point.Hash))
+    };
+}
@@ -0,0 +1,87 @@
+using PallasDotnet.Models;
+
+namespace PallasDotnet;
+
+// Re-serves blocks to downstream chain-sync (N2C) or chain-sync/block-fetch (N2N) clients.
+// Blocks are pushed in the same [era, block] CBOR envelope the clients deliver.
+public class ChainServer
+{
+    private PallasDotnetRs.PallasDotnetRs.ServerWrapper? _server;
+
+    public bool IsRunning => _server != null;
+
+    // Only the last `capacity` blocks are kept, 0 keeps every block.
+    // Use port 0 to let the OS pick a free port, Address returns the bound one.
+    public void Start(string address, ulong magicNumber, Client client = Client.N2N, Bearer bearer = Bearer.Default, ulong capacity = 0)
+    {
+        _server = PallasDotnetRs.PallasDotnetRs.StartServer(address, magicNumber, (byte)client, (byte)bearer, capacity);
+
+        if (_server is null)
+        {
+            throw new Exception("Failed to start server");
+        }
+    }
+
+    public string Address
+    {
+        get
+        {
+            if (_server is null)
+            {
+                throw new Exception("Server not started");
+            }
+
+            return PallasDotnetRs.PallasDotnetRs.GetServerAddress(_server.Value);
+        }
+    }
+
+    public Point Tip
+    {
+        get
+        {
+            if (_server is null)
+            {
+                throw new Exception("Server not started");
+            }
+
+            return Utils.MapPallasPoint(PallasDotnetRs.PallasDotnetRs.GetServerTip(_server.Value));
+        }
+    }
+
+    // Returns false when the block cannot be decoded or does not extend the tip
+    public bool RollForward(byte[] blockCbor)
+    {
+        if (_server is null)
+        {
+            throw new Exception("Server not started");
+        }
+
+        return PallasDotnetRs.PallasDotnetRs.ServerRollForward(_server.Value, blockCbor);
+    }
+
+    // Consumers past the point are rolled back to it. An empty hash rolls back to origin.
+    public bool RollBackward(Point point)
+    {
+        if (_server is null)
+        {
+            throw new Exception("Server not started");
+        }
+
+        return PallasDotnetRs.PallasDotnetRs.ServerRollBackward(_server.Value, new PallasDotnetRs.PallasDotnetRs.Point
+        {
+            slot = point.Slot,
+            hash = new List<byte>(Convert.FromHexString(point.Hash))
+        });
+    }
+
+    public void Stop()
+    {
+        if (_server is null)
+        {
+            return;
+        }
+
+        PallasDotnetRs.PallasDotnetRs.StopServer(_server.Value);
+        _server = null;
+    }
+}
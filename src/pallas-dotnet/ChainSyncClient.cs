@@ -0,0 +1,313 @@
+using PallasDotnet.Models;
+using ClientWrapper = PallasDotnetRs.PallasDotnetRs.ClientWrapper;
+using NextResponseRs = PallasDotnetRs.PallasDotnetRs.NextResponse;
+
+namespace PallasDotnet;
+
+// Chain-sync and everything following it (events, finality, sink, cursor, acked delivery), which
+// behave the same over N2C and N2N. N2cClient and N2nClient add how they connect and the calls
+// specific to their protocols.
+public abstract class ChainSyncClient
+{
+    protected ClientWrapper? _client;
+
+    public bool IsConnected => _client != null;
+    public bool IsSyncing { get; private set; }
+    public ulong BufferSize { get; set; } = 64;
+    public TimeSpan PollInterval { get; set; } = TimeSpan.FromMilliseconds(100);
+
+    // Raised when the reconnect attempts configured in ClientOptions are exhausted, or for a peer set
+    // when every configured peer failed
+    public event EventHandler? Disconnected;
+    public event EventHandler? Reconnected;
+
+    protected ClientWrapper ConnectedClient => _client ?? throw new Exception("Not connected to node");
+
+    private async Task FindIntersectAsync(Point? intersection)
+    {
+        if (intersection is null)
+        {
+            return;
+        }
+
+        var client = ConnectedClient;
+
+        await Task.Run(() =>
+        {
+            PallasDotnetRs.PallasDotnetRs.FindIntersect(client, new PallasDotnetRs.PallasDotnetRs.Point
+            {
+                slot = intersection.Slot,
+                hash = new List<byte>(Convert.FromHexString(intersection.Hash))
+            });
+        });
+    }
+
+    private void OnDisconnected()
+    {
+        IsSyncing = false;
+        Disconnected?.Invoke(this, EventArgs.Empty);
+    }
+
+    public async IAsyncEnumerable<NextResponse> StartChainSyncAsync(Point? intersection = null)
+    {
+        var client = ConnectedClient;
+
+        await FindIntersectAsync(intersection);
+
+        IsSyncing = true;
+        PallasDotnetRs.PallasDotnetRs.StartChainSyncStream(client, BufferSize);
+
+        while (IsSyncing)
+        {
+            NextResponseRs nextResponseRs = PallasDotnetRs.PallasDotnetRs.ChainSyncTryNext(client);
+
+            switch ((NextResponseAction)nextResponseRs.action)
+            {
+                case NextResponseAction.Pending:
+                    await Task.Delay(PollInterval);
+                    break;
+                case NextResponseAction.Error:
+                    OnDisconnected();
+                    break;
+                case NextResponseAction.Cancelled:
+                    IsSyncing = false;
+                    break;
+                case NextResponseAction.Reconnected:
+                    Reconnected?.Invoke(this, EventArgs.Empty);
+                    break;
+                case NextResponseAction.Await:
+                    yield return new(NextResponseAction.Await, default!, default!);
+                    break;
+                case var action:
+                    // N2N rollbacks come with the block rolled back to, except to origin
+                    yield return new(
+                        action,
+                        Utils.MapPallasPoint(nextResponseRs.tip),
+                        nextResponseRs.blockCbor is null ? default! : [.. nextResponseRs.blockCbor]);
+                    break;
+            }
+        }
+    }
+
+    // Pulls up to `maxCount` responses in one call, stopping early at the tip (Await) or once `maxWait`
+    // has passed. Meant for catching up, where one call per block dominates the sync time. A request
+    // still waiting at `maxWait` continues on the next call, so the list can come back empty.
+    public async Task<List<NextResponse>> NextBatchAsync(ulong maxCount, TimeSpan maxWait = default)
+    {
+        var client = ConnectedClient;
+
+        return await Task.Run(() =>
+            Utils.MapNextBatch(PallasDotnetRs.PallasDotnetRs.ChainSyncNextBatch(client, maxCount, (ulong)maxWait.TotalMilliseconds)));
+    }
+
+    // At-least-once delivery: at most `window` responses are prefetched ahead of the last Ack, and
+    // stopping goes back to the last acknowledged point so the rest is delivered again.
+    public async IAsyncEnumerable<AckedResponse> StartAckedChainSyncAsync(ulong window)
+    {
+        var client = ConnectedClient;
+
+        if (!PallasDotnetRs.PallasDotnetRs.StartAckedStream(client, window))
+        {
+            throw new Exception("Chain-sync is already streaming");
+        }
+
+        IsSyncing = true;
+
+        while (IsSyncing)
+        {
+            var ackedResponse = PallasDotnetRs.PallasDotnetRs.AckedStreamTryNext(client);
+
+            switch ((NextResponseAction)ackedResponse.action)
+            {
+                case NextResponseAction.Pending:
+                    await Task.Delay(PollInterval);
+                    break;
+                case NextResponseAction.Error:
+                    OnDisconnected();
+                    break;
+                case NextResponseAction.Reconnected:
+                    Reconnected?.Invoke(this, EventArgs.Empty);
+                    break;
+                case NextResponseAction.Await:
+                    yield return new(new(NextResponseAction.Await, default!, default!), null);
+                    break;
+                case NextResponseAction.RollForward:
+                    yield return new(
+                        new(NextResponseAction.RollForward, Utils.MapPallasPoint(ackedResponse.tip), [.. ackedResponse.blockCbor]),
+                        Utils.MapPallasPoint(ackedResponse.point));
+                    break;
+                case NextResponseAction.RollBack:
+                    yield return new(
+                        new(NextResponseAction.RollBack, Utils.MapPallasPoint(ackedResponse.tip), default!),
+                        Utils.MapPallasPoint(ackedResponse.point));
+                    break;
+            }
+        }
+    }
+
+    // Blocks are only confirmed once `depth` blocks are built on top of them, 0 disables the buffer
+    public void SetFinalityDepth(ulong depth)
+    {
+        PallasDotnetRs.PallasDotnetRs.SetFinalityDepth(ConnectedClient, depth);
+    }
+
+    // Confirmed blocks only, rollbacks are resolved on the Rust side. Can run alongside
+    // StartChainSyncAsync, which keeps yielding the tentative chain. A rollback past a confirmed
+    // block ends the sync like an error.
+    public async IAsyncEnumerable<NextResponse> StartConfirmedChainSyncAsync(Point? intersection = null)
+    {
+        var client = ConnectedClient;
+
+        await FindIntersectAsync(intersection);
+
+        IsSyncing = true;
+
+        while (IsSyncing)
+        {
+            NextResponseRs nextResponseRs = await Task.Run(() => PallasDotnetRs.PallasDotnetRs.ChainSyncNextConfirmed(client));
+
+            switch ((NextResponseAction)nextResponseRs.action)
+            {
+                case NextResponseAction.Pending:
+                    await Task.Delay(PollInterval);
+                    break;
+                case NextResponseAction.Error:
+                    OnDisconnected();
+                    break;
+                case NextResponseAction.Cancelled:
+                    IsSyncing = false;
+                    break;
+                case NextResponseAction.Reconnected:
+                    Reconnected?.Invoke(this, EventArgs.Empty);
+                    break;
+                case NextResponseAction.Await:
+                    yield return new(NextResponseAction.Await, default!, default!);
+                    break;
+                case NextResponseAction.RollForward:
+                    yield return new(NextResponseAction.RollForward, Utils.MapPallasPoint(nextResponseRs.tip), [.. nextResponseRs.blockCbor]);
+                    break;
+            }
+        }
+    }
+
+    // Typed alternative to StartChainSyncAsync, see ChainEvent. Rollbacks yield the undo events of the
+    // blocks they remove.
+    public async IAsyncEnumerable<ChainEvent> StartChainEventsAsync(Point? intersection = null)
+    {
+        var client = ConnectedClient;
+
+        await FindIntersectAsync(intersection);
+
+        IsSyncing = true;
+
+        while (IsSyncing)
+        {
+            var chainEvents = await Task.Run(() => PallasDotnetRs.PallasDotnetRs.ChainSyncNextEvents(client));
+
+            switch ((NextResponseAction)chainEvents.action)
+            {
+                case NextResponseAction.Error:
+                    OnDisconnected();
+                    break;
+                case NextResponseAction.Cancelled:
+                    IsSyncing = false;
+                    break;
+                case NextResponseAction.Reconnected:
+                    Reconnected?.Invoke(this, EventArgs.Empty);
+                    break;
+            }
+
+            foreach (var chainEvent in chainEvents.events)
+            {
+                yield return Utils.MapChainEvent(chainEvent);
+            }
+        }
+    }
+
+    public ProtocolVersion GetNegotiatedVersion()
+    {
+        return Utils.MapVersionInfo(PallasDotnetRs.PallasDotnetRs.GetNegotiatedVersion(ConnectedClient));
+    }
+
+    // Chain-sync only delivers the blocks holding a matching tx, stripped of the other txs.
+    // Pass null or an empty filter to receive every block again.
+    public void SetChainSyncFilter(SyncFilter? filter)
+    {
+        if (!PallasDotnetRs.PallasDotnetRs.SetChainSyncFilter(ConnectedClient, Utils.MapSyncFilter(filter ?? new SyncFilter())))
+        {
+            throw new Exception("Invalid chain-sync filter");
+        }
+    }
+
+    // The indexer follows every block, rollback and intersection of chain-sync from now on
+    public void AttachUtxoIndexer(UtxoIndexer indexer)
+    {
+        PallasDotnetRs.PallasDotnetRs.AttachUtxoIndexer(ConnectedClient, indexer.Wrapper);
+    }
+
+    public void DetachUtxoIndexer()
+    {
+        PallasDotnetRs.PallasDotnetRs.DetachUtxoIndexer(ConnectedClient);
+    }
+
+    // Writes chain-sync to files in the background, resuming from the checkpoint in the directory
+    public void StartSink(SinkOptions options)
+    {
+        if (!PallasDotnetRs.PallasDotnetRs.StartSink(ConnectedClient, Utils.MapSinkOptions(options)))
+        {
+            throw new Exception("Cannot start sink");
+        }
+    }
+
+    public SinkStatus GetSinkStatus()
+    {
+        var status = PallasDotnetRs.PallasDotnetRs.GetSinkStatus(ConnectedClient);
+
+        return new(status.running, status.last.hash is null ? null : Utils.MapPallasPoint(status.last), status.error);
+    }
+
+    public void StopSink()
+    {
+        PallasDotnetRs.PallasDotnetRs.StopSink(ConnectedClient);
+    }
+
+    // Records that the response at `point` is processed, advancing the cursor set in ClientOptions.CursorPath
+    // and letting StartAckedChainSyncAsync prefetch further
+    public void Ack(Point point)
+    {
+        if (!PallasDotnetRs.PallasDotnetRs.AckPoint(ConnectedClient, new PallasDotnetRs.PallasDotnetRs.Point
+        {
+            slot = point.Slot,
+            hash = new List<byte>(Convert.FromHexString(point.Hash))
+        }))
+        {
+            throw new Exception("Cannot acknowledge point");
+        }
+    }
+
+    public List<Point> GetCursorPoints()
+    {
+        return PallasDotnetRs.PallasDotnetRs.GetCursorPoints(ConnectedClient)
+            .Select(Utils.MapPallasPoint)
+            .ToList();
+    }
+
+    public void StopSync()
+    {
+        IsSyncing = false;
+
+        if (_client is not null)
+        {
+            PallasDotnetRs.PallasDotnetRs.Cancel(_client.Value);
+            PallasDotnetRs.PallasDotnetRs.StopChainSyncStream(_client.Value);
+            PallasDotnetRs.PallasDotnetRs.StopAckedStream(_client.Value);
+        }
+    }
+
+    public Task DisconnectAsync()
+    {
+        var client = ConnectedClient;
+
+        return Task.Run(() => PallasDotnetRs.PallasDotnetRs.Disconnect(client));
+    }
+}
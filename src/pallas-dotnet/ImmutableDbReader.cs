@@ -0,0 +1,51 @@
+using PallasDotnet.Models;
+
+namespace PallasDotnet;
+
+// Reads blocks straight from a node's db/immutable directory, e.g. for backfilling history
+// without the network. Responses match the ones of N2cClient chain-sync.
+public static class ImmutableDbReader
+{
+    public static Point GetTip(string path)
+        => Utils.MapPallasPoint(PallasDotnetRs.PallasDotnetRs.GetImmutableDbTip(path));
+
+    // Starts after `from`, or at the first block when no point is given. Throws when the
+    // directory cannot be read or does not contain `from`.
+    // Ends with an Await response once the last immutable block has been read.
+    public static IEnumerable<NextResponse> ReadBlocks(string path, Point? from = null)
+    {
+        var db = PallasDotnetRs.PallasDotnetRs.OpenImmutableDb(path, new PallasDotnetRs.PallasDotnetRs.Point
+        {
+            slot = from?.Slot ?? 0,
+            hash = from is null ? [] : new List<byte>(Convert.FromHexString(from.Hash))
+        }) ?? throw new Exception("Failed to open the immutable db");
+
+        try
+        {
+            while (true)
+            {
+                var nextResponseRs = PallasDotnetRs.PallasDotnetRs.ImmutableDbNext(db);
+                var action = (NextResponseAction)nextResponseRs.action;
+
+                if (action == NextResponseAction.Error)
+                {
+                    throw new Exception("Failed to read the immutable db");
+                }
+
+                Point tip = Utils.MapPallasPoint(nextResponseRs.tip);
+
+                if (action == NextResponseAction.Await)
+                {
+                    yield return new(action, tip, default!);
+                    yield break;
+                }
+
+                yield return new(action, tip, [.. nextResponseRs.blockCbor]);
+            }
+        }
+        finally
+        {
+            PallasDotnetRs.PallasDotnetRs.CloseImmutableDb(db);
+        }
+    }
+}
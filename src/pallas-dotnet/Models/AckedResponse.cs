@@ -0,0 +1,4 @@
+namespace PallasDotnet.Models;
+
+// Point is what to pass to Ack once the response is processed, null for Await
+public record AckedResponse(NextResponse Response, Point? Point);
@@ -0,0 +1,10 @@
+namespace PallasDotnet.Models;
+
+// Default is a unix socket for N2C and TCP (host:port) for N2N
+public enum Bearer
+{
+    Default,
+    Unix,
+    Tcp,
+    NamedPipe
+}
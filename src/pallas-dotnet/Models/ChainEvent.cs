@@ -0,0 +1,31 @@
+namespace PallasDotnet.Models;
+
+public enum ChainEventKind
+{
+    Block = 1,
+    Tx,
+    OutputCreated,
+    OutputSpent,
+    // Negative quantities are burns
+    Mint,
+    Certificate,
+    GovernanceProposal
+}
+
+// Undo events are the counterparts of the events of a rolled back block, emitted in reverse order.
+// OutputTxHash#Index is the output created or spent, Index is otherwise the position of the
+// certificate or proposal in the tx.
+public record ChainEvent(
+    ChainEventKind Kind,
+    bool Undo,
+    Point Block,
+    ulong BlockNumber,
+    string? TxHash,
+    ulong TxIndex,
+    string? OutputTxHash,
+    ulong Index,
+    byte[] Cbor,
+    string? PolicyId,
+    byte[]? AssetName,
+    long Quantity
+);
@@ -0,0 +1,17 @@
+namespace PallasDotnet.Models;
+
+public record ClientOptions(
+    TimeSpan ConnectTimeout = default,
+    TimeSpan HandshakeTimeout = default,
+    TimeSpan RequestTimeout = default,
+    TimeSpan IdleTimeout = default,
+    TimeSpan KeepAliveInterval = default,
+    // Same default as the native ClientOptions, 0 disables reconnecting
+    uint MaxReconnectAttempts = 10,
+    TimeSpan ReconnectBackoff = default,
+    Bearer Bearer = Bearer.Default,
+    HandshakeOptions? Handshake = null,
+    // File of the acknowledged points, ConnectAsync resumes after the newest one still on chain
+    string? CursorPath = null,
+    uint CursorPoints = 0
+);
@@ -0,0 +1,9 @@
+namespace PallasDotnet.Models;
+
+// Versions are plain protocol numbers, an empty list proposes every supported version
+public record HandshakeOptions(
+    List<ulong>? Versions = null,
+    bool Query = false,
+    bool InitiatorAndResponder = false,
+    bool PeerSharing = false
+);
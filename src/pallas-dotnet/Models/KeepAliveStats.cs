@@ -0,0 +1,7 @@
+namespace PallasDotnet.Models;
+
+public record KeepAliveStats(
+    TimeSpan RoundTripTime,
+    ulong RoundTrips,
+    string? Error
+);
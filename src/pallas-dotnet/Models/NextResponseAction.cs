@@ -5,5 +5,8 @@ public enum NextResponseAction
     Error,
     RollForward,
     RollBack,
-    Await
+    Await,
+    Pending,
+    Cancelled,
+    Reconnected
 }
@@ -0,0 +1,3 @@
+namespace PallasDotnet.Models;
+
+public record PeerAddress(string Ip, ushort Port);
@@ -0,0 +1,8 @@
+namespace PallasDotnet.Models;
+
+public record PeerStatus(
+    string Server,
+    bool Connected,
+    bool Active,
+    TimeSpan RoundTripTime
+);
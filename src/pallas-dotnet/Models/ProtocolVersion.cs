@@ -0,0 +1,9 @@
+namespace PallasDotnet.Models;
+
+public record ProtocolVersion(
+    ulong Version,
+    ulong NetworkMagic,
+    bool Query,
+    bool InitiatorOnlyDiffusionMode,
+    bool PeerSharing
+);
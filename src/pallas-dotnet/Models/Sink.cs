@@ -0,0 +1,17 @@
+namespace PallasDotnet.Models;
+
+public enum SinkFormat
+{
+    // Blocks concatenated as served, rollbacks truncate the files
+    RawCbor = 0,
+    // One JSON object per roll forward or rollback
+    Ndjson = 1,
+    // One [action, point, block?] CBOR item per roll forward or rollback
+    CborSeq = 2
+}
+
+// A new file is started once the current one reaches MaxFileBytes, 0 never rotates
+public record SinkOptions(string Directory, SinkFormat Format = SinkFormat.RawCbor, ulong MaxFileBytes = 0);
+
+// Error is empty unless the sink stopped on an error
+public record SinkStatus(bool Running, Point? Last, string Error);
@@ -0,0 +1,13 @@
+namespace PallasDotnet.Models;
+
+// A tx matches when any of its outputs, mints or metadata matches any of the criteria.
+// Hashes are hex encoded, asset names are raw bytes matched under any policy.
+public record SyncFilter(
+    List<string>? Addresses = null,
+    List<string>? PaymentCredentials = null,
+    List<string>? StakeCredentials = null,
+    List<string>? PolicyIds = null,
+    List<byte[]>? AssetNames = null,
+    List<ulong>? MetadataLabels = null,
+    List<string>? ScriptHashes = null
+);
@@ -0,0 +1,6 @@
+namespace PallasDotnet.Models;
+
+public record TxSubmissionOutcome(
+    List<TxSubmissionResult> Results,
+    string? Error
+);
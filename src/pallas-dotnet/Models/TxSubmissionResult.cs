@@ -0,0 +1,7 @@
+namespace PallasDotnet.Models;
+
+public record TxSubmissionResult(
+    string TxId,
+    TxSubmissionStatus Status,
+    bool BodyRequested
+);
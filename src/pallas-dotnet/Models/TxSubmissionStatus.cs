@@ -0,0 +1,11 @@
+namespace PallasDotnet.Models;
+
+public enum TxSubmissionStatus
+{
+    Pending,
+    Announced,
+    BodyRequested,
+    Acknowledged,
+    Timeout,
+    ProtocolError
+}
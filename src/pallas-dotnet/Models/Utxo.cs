@@ -0,0 +1,3 @@
+namespace PallasDotnet.Models;
+
+public record Utxo(string TxHash, ulong Index, byte[] OutputCbor);
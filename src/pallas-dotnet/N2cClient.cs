@@ -1,133 +1,49 @@
 ﻿using PallasDotnet.Models;
-using NextResponseRs = PallasDotnetRs.PallasDotnetRs.NextResponse;
 
 namespace PallasDotnet;
 
-public class N2cClient
+public class N2cClient : ChainSyncClient
 {
-    private PallasDotnetRs.PallasDotnetRs.ClientWrapper? _n2cClient;
-    private string _socketPath = string.Empty;
-    private ulong _magicNumber = 0;
-    private byte[] _lastHash = [];
-    private ulong _lastSlot = 0;
-    private byte _client = 0;
-
-    public bool IsConnected => _n2cClient != null;
-    public bool IsSyncing { get; private set; }
+    [Obsolete("Set ClientOptions.MaxReconnectAttempts instead, 0 disables reconnecting")]
     public bool ShouldReconnect { get; set; } = true;
 
-    public event EventHandler? Disconnected;
-    public event EventHandler? Reconnected;
-
-    public async Task<Point> ConnectAsync(string socketPath, ulong magicNumber)
+    // With Bearer.Tcp in the options socketPath is a host:port, e.g. a socket exposed through socat
+    public async Task<Point> ConnectAsync(string socketPath, ulong magicNumber, ClientOptions? options = null)
     {
+#pragma warning disable CS0618
+        if (!ShouldReconnect)
+        {
+            options = (options ?? new ClientOptions()) with { MaxReconnectAttempts = 0 };
+        }
+#pragma warning restore CS0618
+
         return await Task.Run(() =>
         {
-            _n2cClient = PallasDotnetRs.PallasDotnetRs.Connect(socketPath, magicNumber, (byte)Client.N2C);
+            _client = PallasDotnetRs.PallasDotnetRs.ConnectWithOptions(socketPath, magicNumber, (byte)Client.N2C, Utils.MapClientOptions(options));
 
-            if (_n2cClient is null)
+            if (_client is null)
             {
                 throw new Exception("Failed to connect to node");
             }
-            
-            _magicNumber = magicNumber;
-            _socketPath = socketPath;
-            _client = (byte)Client.N2C;
 
-            var pallasPoint = PallasDotnetRs.PallasDotnetRs.GetTip(_n2cClient.Value);
+            var pallasPoint = PallasDotnetRs.PallasDotnetRs.GetTip(_client.Value);
             return Utils.MapPallasPoint(pallasPoint);
         });
     }
 
-    public async IAsyncEnumerable<NextResponse> StartChainSyncAsync(Point? intersection = null)
-    {
-        if (_n2cClient is null)
-        {
-            throw new Exception("Not connected to node");
-        }
-
-        if (intersection is not null)
-        {
-            await Task.Run(() =>
-            {
-                PallasDotnetRs.PallasDotnetRs.FindIntersect(_n2cClient.Value, new PallasDotnetRs.PallasDotnetRs.Point
-                {
-                    slot = intersection.Slot,
-                    hash = new List<byte>(Convert.FromHexString(intersection.Hash))
-                });
-            });
-        }
-
-        IsSyncing = true;
-        while (IsSyncing)
-        {   
-            NextResponseRs nextResponseRs = PallasDotnetRs.PallasDotnetRs.ChainSyncNext(_n2cClient.Value);
-
-            if ((NextResponseAction)nextResponseRs.action == NextResponseAction.Error)
-            {
-                if (ShouldReconnect)
-                {
-                    _n2cClient = PallasDotnetRs.PallasDotnetRs.Connect(_socketPath, _magicNumber, _client);
-                    PallasDotnetRs.PallasDotnetRs.FindIntersect(_n2cClient.Value, new PallasDotnetRs.PallasDotnetRs.Point
-                    {
-                        slot = _lastSlot,
-                        hash = [.. _lastHash]
-                    });
-                    Reconnected?.Invoke(this, EventArgs.Empty);
-                }
-                else
-                {
-                    IsSyncing = false;
-                    Disconnected?.Invoke(this, EventArgs.Empty);
-                }
-            }
-            else if ((NextResponseAction)nextResponseRs.action == NextResponseAction.Await)
-            {
-                yield return new
-                (
-                    NextResponseAction.Await,
-                    default!,
-                    default!
-                );
-            }
-            else
-            {
-                NextResponseAction nextResponseAction = (NextResponseAction)nextResponseRs.action;
-                Point tip = Utils.MapPallasPoint(nextResponseRs.tip);
-
-                NextResponse nextResponse = nextResponseAction switch
-                {
-                    NextResponseAction.RollForward => new(nextResponseAction, tip, [.. nextResponseRs.blockCbor]),
-                    NextResponseAction.RollBack => new(nextResponseAction, tip, default!),
-                    _ => default!
-                };
-                
-                yield return nextResponse;
-            }
-        }
-    }
-
     public async Task<List<byte[]>> GetUtxoByAddressCborAsync(string address)
     {
-        if (_n2cClient is null)
-        {
-            throw new Exception("Not connected to node");
-        }
-        var utxoByAddress = await Task.Run(() => PallasDotnetRs.PallasDotnetRs.GetUtxoByAddressCbor(_n2cClient.Value, address));
-        return utxoByAddress?.Select(utxo => utxo.ToArray()).ToList() ?? [];
-    }
+        var client = ConnectedClient;
 
-    public void StopSync()
-    {
-        IsSyncing = false;
+        var utxoByAddress = await Task.Run(() => PallasDotnetRs.PallasDotnetRs.GetUtxoByAddressCbor(client, address));
+        return utxoByAddress?.Select(utxo => utxo.ToArray()).ToList() ?? [];
     }
 
-    public Task DisconnectAsync()
+    public static async Task<List<ProtocolVersion>> QueryVersionsAsync(string socketPath, ulong magicNumber, ClientOptions? options = null)
     {
-        if (_n2cClient is null)
-        {
-            throw new Exception("Not connected to node");
-        }
-        return Task.Run(() => PallasDotnetRs.PallasDotnetRs.Disconnect(_n2cClient.Value));
+        return await Task.Run(() =>
+            PallasDotnetRs.PallasDotnetRs.QueryVersions(socketPath, magicNumber, (byte)Client.N2C, Utils.MapClientOptions(options))
+                .Select(Utils.MapVersionInfo)
+                .ToList());
     }
 }
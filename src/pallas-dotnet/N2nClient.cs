@@ -1,110 +1,29 @@
 using PallasDotnet.Models;
-using NextResponseRs = PallasDotnetRs.PallasDotnetRs.NextResponse;
 
 namespace PallasDotnet;
 
-public class N2nClient
+public class N2nClient : ChainSyncClient
 {
-    private PallasDotnetRs.PallasDotnetRs.ClientWrapper? _n2nClient;
-    private string _server = string.Empty;
-    private ulong _magicNumber = 0;
-    private bool IsSyncing { get; set; }
-    private bool IsConnected => _n2nClient != null;
-    public bool ShouldReconnect { get; set; } = true; 
-    private ulong _lastSlot = 0;   
-    private byte[] _lastHash = [];
-    private byte _client = 0;
-    public event EventHandler? Disconnected;
-    public event EventHandler? Reconnected;
-
-    public async Task<Point> ConnectAsync(string server, ulong magicNumber)
+    public Task<Point> ConnectAsync(string server, ulong magicNumber, ClientOptions? options = null)
+        => ConnectAsync([server], magicNumber, options);
+
+    // Chain-sync runs on the fastest relay and fails over to the others natively,
+    // re-intersecting at the last delivered point
+    public async Task<Point> ConnectAsync(IEnumerable<string> servers, ulong magicNumber, ClientOptions? options = null, bool fetchFromFastest = false)
     {
-        _n2nClient = PallasDotnetRs.PallasDotnetRs.Connect(server, magicNumber, (byte)Client.N2N);
+        _client = PallasDotnetRs.PallasDotnetRs.ConnectPeerSet(servers.ToList(), magicNumber, Utils.MapClientOptions(options), fetchFromFastest);
 
-        if (_n2nClient is null)
+        if (_client is null)
         {
             throw new Exception("Failed to connect to node");
         }
 
-        _server = server;
-        _magicNumber = magicNumber;  
-        _client = (byte)Client.N2N;
-
         return await GetTipAsync();
     }
 
-    public async IAsyncEnumerable<NextResponse> StartChainSyncAsync(Point? intersection = null)
-    {
-        if (_n2nClient is null)
-        {
-            throw new Exception("Not connected to node");
-        }
-
-        if (intersection is not null)
-        {
-            await Task.Run(() =>
-            {
-                PallasDotnetRs.PallasDotnetRs.FindIntersect(_n2nClient.Value, new PallasDotnetRs.PallasDotnetRs.Point
-                {
-                    slot = intersection.Slot,
-                    hash = new List<byte>(Convert.FromHexString(intersection.Hash))
-                });
-            });
-        }
-
-        IsSyncing = true;
-        
-        while (IsSyncing)
-        {
-            NextResponseRs nextResponseRs = PallasDotnetRs.PallasDotnetRs.ChainSyncNext(_n2nClient.Value);
-
-            if ((NextResponseAction)nextResponseRs.action == NextResponseAction.Error)
-            {
-                if (ShouldReconnect)
-                {
-                    _n2nClient = PallasDotnetRs.PallasDotnetRs.Connect(_server, _magicNumber, _client);
-
-                    PallasDotnetRs.PallasDotnetRs.FindIntersect(_n2nClient.Value, new PallasDotnetRs.PallasDotnetRs.Point
-                    {
-                        slot = _lastSlot,
-                        hash = [.. _lastHash]
-                    });
-
-                    Reconnected?.Invoke(this, EventArgs.Empty);
-                }
-                else
-                {
-                    IsSyncing = false;
-                    Disconnected?.Invoke(this, EventArgs.Empty);
-                }
-            }
-            else if ((NextResponseAction)nextResponseRs.action == NextResponseAction.Await)
-            {
-                yield return new
-                (
-                    NextResponseAction.Await,
-                    default!,
-                    default!
-                );
-            }
-            else
-            {
-                NextResponseAction nextResponseAction = (NextResponseAction)nextResponseRs.action;
-                Point tip = Utils.MapPallasPoint(nextResponseRs.tip);
-
-                NextResponse nextResponse = new(nextResponseAction, tip, [.. nextResponseRs.blockCbor]);
-
-                yield return nextResponse;
-            }
-        }
-    }
-
     public async Task<byte[]> FetchBlockAsync(Point? intersection = null)
     {
-        if (_n2nClient is null)
-        {
-            throw new Exception("Not connected to node");
-        }
+        var client = ConnectedClient;
 
         if (intersection is null)
         {
@@ -112,7 +31,7 @@ public class N2nClient
         }
 
         return await Task.Run(() => {
-            return PallasDotnetRs.PallasDotnetRs.FetchBlock(_n2nClient.Value, new PallasDotnetRs.PallasDotnetRs.Point
+            return PallasDotnetRs.PallasDotnetRs.FetchBlock(client, new PallasDotnetRs.PallasDotnetRs.Point
             {
                 slot = intersection.Slot,
                 hash = new List<byte>(Convert.FromHexString(intersection.Hash))
@@ -120,17 +39,74 @@ public class N2nClient
         });
     }
 
+    // False when the connection's tx submission client was already used by a stopped session
+    public bool StartTxSubmission()
+    {
+        return PallasDotnetRs.PallasDotnetRs.StartTxSubmission(ConnectedClient);
+    }
+
+    public string? EnqueueTx(byte[] txCbor)
+    {
+        List<byte>? txId = PallasDotnetRs.PallasDotnetRs.EnqueueTx(ConnectedClient, txCbor);
+
+        return txId is null ? null : Convert.ToHexString([.. txId]);
+    }
+
+    public TxSubmissionOutcome GetTxSubmissionStatus()
+    {
+        var outcome = PallasDotnetRs.PallasDotnetRs.GetTxSubmissionStatus(ConnectedClient);
+
+        var results = outcome.results
+            .Select(result => new TxSubmissionResult(Convert.ToHexString([.. result.txId]), (TxSubmissionStatus)result.status, result.bodyRequested))
+            .ToList();
+
+        return new(results, outcome.error);
+    }
+
+    public void StopTxSubmission()
+    {
+        PallasDotnetRs.PallasDotnetRs.StopTxSubmission(ConnectedClient);
+    }
+
+    // Requires a handshake with PeerSharing enabled, see HandshakeOptions
+    public async Task<List<PeerAddress>> SharePeersAsync(byte amount)
+    {
+        var client = ConnectedClient;
+
+        return await Task.Run(() =>
+            PallasDotnetRs.PallasDotnetRs.SharePeers(client, amount)
+                .Select(address => new PeerAddress(address.ip, address.port))
+                .ToList());
+    }
+
+    public List<PeerStatus> GetPeerStatus()
+    {
+        return PallasDotnetRs.PallasDotnetRs.GetPeerSetStatus(ConnectedClient)
+            .Select(status => new PeerStatus(status.server, status.connected, status.active, TimeSpan.FromMicroseconds(status.rttUs)))
+            .ToList();
+    }
+
+    public KeepAliveStats GetKeepAliveStats()
+    {
+        var stats = PallasDotnetRs.PallasDotnetRs.GetKeepAliveStats(ConnectedClient);
+
+        return new(TimeSpan.FromMicroseconds(stats.rttUs), stats.roundtrips, stats.error);
+    }
+
+    public static async Task<List<ProtocolVersion>> QueryVersionsAsync(string server, ulong magicNumber, ClientOptions? options = null)
+    {
+        return await Task.Run(() =>
+            PallasDotnetRs.PallasDotnetRs.QueryVersions(server, magicNumber, (byte)Client.N2N, Utils.MapClientOptions(options))
+                .Select(Utils.MapVersionInfo)
+                .ToList());
+    }
+
     public async Task<Point> GetTipAsync()
     {
-        if (_n2nClient is null)
-        {
-            throw new Exception("Not connected to node");
-        }
+        PallasDotnetRs.PallasDotnetRs.Point tip = PallasDotnetRs.PallasDotnetRs.GetTip(ConnectedClient);
 
-        PallasDotnetRs.PallasDotnetRs.Point tip = PallasDotnetRs.PallasDotnetRs.GetTip(_n2nClient.Value);
-        
         return await Task.Run(() => {
             return Utils.MapPallasPoint(tip);
         });
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,11 @@
+namespace PallasDotnet;
+
+public static class NativeRuntime
+{
+    // Must run before the first client connects, returns false if the runtime is already started
+    public static bool Init(uint workerThreads = 0, string threadNamePrefix = "", bool currentThread = false)
+        => PallasDotnetRs.PallasDotnetRs.InitRuntime(workerThreads, threadNamePrefix, currentThread);
+
+    public static void Shutdown(TimeSpan timeout)
+        => PallasDotnetRs.PallasDotnetRs.ShutdownRuntime((ulong)timeout.TotalMilliseconds);
+}
@@ -14,12 +14,127 @@ namespace PallasDotnetRs
         public interface IOpaqueHandle: IEquatable<IOpaqueHandle>, IDisposable {}
 
         
+        public struct ServerWrapper {
+            public UIntPtr serverPtr;
+        }
+        public struct ClientOptions {
+            public ulong connectTimeoutMs;
+            public ulong handshakeTimeoutMs;
+            public ulong requestTimeoutMs;
+            public ulong idleTimeoutMs;
+            public ulong keepaliveIntervalMs;
+            public uint maxReconnectAttempts;
+            public ulong reconnectBackoffMs;
+            public byte bearer;
+            public HandshakeOptions handshake;
+            public string cursorPath;
+            public uint cursorPoints;
+        }
+        public struct VersionInfo {
+            public ulong version;
+            public ulong networkMagic;
+            public bool query;
+            public bool initiatorOnlyDiffusionMode;
+            public bool peerSharing;
+        }
+        public struct HandshakeOptions {
+            public List<ulong> versions;
+            public bool query;
+            public bool initiatorAndResponder;
+            public bool peerSharing;
+        }
+        public struct KeepAliveStats {
+            public ulong rttUs;
+            public ulong roundtrips;
+            public string error;
+        }
+        public struct PeerAddress {
+            public string ip;
+            public ushort port;
+        }
+        public struct SinkStatus {
+            public bool running;
+            public Point last;
+            public string error;
+        }
+        public struct SinkOptions {
+            public string directory;
+            public byte format;
+            public ulong maxFileBytes;
+        }
+        public struct RedeemerExUnits {
+            public byte tag;
+            public uint index;
+            public ulong mem;
+            public ulong steps;
+        }
+        public struct ScriptEvaluator {
+        }
+        public struct SlotConfig {
+            public ulong zeroTime;
+            public ulong zeroSlot;
+            public uint slotLength;
+        }
+        public struct EvaluationResult {
+            public List<RedeemerExUnits> redeemers;
+            public string error;
+            public List<string> logs;
+        }
+        public struct UtxoIndexerWrapper {
+            public UIntPtr indexerPtr;
+        }
+        public struct UtxoRef {
+            public List<byte> txHash;
+            public ulong index;
+        }
+        public struct TxValidator {
+        }
+        public struct ProtocolParameters {
+            public byte era;
+            public uint minfeeA;
+            public uint minfeeB;
+            public uint maxBlockBodySize;
+            public uint maxTransactionSize;
+            public uint maxBlockHeaderSize;
+            public ulong keyDeposit;
+            public ulong poolDeposit;
+            public ulong protocolMajor;
+            public ulong protocolMinor;
+            public ulong minPoolCost;
+            public ulong adaPerUtxoByte;
+            public uint maxValueSize;
+            public uint collateralPercentage;
+            public uint maxCollateralInputs;
+            public ulong maxTxExMem;
+            public ulong maxTxExSteps;
+            public ulong maxBlockExMem;
+            public ulong maxBlockExSteps;
+            public ulong memPriceNumerator;
+            public ulong memPriceDenominator;
+            public ulong stepPriceNumerator;
+            public ulong stepPriceDenominator;
+            public List<long> plutusV1CostModel;
+            public List<long> plutusV2CostModel;
+            public List<long> plutusV3CostModel;
+        }
+        public struct ResolvedInput {
+            public List<byte> txHash;
+            public ulong index;
+            public List<byte> outputCbor;
+        }
         public struct NetworkMagic {
         }
         public struct Point {
             public ulong slot;
             public List<byte> hash;
         }
+        public struct NextBatch {
+            public List<NextResponse> responses;
+            public List<byte> blocks;
+            public List<ulong> offsets;
+        }
+        public struct PallasUtility {
+        }
         public struct NextResponse {
             public byte action;
             public Point tip;
@@ -29,75 +144,928 @@ namespace PallasDotnetRs
             public byte client;
             public UIntPtr clientPtr;
         }
-        public struct PallasUtility {
+        public struct ChainEvents {
+            public byte action;
+            public Point tip;
+            public List<ChainEvent> events;
         }
-        public static ulong MainnetMagic(
+        public struct ChainEvent {
+            public byte kind;
+            public bool undo;
+            public ulong slot;
+            public List<byte> blockHash;
+            public ulong blockNumber;
+            public List<byte> txHash;
+            public ulong txIndex;
+            public List<byte> outputTxHash;
+            public ulong index;
+            public List<byte> cbor;
+            public List<byte> policyId;
+            public List<byte> assetName;
+            public long quantity;
+        }
+        public struct ImmutableDbWrapper {
+            public UIntPtr dbPtr;
+        }
+        public struct TxSubmissionResult {
+            public List<byte> txId;
+            public byte status;
+            public bool bodyRequested;
+        }
+        public struct TxSubmissionOutcome {
+            public List<TxSubmissionResult> results;
+            public string error;
+        }
+        public struct AckedResponse {
+            public byte action;
+            public Point tip;
+            public List<byte> blockCbor;
+            public Point point;
+        }
+        public struct NativeRuntime {
+        }
+        public struct PeerStatus {
+            public string server;
+            public bool connected;
+            public bool active;
+            public ulong rttUs;
+        }
+        public struct BlockPage {
+            public List<List<byte>> blocks;
+            public Point last;
+        }
+        public struct BlockStoreWrapper {
+            public UIntPtr storePtr;
+        }
+        public struct SyncFilter {
+            public List<string> addresses;
+            public List<List<byte>> paymentCredentials;
+            public List<List<byte>> stakeCredentials;
+            public List<List<byte>> policyIds;
+            public List<List<byte>> assetNames;
+            public List<ulong> metadataLabels;
+            public List<List<byte>> scriptHashes;
+        }
+        public static bool AckPoint(
+            ClientWrapper clientWrapper,
+            Point point
         ) {
-            return _FnMainnetMagic();
+            return (_FnAckPoint(_StructClientWrapper.Encode(clientWrapper),_StructPoint.Encode(point)) != 0);
         }
-        public static ulong TestnetMagic(
+        public static List<Point> GetCursorPoints(
+            ClientWrapper clientWrapper
         ) {
-            return _FnTestnetMagic();
+            return _FreeSlice<Point, _StructPoint, List<Point>>(_FnGetCursorPoints(_StructClientWrapper.Encode(clientWrapper)), 24, 8, _arg1 => (_arg1).Decode());
+        }
+        public static void StopServer(
+            ServerWrapper serverWrapper
+        ) {
+            _FnStopServer(_StructServerWrapper.Encode(serverWrapper));
+        }
+        public static ServerWrapper StartServer(
+            string address,
+            ulong networkMagic,
+            byte client,
+            byte bearer,
+            ulong capacity
+        ) {
+            return _DecodeOption(_FnStartServer(_AllocStr(address),networkMagic,client,bearer,capacity), _arg2 => (_arg2).Decode());
+        }
+        public static Point GetServerTip(
+            ServerWrapper serverWrapper
+        ) {
+            return (_FnGetServerTip(_StructServerWrapper.Encode(serverWrapper))).Decode();
+        }
+        public static string GetServerAddress(
+            ServerWrapper serverWrapper
+        ) {
+            return _FreeStr(_FnGetServerAddress(_StructServerWrapper.Encode(serverWrapper)));
+        }
+        public static bool ServerRollForward(
+            ServerWrapper serverWrapper,
+            IReadOnlyCollection<byte> blockCbor
+        ) {
+            return (_FnServerRollForward(_StructServerWrapper.Encode(serverWrapper),_AllocSlice<byte, byte>(blockCbor, 1, 1, _arg3 => _arg3)) != 0);
+        }
+        public static bool ServerRollBackward(
+            ServerWrapper serverWrapper,
+            Point point
+        ) {
+            return (_FnServerRollBackward(_StructServerWrapper.Encode(serverWrapper),_StructPoint.Encode(point)) != 0);
+        }
+        public static NextResponse ChainSyncTryNext(
+            ClientWrapper clientWrapper
+        ) {
+            return (_FnChainSyncTryNext(_StructClientWrapper.Encode(clientWrapper))).Decode();
+        }
+        public static void StopChainSyncStream(
+            ClientWrapper clientWrapper
+        ) {
+            _FnStopChainSyncStream(_StructClientWrapper.Encode(clientWrapper));
+        }
+        public static void StartChainSyncStream(
+            ClientWrapper clientWrapper,
+            ulong bufferSize
+        ) {
+            _FnStartChainSyncStream(_StructClientWrapper.Encode(clientWrapper),bufferSize);
+        }
+        public static void SetFinalityDepth(
+            ClientWrapper clientWrapper,
+            ulong depth
+        ) {
+            _FnSetFinalityDepth(_StructClientWrapper.Encode(clientWrapper),depth);
+        }
+        public static NextResponse ChainSyncNextConfirmed(
+            ClientWrapper clientWrapper
+        ) {
+            return (_FnChainSyncNextConfirmed(_StructClientWrapper.Encode(clientWrapper))).Decode();
+        }
+        public static List<VersionInfo> QueryVersions(
+            string pathOrServer,
+            ulong networkMagic,
+            byte client,
+            ClientOptions options
+        ) {
+            return _FreeSlice<VersionInfo, _StructVersionInfo, List<VersionInfo>>(_FnQueryVersions(_AllocStr(pathOrServer),networkMagic,client,_StructClientOptions.Encode(options)), 24, 8, _arg4 => (_arg4).Decode());
+        }
+        public static VersionInfo GetNegotiatedVersion(
+            ClientWrapper clientWrapper
+        ) {
+            return (_FnGetNegotiatedVersion(_StructClientWrapper.Encode(clientWrapper))).Decode();
+        }
+        public static KeepAliveStats GetKeepAliveStats(
+            ClientWrapper clientWrapper
+        ) {
+            return (_FnGetKeepAliveStats(_StructClientWrapper.Encode(clientWrapper))).Decode();
+        }
+        public static List<PeerAddress> SharePeers(
+            ClientWrapper clientWrapper,
+            byte amount
+        ) {
+            return _FreeSlice<PeerAddress, _StructPeerAddress, List<PeerAddress>>(_FnSharePeers(_StructClientWrapper.Encode(clientWrapper),amount), 24, 8, _arg5 => (_arg5).Decode());
+        }
+        public static void StopSink(
+            ClientWrapper clientWrapper
+        ) {
+            _FnStopSink(_StructClientWrapper.Encode(clientWrapper));
+        }
+        public static bool StartSink(
+            ClientWrapper clientWrapper,
+            SinkOptions options
+        ) {
+            return (_FnStartSink(_StructClientWrapper.Encode(clientWrapper),_StructSinkOptions.Encode(options)) != 0);
+        }
+        public static SinkStatus GetSinkStatus(
+            ClientWrapper clientWrapper
+        ) {
+            return (_FnGetSinkStatus(_StructClientWrapper.Encode(clientWrapper))).Decode();
+        }
+        public static EvaluationResult EvaluateTx(
+            IReadOnlyCollection<byte> txCbor,
+            IReadOnlyCollection<ResolvedInput> resolvedInputs,
+            ProtocolParameters protocolParams,
+            SlotConfig slotConfig
+        ) {
+            return (_FnEvaluateTx(_AllocSlice<byte, byte>(txCbor, 1, 1, _arg6 => _arg6),_AllocSlice<ResolvedInput, _StructResolvedInput>(resolvedInputs, 40, 8, _arg7 => _StructResolvedInput.Encode(_arg7)),_StructProtocolParameters.Encode(protocolParams),_StructSlotConfig.Encode(slotConfig))).Decode();
+        }
+        public static void CloseUtxoIndexer(
+            UtxoIndexerWrapper indexerWrapper
+        ) {
+            _FnCloseUtxoIndexer(_StructUtxoIndexerWrapper.Encode(indexerWrapper));
+        }
+        public static UtxoIndexerWrapper CreateUtxoIndexer(
+            ulong maxRollback
+        ) {
+            return (_FnCreateUtxoIndexer(maxRollback)).Decode();
+        }
+        public static Point GetUtxoIndexerTip(
+            UtxoIndexerWrapper indexerWrapper
+        ) {
+            return (_FnGetUtxoIndexerTip(_StructUtxoIndexerWrapper.Encode(indexerWrapper))).Decode();
+        }
+        public static string GetUtxoIndexerError(
+            UtxoIndexerWrapper indexerWrapper
+        ) {
+            return _DecodeOption(_FnGetUtxoIndexerError(_StructUtxoIndexerWrapper.Encode(indexerWrapper)), _arg8 => _FreeStr(_arg8));
+        }
+        public static List<ResolvedInput> GetIndexedUtxosByRefs(
+            UtxoIndexerWrapper indexerWrapper,
+            IReadOnlyCollection<UtxoRef> refs,
+            Point at
+        ) {
+            return _FreeSlice<ResolvedInput, _StructResolvedInput, List<ResolvedInput>>(_FnGetIndexedUtxosByRefs(_StructUtxoIndexerWrapper.Encode(indexerWrapper),_AllocSlice<UtxoRef, _StructUtxoRef>(refs, 24, 8, _arg9 => _StructUtxoRef.Encode(_arg9)),_StructPoint.Encode(at)), 40, 8, _arg10 => (_arg10).Decode());
+        }
+        public static bool UtxoIndexerRollForward(
+            UtxoIndexerWrapper indexerWrapper,
+            IReadOnlyCollection<byte> blockCbor
+        ) {
+            return (_FnUtxoIndexerRollForward(_StructUtxoIndexerWrapper.Encode(indexerWrapper),_AllocSlice<byte, byte>(blockCbor, 1, 1, _arg11 => _arg11)) != 0);
+        }
+        public static List<ResolvedInput> GetIndexedUtxosByAsset(
+            UtxoIndexerWrapper indexerWrapper,
+            IReadOnlyCollection<byte> policyId,
+            IReadOnlyCollection<byte> assetName,
+            Point at
+        ) {
+            return _FreeSlice<ResolvedInput, _StructResolvedInput, List<ResolvedInput>>(_FnGetIndexedUtxosByAsset(_StructUtxoIndexerWrapper.Encode(indexerWrapper),_AllocSlice<byte, byte>(policyId, 1, 1, _arg12 => _arg12),_AllocSlice<byte, byte>(assetName, 1, 1, _arg13 => _arg13),_StructPoint.Encode(at)), 40, 8, _arg14 => (_arg14).Decode());
+        }
+        public static bool UtxoIndexerRollBackward(
+            UtxoIndexerWrapper indexerWrapper,
+            Point point
+        ) {
+            return (_FnUtxoIndexerRollBackward(_StructUtxoIndexerWrapper.Encode(indexerWrapper),_StructPoint.Encode(point)) != 0);
+        }
+        public static List<ResolvedInput> GetIndexedUtxosByPolicy(
+            UtxoIndexerWrapper indexerWrapper,
+            IReadOnlyCollection<byte> policyId,
+            Point at
+        ) {
+            return _FreeSlice<ResolvedInput, _StructResolvedInput, List<ResolvedInput>>(_FnGetIndexedUtxosByPolicy(_StructUtxoIndexerWrapper.Encode(indexerWrapper),_AllocSlice<byte, byte>(policyId, 1, 1, _arg15 => _arg15),_StructPoint.Encode(at)), 40, 8, _arg16 => (_arg16).Decode());
+        }
+        public static List<ResolvedInput> GetIndexedUtxosByAddress(
+            UtxoIndexerWrapper indexerWrapper,
+            string address,
+            Point at
+        ) {
+            return _FreeSlice<ResolvedInput, _StructResolvedInput, List<ResolvedInput>>(_FnGetIndexedUtxosByAddress(_StructUtxoIndexerWrapper.Encode(indexerWrapper),_AllocStr(address),_StructPoint.Encode(at)), 40, 8, _arg17 => (_arg17).Decode());
+        }
+        public static List<ResolvedInput> GetIndexedUtxosByPaymentCredential(
+            UtxoIndexerWrapper indexerWrapper,
+            IReadOnlyCollection<byte> credential,
+            Point at
+        ) {
+            return _FreeSlice<ResolvedInput, _StructResolvedInput, List<ResolvedInput>>(_FnGetIndexedUtxosByPaymentCredential(_StructUtxoIndexerWrapper.Encode(indexerWrapper),_AllocSlice<byte, byte>(credential, 1, 1, _arg18 => _arg18),_StructPoint.Encode(at)), 40, 8, _arg19 => (_arg19).Decode());
+        }
+        public static void AttachUtxoIndexer(
+            ClientWrapper clientWrapper,
+            UtxoIndexerWrapper indexerWrapper
+        ) {
+            _FnAttachUtxoIndexer(_StructClientWrapper.Encode(clientWrapper),_StructUtxoIndexerWrapper.Encode(indexerWrapper));
+        }
+        public static void DetachUtxoIndexer(
+            ClientWrapper clientWrapper
+        ) {
+            _FnDetachUtxoIndexer(_StructClientWrapper.Encode(clientWrapper));
+        }
+        public static List<string> ValidateTx(
+            IReadOnlyCollection<byte> txCbor,
+            IReadOnlyCollection<ResolvedInput> resolvedInputs,
+            ProtocolParameters protocolParams,
+            ulong networkMagic,
+            ulong slot
+        ) {
+            return _FreeSlice<string, _RawSlice, List<string>>(_FnValidateTx(_AllocSlice<byte, byte>(txCbor, 1, 1, _arg20 => _arg20),_AllocSlice<ResolvedInput, _StructResolvedInput>(resolvedInputs, 40, 8, _arg21 => _StructResolvedInput.Encode(_arg21)),_StructProtocolParameters.Encode(protocolParams),networkMagic,slot), 16, 8, _arg22 => _FreeStr(_arg22));
+        }
+        public static ulong MainnetMagic(
+        ) {
+            return _FnMainnetMagic();
         }
         public static ulong PreviewMagic(
         ) {
             return _FnPreviewMagic();
         }
+        public static ulong TestnetMagic(
+        ) {
+            return _FnTestnetMagic();
+        }
         public static ulong PreProductionMagic(
         ) {
             return _FnPreProductionMagic();
         }
+        public static void Cancel(
+            ClientWrapper clientWrapper
+        ) {
+            _FnCancel(_StructClientWrapper.Encode(clientWrapper));
+        }
         public static ClientWrapper Connect(
             string pathOrServer,
             ulong networkMagic,
             byte client
         ) {
-            return (_FnConnect(_AllocStr(pathOrServer),networkMagic,client)).Decode();
-        }
-        public static List<List<byte>> GetUtxoByAddressCbor(
-            ClientWrapper clientWrapper,
-            string address
-        ) {
-            return _FreeSlice<List<byte>, _RawSlice, List<List<byte>>>(_FnGetUtxoByAddressCbor(_StructClientWrapper.Encode(clientWrapper),_AllocStr(address)), 16, 8, _arg1 => _FreeSlice<byte, byte, List<byte>>(_arg1, 1, 1, _arg2 => _arg2));
+            return _DecodeOption(_FnConnect(_AllocStr(pathOrServer),networkMagic,client), _arg23 => (_arg23).Decode());
         }
         public static Point GetTip(
             ClientWrapper clientWrapper
         ) {
             return (_FnGetTip(_StructClientWrapper.Encode(clientWrapper))).Decode();
         }
+        public static TxSubmissionOutcome SubmitTx(
+            string server,
+            ulong magic,
+            IReadOnlyCollection<byte> tx
+        ) {
+            return (_FnSubmitTx(_AllocStr(server),magic,_AllocSlice<byte, byte>(tx, 1, 1, _arg24 => _arg24))).Decode();
+        }
+        public static void Disconnect(
+            ClientWrapper clientWrapper
+        ) {
+            _FnDisconnect(_StructClientWrapper.Encode(clientWrapper));
+        }
+        public static List<byte> EnqueueTx(
+            ClientWrapper clientWrapper,
+            IReadOnlyCollection<byte> tx
+        ) {
+            return _DecodeOption(_FnEnqueueTx(_StructClientWrapper.Encode(clientWrapper),_AllocSlice<byte, byte>(tx, 1, 1, _arg25 => _arg25)), _arg26 => _FreeSlice<byte, byte, List<byte>>(_arg26, 1, 1, _arg27 => _arg27));
+        }
+        public static TxSubmissionOutcome SubmitTxs(
+            string server,
+            ulong magic,
+            IReadOnlyCollection<List<byte>> txs
+        ) {
+            return (_FnSubmitTxs(_AllocStr(server),magic,_AllocSlice<List<byte>, _RawSlice>(txs, 16, 8, _arg28 => _AllocSlice<byte, byte>(_arg28, 1, 1, _arg29 => _arg29)))).Decode();
+        }
+        public static List<byte> FetchBlock(
+            ClientWrapper clientWrapper,
+            Point point
+        ) {
+            return _DecodeOption(_FnFetchBlock(_StructClientWrapper.Encode(clientWrapper),_StructPoint.Encode(point)), _arg30 => _FreeSlice<byte, byte, List<byte>>(_arg30, 1, 1, _arg31 => _arg31));
+        }
         public static Point FindIntersect(
             ClientWrapper clientWrapper,
             Point knownPoint
         ) {
-            return _DecodeOption(_FnFindIntersect(_StructClientWrapper.Encode(clientWrapper),_StructPoint.Encode(knownPoint)), _arg3 => (_arg3).Decode());
+            return _DecodeOption(_FnFindIntersect(_StructClientWrapper.Encode(clientWrapper),_StructPoint.Encode(knownPoint)), _arg32 => (_arg32).Decode());
         }
         public static NextResponse ChainSyncNext(
             ClientWrapper clientWrapper
         ) {
             return (_FnChainSyncNext(_StructClientWrapper.Encode(clientWrapper))).Decode();
         }
-        public static void Disconnect(
+        public static void StopTxSubmission(
             ClientWrapper clientWrapper
         ) {
-            _FnDisconnect(_StructClientWrapper.Encode(clientWrapper));
+            _FnStopTxSubmission(_StructClientWrapper.Encode(clientWrapper));
         }
-        public static List<byte> FetchBlock(
+        public static bool StartTxSubmission(
+            ClientWrapper clientWrapper
+        ) {
+            return (_FnStartTxSubmission(_StructClientWrapper.Encode(clientWrapper)) != 0);
+        }
+        public static ClientWrapper ConnectWithOptions(
+            string pathOrServer,
+            ulong networkMagic,
+            byte client,
+            ClientOptions options
+        ) {
+            return _DecodeOption(_FnConnectWithOptions(_AllocStr(pathOrServer),networkMagic,client,_StructClientOptions.Encode(options)), _arg33 => (_arg33).Decode());
+        }
+        public static NextBatch ChainSyncNextBatch(
             ClientWrapper clientWrapper,
-            Point point
+            ulong maxCount,
+            ulong maxWaitMs
         ) {
-            return _DecodeOption(_FnFetchBlock(_StructClientWrapper.Encode(clientWrapper),_StructPoint.Encode(point)), _arg4 => _FreeSlice<byte, byte, List<byte>>(_arg4, 1, 1, _arg5 => _arg5));
+            return (_FnChainSyncNextBatch(_StructClientWrapper.Encode(clientWrapper),maxCount,maxWaitMs)).Decode();
         }
-        public static List<byte> SubmitTx(
-            string server,
-            ulong magic,
-            IReadOnlyCollection<byte> tx
+        public static TxSubmissionOutcome GetTxSubmissionStatus(
+            ClientWrapper clientWrapper
+        ) {
+            return (_FnGetTxSubmissionStatus(_StructClientWrapper.Encode(clientWrapper))).Decode();
+        }
+        public static List<List<byte>> GetUtxoByAddressCbor(
+            ClientWrapper clientWrapper,
+            string address
         ) {
-            return _FreeSlice<byte, byte, List<byte>>(_FnSubmitTx(_AllocStr(server),magic,_AllocSlice<byte, byte>(tx, 1, 1, _arg6 => _arg6)), 1, 1, _arg7 => _arg7);
+            return _FreeSlice<List<byte>, _RawSlice, List<List<byte>>>(_FnGetUtxoByAddressCbor(_StructClientWrapper.Encode(clientWrapper),_AllocStr(address)), 16, 8, _arg34 => _FreeSlice<byte, byte, List<byte>>(_arg34, 1, 1, _arg35 => _arg35));
         }
         public static string AddressBytesToBech32(
             IReadOnlyCollection<byte> addressBytes
         ) {
-            return _FreeStr(_FnAddressBytesToBech32(_AllocSlice<byte, byte>(addressBytes, 1, 1, _arg8 => _arg8)));
+            return _FreeStr(_FnAddressBytesToBech32(_AllocSlice<byte, byte>(addressBytes, 1, 1, _arg36 => _arg36)));
+        }
+        public static ChainEvents ChainSyncNextEvents(
+            ClientWrapper clientWrapper
+        ) {
+            return (_FnChainSyncNextEvents(_StructClientWrapper.Encode(clientWrapper))).Decode();
+        }
+        public static NextResponse ImmutableDbNext(
+            ImmutableDbWrapper dbWrapper
+        ) {
+            return (_FnImmutableDbNext(_StructImmutableDbWrapper.Encode(dbWrapper))).Decode();
+        }
+        public static ImmutableDbWrapper OpenImmutableDb(
+            string path,
+            Point point
+        ) {
+            return _DecodeOption(_FnOpenImmutableDb(_AllocStr(path),_StructPoint.Encode(point)), _arg37 => (_arg37).Decode());
+        }
+        public static void CloseImmutableDb(
+            ImmutableDbWrapper dbWrapper
+        ) {
+            _FnCloseImmutableDb(_StructImmutableDbWrapper.Encode(dbWrapper));
+        }
+        public static Point GetImmutableDbTip(
+            string path
+        ) {
+            return (_FnGetImmutableDbTip(_AllocStr(path))).Decode();
+        }
+        public static void StopAckedStream(
+            ClientWrapper clientWrapper
+        ) {
+            _FnStopAckedStream(_StructClientWrapper.Encode(clientWrapper));
+        }
+        public static bool StartAckedStream(
+            ClientWrapper clientWrapper,
+            ulong window
+        ) {
+            return (_FnStartAckedStream(_StructClientWrapper.Encode(clientWrapper),window) != 0);
+        }
+        public static AckedResponse AckedStreamTryNext(
+            ClientWrapper clientWrapper
+        ) {
+            return (_FnAckedStreamTryNext(_StructClientWrapper.Encode(clientWrapper))).Decode();
+        }
+        public static bool InitRuntime(
+            uint workerThreads,
+            string threadNamePrefix,
+            bool enableCurrentThread
+        ) {
+            return (_FnInitRuntime(workerThreads,_AllocStr(threadNamePrefix),(enableCurrentThread ? (byte)1 : (byte)0)) != 0);
+        }
+        public static void ShutdownRuntime(
+            ulong timeoutMs
+        ) {
+            _FnShutdownRuntime(timeoutMs);
+        }
+        public static ClientWrapper ConnectPeerSet(
+            IReadOnlyCollection<string> servers,
+            ulong networkMagic,
+            ClientOptions options,
+            bool fetchFromFastest
+        ) {
+            return _DecodeOption(_FnConnectPeerSet(_AllocSlice<string, _RawSlice>(servers, 16, 8, _arg38 => _AllocStr(_arg38)),networkMagic,_StructClientOptions.Encode(options),(fetchFromFastest ? (byte)1 : (byte)0)), _arg39 => (_arg39).Decode());
+        }
+        public static List<PeerStatus> GetPeerSetStatus(
+            ClientWrapper clientWrapper
+        ) {
+            return _FreeSlice<PeerStatus, _StructPeerStatus, List<PeerStatus>>(_FnGetPeerSetStatus(_StructClientWrapper.Encode(clientWrapper)), 32, 8, _arg40 => (_arg40).Decode());
+        }
+        public static List<byte> GetBlock(
+            BlockStoreWrapper storeWrapper,
+            Point point
+        ) {
+            return _DecodeOption(_FnGetBlock(_StructBlockStoreWrapper.Encode(storeWrapper),_StructPoint.Encode(point)), _arg41 => _FreeSlice<byte, byte, List<byte>>(_arg41, 1, 1, _arg42 => _arg42));
+        }
+        public static bool PutBlock(
+            BlockStoreWrapper storeWrapper,
+            IReadOnlyCollection<byte> blockCbor
+        ) {
+            return (_FnPutBlock(_StructBlockStoreWrapper.Encode(storeWrapper),_AllocSlice<byte, byte>(blockCbor, 1, 1, _arg43 => _arg43)) != 0);
+        }
+        public static bool RollbackTo(
+            BlockStoreWrapper storeWrapper,
+            Point point
+        ) {
+            return (_FnRollbackTo(_StructBlockStoreWrapper.Encode(storeWrapper),_StructPoint.Encode(point)) != 0);
+        }
+        public static List<List<byte>> GetBlockRange(
+            BlockStoreWrapper storeWrapper,
+            Point from,
+            Point to
+        ) {
+            return _FreeSlice<List<byte>, _RawSlice, List<List<byte>>>(_FnGetBlockRange(_StructBlockStoreWrapper.Encode(storeWrapper),_StructPoint.Encode(from),_StructPoint.Encode(to)), 16, 8, _arg44 => _FreeSlice<byte, byte, List<byte>>(_arg44, 1, 1, _arg45 => _arg45));
+        }
+        public static BlockPage GetBlocksAfter(
+            BlockStoreWrapper storeWrapper,
+            Point point,
+            ulong maxCount
+        ) {
+            return (_FnGetBlocksAfter(_StructBlockStoreWrapper.Encode(storeWrapper),_StructPoint.Encode(point),maxCount)).Decode();
+        }
+        public static BlockStoreWrapper OpenBlockStore(
+            string path
+        ) {
+            return _DecodeOption(_FnOpenBlockStore(_AllocStr(path)), _arg46 => (_arg46).Decode());
+        }
+        public static void CloseBlockStore(
+            BlockStoreWrapper storeWrapper
+        ) {
+            _FnCloseBlockStore(_StructBlockStoreWrapper.Encode(storeWrapper));
+        }
+        public static List<byte> GetBlockByNumber(
+            BlockStoreWrapper storeWrapper,
+            ulong number
+        ) {
+            return _DecodeOption(_FnGetBlockByNumber(_StructBlockStoreWrapper.Encode(storeWrapper),number), _arg47 => _FreeSlice<byte, byte, List<byte>>(_arg47, 1, 1, _arg48 => _arg48));
+        }
+        public static Point GetBlockStoreTip(
+            BlockStoreWrapper storeWrapper
+        ) {
+            return (_FnGetBlockStoreTip(_StructBlockStoreWrapper.Encode(storeWrapper))).Decode();
+        }
+        public static bool SetChainSyncFilter(
+            ClientWrapper clientWrapper,
+            SyncFilter filter
+        ) {
+            return (_FnSetChainSyncFilter(_StructClientWrapper.Encode(clientWrapper),_StructSyncFilter.Encode(filter)) != 0);
+        }
+        [StructLayout(LayoutKind.Sequential)]
+        private struct _StructServerWrapper {
+            public UIntPtr serverPtr;
+            public static _StructServerWrapper Encode(ServerWrapper structArg) {
+                return new _StructServerWrapper {
+                    serverPtr = structArg.serverPtr
+                };
+            }
+            public ServerWrapper Decode() {
+                return new ServerWrapper {
+                    serverPtr = this.serverPtr
+                };
+            }
+        }
+        [StructLayout(LayoutKind.Sequential)]
+        private struct _StructClientOptions {
+            public ulong connectTimeoutMs;
+            public ulong handshakeTimeoutMs;
+            public ulong requestTimeoutMs;
+            public ulong idleTimeoutMs;
+            public ulong keepaliveIntervalMs;
+            public uint maxReconnectAttempts;
+            public ulong reconnectBackoffMs;
+            public byte bearer;
+            public _StructHandshakeOptions handshake;
+            public _RawSlice cursorPath;
+            public uint cursorPoints;
+            public static _StructClientOptions Encode(ClientOptions structArg) {
+                return new _StructClientOptions {
+                    connectTimeoutMs = structArg.connectTimeoutMs,
+                    handshakeTimeoutMs = structArg.handshakeTimeoutMs,
+                    requestTimeoutMs = structArg.requestTimeoutMs,
+                    idleTimeoutMs = structArg.idleTimeoutMs,
+                    keepaliveIntervalMs = structArg.keepaliveIntervalMs,
+                    maxReconnectAttempts = structArg.maxReconnectAttempts,
+                    reconnectBackoffMs = structArg.reconnectBackoffMs,
+                    bearer = structArg.bearer,
+                    handshake = _StructHandshakeOptions.Encode(structArg.handshake),
+                    cursorPath = _AllocStr(structArg.cursorPath),
+                    cursorPoints = structArg.cursorPoints
+                };
+            }
+            public ClientOptions Decode() {
+                return new ClientOptions {
+                    connectTimeoutMs = this.connectTimeoutMs,
+                    handshakeTimeoutMs = this.handshakeTimeoutMs,
+                    requestTimeoutMs = this.requestTimeoutMs,
+                    idleTimeoutMs = this.idleTimeoutMs,
+                    keepaliveIntervalMs = this.keepaliveIntervalMs,
+                    maxReconnectAttempts = this.maxReconnectAttempts,
+                    reconnectBackoffMs = this.reconnectBackoffMs,
+                    bearer = this.bearer,
+                    handshake = (this.handshake).Decode(),
+                    cursorPath = _FreeStr(this.cursorPath),
+                    cursorPoints = this.cursorPoints
+                };
+            }
+        }
+        [StructLayout(LayoutKind.Sequential)]
+        private struct _StructVersionInfo {
+            public ulong version;
+            public ulong networkMagic;
+            public byte query;
+            public byte initiatorOnlyDiffusionMode;
+            public byte peerSharing;
+            public static _StructVersionInfo Encode(VersionInfo structArg) {
+                return new _StructVersionInfo {
+                    version = structArg.version,
+                    networkMagic = structArg.networkMagic,
+                    query = (structArg.query ? (byte)1 : (byte)0),
+                    initiatorOnlyDiffusionMode = (structArg.initiatorOnlyDiffusionMode ? (byte)1 : (byte)0),
+                    peerSharing = (structArg.peerSharing ? (byte)1 : (byte)0)
+                };
+            }
+            public VersionInfo Decode() {
+                return new VersionInfo {
+                    version = this.version,
+                    networkMagic = this.networkMagic,
+                    query = (this.query != 0),
+                    initiatorOnlyDiffusionMode = (this.initiatorOnlyDiffusionMode != 0),
+                    peerSharing = (this.peerSharing != 0)
+                };
+            }
+        }
+        [StructLayout(LayoutKind.Sequential)]
+        private struct _StructHandshakeOptions {
+            public _RawSlice versions;
+            public byte query;
+            public byte initiatorAndResponder;
+            public byte peerSharing;
+            public static _StructHandshakeOptions Encode(HandshakeOptions structArg) {
+                return new _StructHandshakeOptions {
+                    versions = _AllocSlice<ulong, ulong>(structArg.versions, 8, 8, _arg49 => _arg49),
+                    query = (structArg.query ? (byte)1 : (byte)0),
+                    initiatorAndResponder = (structArg.initiatorAndResponder ? (byte)1 : (byte)0),
+                    peerSharing = (structArg.peerSharing ? (byte)1 : (byte)0)
+                };
+            }
+            public HandshakeOptions Decode() {
+                return new HandshakeOptions {
+                    versions = _FreeSlice<ulong, ulong, List<ulong>>(this.versions, 8, 8, _arg50 => _arg50),
+                    query = (this.query != 0),
+                    initiatorAndResponder = (this.initiatorAndResponder != 0),
+                    peerSharing = (this.peerSharing != 0)
+                };
+            }
+        }
+        [StructLayout(LayoutKind.Sequential)]
+        private struct _StructKeepAliveStats {
+            public ulong rttUs;
+            public ulong roundtrips;
+            public _RawTuple0 error;
+            public static _StructKeepAliveStats Encode(KeepAliveStats structArg) {
+                return new _StructKeepAliveStats {
+                    rttUs = structArg.rttUs,
+                    roundtrips = structArg.roundtrips,
+                    error = _EncodeOption(structArg.error, _arg51 => _AllocStr(_arg51))
+                };
+            }
+            public KeepAliveStats Decode() {
+                return new KeepAliveStats {
+                    rttUs = this.rttUs,
+                    roundtrips = this.roundtrips,
+                    error = _DecodeOption(this.error, _arg52 => _FreeStr(_arg52))
+                };
+            }
+        }
+        [StructLayout(LayoutKind.Sequential)]
+        private struct _StructPeerAddress {
+            public _RawSlice ip;
+            public ushort port;
+            public static _StructPeerAddress Encode(PeerAddress structArg) {
+                return new _StructPeerAddress {
+                    ip = _AllocStr(structArg.ip),
+                    port = structArg.port
+                };
+            }
+            public PeerAddress Decode() {
+                return new PeerAddress {
+                    ip = _FreeStr(this.ip),
+                    port = this.port
+                };
+            }
+        }
+        [StructLayout(LayoutKind.Sequential)]
+        private struct _StructSinkStatus {
+            public byte running;
+            public _RawTuple1 last;
+            public _RawSlice error;
+            public static _StructSinkStatus Encode(SinkStatus structArg) {
+                return new _StructSinkStatus {
+                    running = (structArg.running ? (byte)1 : (byte)0),
+                    last = _EncodeOption(structArg.last, _arg53 => _StructPoint.Encode(_arg53)),
+                    error = _AllocStr(structArg.error)
+                };
+            }
+            public SinkStatus Decode() {
+                return new SinkStatus {
+                    running = (this.running != 0),
+                    last = _DecodeOption(this.last, _arg54 => (_arg54).Decode()),
+                    error = _FreeStr(this.error)
+                };
+            }
+        }
+        [StructLayout(LayoutKind.Sequential)]
+        private struct _StructSinkOptions {
+            public _RawSlice directory;
+            public byte format;
+            public ulong maxFileBytes;
+            public static _StructSinkOptions Encode(SinkOptions structArg) {
+                return new _StructSinkOptions {
+                    directory = _AllocStr(structArg.directory),
+                    format = structArg.format,
+                    maxFileBytes = structArg.maxFileBytes
+                };
+            }
+            public SinkOptions Decode() {
+                return new SinkOptions {
+                    directory = _FreeStr(this.directory),
+                    format = this.format,
+                    maxFileBytes = this.maxFileBytes
+                };
+            }
+        }
+        [StructLayout(LayoutKind.Sequential)]
+        private struct _StructRedeemerExUnits {
+            public byte tag;
+            public uint index;
+            public ulong mem;
+            public ulong steps;
+            public static _StructRedeemerExUnits Encode(RedeemerExUnits structArg) {
+                return new _StructRedeemerExUnits {
+                    tag = structArg.tag,
+                    index = structArg.index,
+                    mem = structArg.mem,
+                    steps = structArg.steps
+                };
+            }
+            public RedeemerExUnits Decode() {
+                return new RedeemerExUnits {
+                    tag = this.tag,
+                    index = this.index,
+                    mem = this.mem,
+                    steps = this.steps
+                };
+            }
+        }
+        [StructLayout(LayoutKind.Sequential)]
+        private struct _StructScriptEvaluator {
+            public static _StructScriptEvaluator Encode(ScriptEvaluator structArg) {
+                return new _StructScriptEvaluator {
+                };
+            }
+            public ScriptEvaluator Decode() {
+                return new ScriptEvaluator {
+                };
+            }
+        }
+        [StructLayout(LayoutKind.Sequential)]
+        private struct _StructSlotConfig {
+            public ulong zeroTime;
+            public ulong zeroSlot;
+            public uint slotLength;
+            public static _StructSlotConfig Encode(SlotConfig structArg) {
+                return new _StructSlotConfig {
+                    zeroTime = structArg.zeroTime,
+                    zeroSlot = structArg.zeroSlot,
+                    slotLength = structArg.slotLength
+                };
+            }
+            public SlotConfig Decode() {
+                return new SlotConfig {
+                    zeroTime = this.zeroTime,
+                    zeroSlot = this.zeroSlot,
+                    slotLength = this.slotLength
+                };
+            }
+        }
+        [StructLayout(LayoutKind.Sequential)]
+        private struct _StructEvaluationResult {
+            public _RawSlice redeemers;
+            public _RawTuple0 error;
+            public _RawSlice logs;
+            public static _StructEvaluationResult Encode(EvaluationResult structArg) {
+                return new _StructEvaluationResult {
+                    redeemers = _AllocSlice<RedeemerExUnits, _StructRedeemerExUnits>(structArg.redeemers, 24, 8, _arg55 => _StructRedeemerExUnits.Encode(_arg55)),
+                    error = _EncodeOption(structArg.error, _arg56 => _AllocStr(_arg56)),
+                    logs = _AllocSlice<string, _RawSlice>(structArg.logs, 16, 8, _arg57 => _AllocStr(_arg57))
+                };
+            }
+            public EvaluationResult Decode() {
+                return new EvaluationResult {
+                    redeemers = _FreeSlice<RedeemerExUnits, _StructRedeemerExUnits, List<RedeemerExUnits>>(this.redeemers, 24, 8, _arg58 => (_arg58).Decode()),
+                    error = _DecodeOption(this.error, _arg59 => _FreeStr(_arg59)),
+                    logs = _FreeSlice<string, _RawSlice, List<string>>(this.logs, 16, 8, _arg60 => _FreeStr(_arg60))
+                };
+            }
+        }
+        [StructLayout(LayoutKind.Sequential)]
+        private struct _StructUtxoIndexerWrapper {
+            public UIntPtr indexerPtr;
+            public static _StructUtxoIndexerWrapper Encode(UtxoIndexerWrapper structArg) {
+                return new _StructUtxoIndexerWrapper {
+                    indexerPtr = structArg.indexerPtr
+                };
+            }
+            public UtxoIndexerWrapper Decode() {
+                return new UtxoIndexerWrapper {
+                    indexerPtr = this.indexerPtr
+                };
+            }
+        }
+        [StructLayout(LayoutKind.Sequential)]
+        private struct _StructUtxoRef {
+            public _RawSlice txHash;
+            public ulong index;
+            public static _StructUtxoRef Encode(UtxoRef structArg) {
+                return new _StructUtxoRef {
+                    txHash = _AllocSlice<byte, byte>(structArg.txHash, 1, 1, _arg61 => _arg61),
+                    index = structArg.index
+                };
+            }
+            public UtxoRef Decode() {
+                return new UtxoRef {
+                    txHash = _FreeSlice<byte, byte, List<byte>>(this.txHash, 1, 1, _arg62 => _arg62),
+                    index = this.index
+                };
+            }
+        }
+        [StructLayout(LayoutKind.Sequential)]
+        private struct _StructTxValidator {
+            public static _StructTxValidator Encode(TxValidator structArg) {
+                return new _StructTxValidator {
+                };
+            }
+            public TxValidator Decode() {
+                return new TxValidator {
+                };
+            }
+        }
+        [StructLayout(LayoutKind.Sequential)]
+        private struct _StructProtocolParameters {
+            public byte era;
+            public uint minfeeA;
+            public uint minfeeB;
+            public uint maxBlockBodySize;
+            public uint maxTransactionSize;
+            public uint maxBlockHeaderSize;
+            public ulong keyDeposit;
+            public ulong poolDeposit;
+            public ulong protocolMajor;
+            public ulong protocolMinor;
+            public ulong minPoolCost;
+            public ulong adaPerUtxoByte;
+            public uint maxValueSize;
+            public uint collateralPercentage;
+            public uint maxCollateralInputs;
+            public ulong maxTxExMem;
+            public ulong maxTxExSteps;
+            public ulong maxBlockExMem;
+            public ulong maxBlockExSteps;
+            public ulong memPriceNumerator;
+            public ulong memPriceDenominator;
+            public ulong stepPriceNumerator;
+            public ulong stepPriceDenominator;
+            public _RawSlice plutusV1CostModel;
+            public _RawSlice plutusV2CostModel;
+            public _RawSlice plutusV3CostModel;
+            public static _StructProtocolParameters Encode(ProtocolParameters structArg) {
+                return new _StructProtocolParameters {
+                    era = structArg.era,
+                    minfeeA = structArg.minfeeA,
+                    minfeeB = structArg.minfeeB,
+                    maxBlockBodySize = structArg.maxBlockBodySize,
+                    maxTransactionSize = structArg.maxTransactionSize,
+                    maxBlockHeaderSize = structArg.maxBlockHeaderSize,
+                    keyDeposit = structArg.keyDeposit,
+                    poolDeposit = structArg.poolDeposit,
+                    protocolMajor = structArg.protocolMajor,
+                    protocolMinor = structArg.protocolMinor,
+                    minPoolCost = structArg.minPoolCost,
+                    adaPerUtxoByte = structArg.adaPerUtxoByte,
+                    maxValueSize = structArg.maxValueSize,
+                    collateralPercentage = structArg.collateralPercentage,
+                    maxCollateralInputs = structArg.maxCollateralInputs,
+                    maxTxExMem = structArg.maxTxExMem,
+                    maxTxExSteps = structArg.maxTxExSteps,
+                    maxBlockExMem = structArg.maxBlockExMem,
+                    maxBlockExSteps = structArg.maxBlockExSteps,
+                    memPriceNumerator = structArg.memPriceNumerator,
+                    memPriceDenominator = structArg.memPriceDenominator,
+                    stepPriceNumerator = structArg.stepPriceNumerator,
+                    stepPriceDenominator = structArg.stepPriceDenominator,
+                    plutusV1CostModel = _AllocSlice<long, long>(structArg.plutusV1CostModel, 8, 8, _arg63 => _arg63),
+                    plutusV2CostModel = _AllocSlice<long, long>(structArg.plutusV2CostModel, 8, 8, _arg64 => _arg64),
+                    plutusV3CostModel = _AllocSlice<long, long>(structArg.plutusV3CostModel, 8, 8, _arg65 => _arg65)
+                };
+            }
+            public ProtocolParameters Decode() {
+                return new ProtocolParameters {
+                    era = this.era,
+                    minfeeA = this.minfeeA,
+                    minfeeB = this.minfeeB,
+                    maxBlockBodySize = this.maxBlockBodySize,
+                    maxTransactionSize = this.maxTransactionSize,
+                    maxBlockHeaderSize = this.maxBlockHeaderSize,
+                    keyDeposit = this.keyDeposit,
+                    poolDeposit = this.poolDeposit,
+                    protocolMajor = this.protocolMajor,
+                    protocolMinor = this.protocolMinor,
+                    minPoolCost = this.minPoolCost,
+                    adaPerUtxoByte = this.adaPerUtxoByte,
+                    maxValueSize = this.maxValueSize,
+                    collateralPercentage = this.collateralPercentage,
+                    maxCollateralInputs = this.maxCollateralInputs,
+                    maxTxExMem = this.maxTxExMem,
+                    maxTxExSteps = this.maxTxExSteps,
+                    maxBlockExMem = this.maxBlockExMem,
+                    maxBlockExSteps = this.maxBlockExSteps,
+                    memPriceNumerator = this.memPriceNumerator,
+                    memPriceDenominator = this.memPriceDenominator,
+                    stepPriceNumerator = this.stepPriceNumerator,
+                    stepPriceDenominator = this.stepPriceDenominator,
+                    plutusV1CostModel = _FreeSlice<long, long, List<long>>(this.plutusV1CostModel, 8, 8, _arg66 => _arg66),
+                    plutusV2CostModel = _FreeSlice<long, long, List<long>>(this.plutusV2CostModel, 8, 8, _arg67 => _arg67),
+                    plutusV3CostModel = _FreeSlice<long, long, List<long>>(this.plutusV3CostModel, 8, 8, _arg68 => _arg68)
+                };
+            }
+        }
+        [StructLayout(LayoutKind.Sequential)]
+        private struct _StructResolvedInput {
+            public _RawSlice txHash;
+            public ulong index;
+            public _RawSlice outputCbor;
+            public static _StructResolvedInput Encode(ResolvedInput structArg) {
+                return new _StructResolvedInput {
+                    txHash = _AllocSlice<byte, byte>(structArg.txHash, 1, 1, _arg69 => _arg69),
+                    index = structArg.index,
+                    outputCbor = _AllocSlice<byte, byte>(structArg.outputCbor, 1, 1, _arg70 => _arg70)
+                };
+            }
+            public ResolvedInput Decode() {
+                return new ResolvedInput {
+                    txHash = _FreeSlice<byte, byte, List<byte>>(this.txHash, 1, 1, _arg71 => _arg71),
+                    index = this.index,
+                    outputCbor = _FreeSlice<byte, byte, List<byte>>(this.outputCbor, 1, 1, _arg72 => _arg72)
+                };
+            }
         }
         [StructLayout(LayoutKind.Sequential)]
         private struct _StructNetworkMagic {
@@ -105,105 +1073,566 @@ namespace PallasDotnetRs
                 return new _StructNetworkMagic {
                 };
             }
-            public NetworkMagic Decode() {
-                return new NetworkMagic {
+            public NetworkMagic Decode() {
+                return new NetworkMagic {
+                };
+            }
+        }
+        [StructLayout(LayoutKind.Sequential)]
+        private struct _StructPoint {
+            public ulong slot;
+            public _RawSlice hash;
+            public static _StructPoint Encode(Point structArg) {
+                return new _StructPoint {
+                    slot = structArg.slot,
+                    hash = _AllocSlice<byte, byte>(structArg.hash, 1, 1, _arg73 => _arg73)
+                };
+            }
+            public Point Decode() {
+                return new Point {
+                    slot = this.slot,
+                    hash = _FreeSlice<byte, byte, List<byte>>(this.hash, 1, 1, _arg74 => _arg74)
+                };
+            }
+        }
+        [StructLayout(LayoutKind.Sequential)]
+        private struct _StructNextBatch {
+            public _RawSlice responses;
+            public _RawSlice blocks;
+            public _RawSlice offsets;
+            public static _StructNextBatch Encode(NextBatch structArg) {
+                return new _StructNextBatch {
+                    responses = _AllocSlice<NextResponse, _StructNextResponse>(structArg.responses, 64, 8, _arg75 => _StructNextResponse.Encode(_arg75)),
+                    blocks = _AllocSlice<byte, byte>(structArg.blocks, 1, 1, _arg76 => _arg76),
+                    offsets = _AllocSlice<ulong, ulong>(structArg.offsets, 8, 8, _arg77 => _arg77)
+                };
+            }
+            public NextBatch Decode() {
+                return new NextBatch {
+                    responses = _FreeSlice<NextResponse, _StructNextResponse, List<NextResponse>>(this.responses, 64, 8, _arg78 => (_arg78).Decode()),
+                    blocks = _FreeSlice<byte, byte, List<byte>>(this.blocks, 1, 1, _arg79 => _arg79),
+                    offsets = _FreeSlice<ulong, ulong, List<ulong>>(this.offsets, 8, 8, _arg80 => _arg80)
+                };
+            }
+        }
+        [StructLayout(LayoutKind.Sequential)]
+        private struct _StructPallasUtility {
+            public static _StructPallasUtility Encode(PallasUtility structArg) {
+                return new _StructPallasUtility {
+                };
+            }
+            public PallasUtility Decode() {
+                return new PallasUtility {
+                };
+            }
+        }
+        [StructLayout(LayoutKind.Sequential)]
+        private struct _StructNextResponse {
+            public byte action;
+            public _RawTuple1 tip;
+            public _RawTuple0 blockCbor;
+            public static _StructNextResponse Encode(NextResponse structArg) {
+                return new _StructNextResponse {
+                    action = structArg.action,
+                    tip = _EncodeOption(structArg.tip, _arg81 => _StructPoint.Encode(_arg81)),
+                    blockCbor = _EncodeOption(structArg.blockCbor, _arg82 => _AllocSlice<byte, byte>(_arg82, 1, 1, _arg83 => _arg83))
+                };
+            }
+            public NextResponse Decode() {
+                return new NextResponse {
+                    action = this.action,
+                    tip = _DecodeOption(this.tip, _arg84 => (_arg84).Decode()),
+                    blockCbor = _DecodeOption(this.blockCbor, _arg85 => _FreeSlice<byte, byte, List<byte>>(_arg85, 1, 1, _arg86 => _arg86))
+                };
+            }
+        }
+        [StructLayout(LayoutKind.Sequential)]
+        private struct _StructClientWrapper {
+            public byte client;
+            public UIntPtr clientPtr;
+            public static _StructClientWrapper Encode(ClientWrapper structArg) {
+                return new _StructClientWrapper {
+                    client = structArg.client,
+                    clientPtr = structArg.clientPtr
+                };
+            }
+            public ClientWrapper Decode() {
+                return new ClientWrapper {
+                    client = this.client,
+                    clientPtr = this.clientPtr
+                };
+            }
+        }
+        [StructLayout(LayoutKind.Sequential)]
+        private struct _StructChainEvents {
+            public byte action;
+            public _RawTuple1 tip;
+            public _RawSlice events;
+            public static _StructChainEvents Encode(ChainEvents structArg) {
+                return new _StructChainEvents {
+                    action = structArg.action,
+                    tip = _EncodeOption(structArg.tip, _arg87 => _StructPoint.Encode(_arg87)),
+                    events = _AllocSlice<ChainEvent, _StructChainEvent>(structArg.events, 144, 8, _arg88 => _StructChainEvent.Encode(_arg88))
+                };
+            }
+            public ChainEvents Decode() {
+                return new ChainEvents {
+                    action = this.action,
+                    tip = _DecodeOption(this.tip, _arg89 => (_arg89).Decode()),
+                    events = _FreeSlice<ChainEvent, _StructChainEvent, List<ChainEvent>>(this.events, 144, 8, _arg90 => (_arg90).Decode())
+                };
+            }
+        }
+        [StructLayout(LayoutKind.Sequential)]
+        private struct _StructChainEvent {
+            public byte kind;
+            public byte undo;
+            public ulong slot;
+            public _RawSlice blockHash;
+            public ulong blockNumber;
+            public _RawSlice txHash;
+            public ulong txIndex;
+            public _RawSlice outputTxHash;
+            public ulong index;
+            public _RawSlice cbor;
+            public _RawSlice policyId;
+            public _RawSlice assetName;
+            public long quantity;
+            public static _StructChainEvent Encode(ChainEvent structArg) {
+                return new _StructChainEvent {
+                    kind = structArg.kind,
+                    undo = (structArg.undo ? (byte)1 : (byte)0),
+                    slot = structArg.slot,
+                    blockHash = _AllocSlice<byte, byte>(structArg.blockHash, 1, 1, _arg91 => _arg91),
+                    blockNumber = structArg.blockNumber,
+                    txHash = _AllocSlice<byte, byte>(structArg.txHash, 1, 1, _arg92 => _arg92),
+                    txIndex = structArg.txIndex,
+                    outputTxHash = _AllocSlice<byte, byte>(structArg.outputTxHash, 1, 1, _arg93 => _arg93),
+                    index = structArg.index,
+                    cbor = _AllocSlice<byte, byte>(structArg.cbor, 1, 1, _arg94 => _arg94),
+                    policyId = _AllocSlice<byte, byte>(structArg.policyId, 1, 1, _arg95 => _arg95),
+                    assetName = _AllocSlice<byte, byte>(structArg.assetName, 1, 1, _arg96 => _arg96),
+                    quantity = structArg.quantity
+                };
+            }
+            public ChainEvent Decode() {
+                return new ChainEvent {
+                    kind = this.kind,
+                    undo = (this.undo != 0),
+                    slot = this.slot,
+                    blockHash = _FreeSlice<byte, byte, List<byte>>(this.blockHash, 1, 1, _arg97 => _arg97),
+                    blockNumber = this.blockNumber,
+                    txHash = _FreeSlice<byte, byte, List<byte>>(this.txHash, 1, 1, _arg98 => _arg98),
+                    txIndex = this.txIndex,
+                    outputTxHash = _FreeSlice<byte, byte, List<byte>>(this.outputTxHash, 1, 1, _arg99 => _arg99),
+                    index = this.index,
+                    cbor = _FreeSlice<byte, byte, List<byte>>(this.cbor, 1, 1, _arg100 => _arg100),
+                    policyId = _FreeSlice<byte, byte, List<byte>>(this.policyId, 1, 1, _arg101 => _arg101),
+                    assetName = _FreeSlice<byte, byte, List<byte>>(this.assetName, 1, 1, _arg102 => _arg102),
+                    quantity = this.quantity
+                };
+            }
+        }
+        [StructLayout(LayoutKind.Sequential)]
+        private struct _StructImmutableDbWrapper {
+            public UIntPtr dbPtr;
+            public static _StructImmutableDbWrapper Encode(ImmutableDbWrapper structArg) {
+                return new _StructImmutableDbWrapper {
+                    dbPtr = structArg.dbPtr
+                };
+            }
+            public ImmutableDbWrapper Decode() {
+                return new ImmutableDbWrapper {
+                    dbPtr = this.dbPtr
+                };
+            }
+        }
+        [StructLayout(LayoutKind.Sequential)]
+        private struct _StructTxSubmissionResult {
+            public _RawSlice txId;
+            public byte status;
+            public byte bodyRequested;
+            public static _StructTxSubmissionResult Encode(TxSubmissionResult structArg) {
+                return new _StructTxSubmissionResult {
+                    txId = _AllocSlice<byte, byte>(structArg.txId, 1, 1, _arg103 => _arg103),
+                    status = structArg.status,
+                    bodyRequested = (structArg.bodyRequested ? (byte)1 : (byte)0)
+                };
+            }
+            public TxSubmissionResult Decode() {
+                return new TxSubmissionResult {
+                    txId = _FreeSlice<byte, byte, List<byte>>(this.txId, 1, 1, _arg104 => _arg104),
+                    status = this.status,
+                    bodyRequested = (this.bodyRequested != 0)
                 };
             }
         }
         [StructLayout(LayoutKind.Sequential)]
-        private struct _StructPoint {
-            public ulong slot;
-            public _RawSlice hash;
-            public static _StructPoint Encode(Point structArg) {
-                return new _StructPoint {
-                    slot = structArg.slot,
-                    hash = _AllocSlice<byte, byte>(structArg.hash, 1, 1, _arg9 => _arg9)
+        private struct _StructTxSubmissionOutcome {
+            public _RawSlice results;
+            public _RawTuple0 error;
+            public static _StructTxSubmissionOutcome Encode(TxSubmissionOutcome structArg) {
+                return new _StructTxSubmissionOutcome {
+                    results = _AllocSlice<TxSubmissionResult, _StructTxSubmissionResult>(structArg.results, 24, 8, _arg105 => _StructTxSubmissionResult.Encode(_arg105)),
+                    error = _EncodeOption(structArg.error, _arg106 => _AllocStr(_arg106))
                 };
             }
-            public Point Decode() {
-                return new Point {
-                    slot = this.slot,
-                    hash = _FreeSlice<byte, byte, List<byte>>(this.hash, 1, 1, _arg10 => _arg10)
+            public TxSubmissionOutcome Decode() {
+                return new TxSubmissionOutcome {
+                    results = _FreeSlice<TxSubmissionResult, _StructTxSubmissionResult, List<TxSubmissionResult>>(this.results, 24, 8, _arg107 => (_arg107).Decode()),
+                    error = _DecodeOption(this.error, _arg108 => _FreeStr(_arg108))
                 };
             }
         }
         [StructLayout(LayoutKind.Sequential)]
-        private struct _StructNextResponse {
+        private struct _StructAckedResponse {
             public byte action;
-            public _RawTuple0 tip;
-            public _RawTuple1 blockCbor;
-            public static _StructNextResponse Encode(NextResponse structArg) {
-                return new _StructNextResponse {
+            public _RawTuple1 tip;
+            public _RawTuple0 blockCbor;
+            public _RawTuple1 point;
+            public static _StructAckedResponse Encode(AckedResponse structArg) {
+                return new _StructAckedResponse {
                     action = structArg.action,
-                    tip = _EncodeOption(structArg.tip, _arg11 => _StructPoint.Encode(_arg11)),
-                    blockCbor = _EncodeOption(structArg.blockCbor, _arg12 => _AllocSlice<byte, byte>(_arg12, 1, 1, _arg13 => _arg13))
+                    tip = _EncodeOption(structArg.tip, _arg109 => _StructPoint.Encode(_arg109)),
+                    blockCbor = _EncodeOption(structArg.blockCbor, _arg110 => _AllocSlice<byte, byte>(_arg110, 1, 1, _arg111 => _arg111)),
+                    point = _EncodeOption(structArg.point, _arg112 => _StructPoint.Encode(_arg112))
                 };
             }
-            public NextResponse Decode() {
-                return new NextResponse {
+            public AckedResponse Decode() {
+                return new AckedResponse {
                     action = this.action,
-                    tip = _DecodeOption(this.tip, _arg14 => (_arg14).Decode()),
-                    blockCbor = _DecodeOption(this.blockCbor, _arg15 => _FreeSlice<byte, byte, List<byte>>(_arg15, 1, 1, _arg16 => _arg16))
+                    tip = _DecodeOption(this.tip, _arg113 => (_arg113).Decode()),
+                    blockCbor = _DecodeOption(this.blockCbor, _arg114 => _FreeSlice<byte, byte, List<byte>>(_arg114, 1, 1, _arg115 => _arg115)),
+                    point = _DecodeOption(this.point, _arg116 => (_arg116).Decode())
                 };
             }
         }
         [StructLayout(LayoutKind.Sequential)]
-        private struct _StructClientWrapper {
-            public byte client;
-            public UIntPtr clientPtr;
-            public static _StructClientWrapper Encode(ClientWrapper structArg) {
-                return new _StructClientWrapper {
-                    client = structArg.client,
-                    clientPtr = structArg.clientPtr
+        private struct _StructNativeRuntime {
+            public static _StructNativeRuntime Encode(NativeRuntime structArg) {
+                return new _StructNativeRuntime {
                 };
             }
-            public ClientWrapper Decode() {
-                return new ClientWrapper {
-                    client = this.client,
-                    clientPtr = this.clientPtr
+            public NativeRuntime Decode() {
+                return new NativeRuntime {
                 };
             }
         }
         [StructLayout(LayoutKind.Sequential)]
-        private struct _StructPallasUtility {
-            public static _StructPallasUtility Encode(PallasUtility structArg) {
-                return new _StructPallasUtility {
+        private struct _StructPeerStatus {
+            public _RawSlice server;
+            public byte connected;
+            public byte active;
+            public ulong rttUs;
+            public static _StructPeerStatus Encode(PeerStatus structArg) {
+                return new _StructPeerStatus {
+                    server = _AllocStr(structArg.server),
+                    connected = (structArg.connected ? (byte)1 : (byte)0),
+                    active = (structArg.active ? (byte)1 : (byte)0),
+                    rttUs = structArg.rttUs
                 };
             }
-            public PallasUtility Decode() {
-                return new PallasUtility {
+            public PeerStatus Decode() {
+                return new PeerStatus {
+                    server = _FreeStr(this.server),
+                    connected = (this.connected != 0),
+                    active = (this.active != 0),
+                    rttUs = this.rttUs
                 };
             }
         }
+        [StructLayout(LayoutKind.Sequential)]
+        private struct _StructBlockPage {
+            public _RawSlice blocks;
+            public _StructPoint last;
+            public static _StructBlockPage Encode(BlockPage structArg) {
+                return new _StructBlockPage {
+                    blocks = _AllocSlice<List<byte>, _RawSlice>(structArg.blocks, 16, 8, _arg117 => _AllocSlice<byte, byte>(_arg117, 1, 1, _arg118 => _arg118)),
+                    last = _StructPoint.Encode(structArg.last)
+                };
+            }
+            public BlockPage Decode() {
+                return new BlockPage {
+                    blocks = _FreeSlice<List<byte>, _RawSlice, List<List<byte>>>(this.blocks, 16, 8, _arg119 => _FreeSlice<byte, byte, List<byte>>(_arg119, 1, 1, _arg120 => _arg120)),
+                    last = (this.last).Decode()
+                };
+            }
+        }
+        [StructLayout(LayoutKind.Sequential)]
+        private struct _StructBlockStoreWrapper {
+            public UIntPtr storePtr;
+            public static _StructBlockStoreWrapper Encode(BlockStoreWrapper structArg) {
+                return new _StructBlockStoreWrapper {
+                    storePtr = structArg.storePtr
+                };
+            }
+            public BlockStoreWrapper Decode() {
+                return new BlockStoreWrapper {
+                    storePtr = this.storePtr
+                };
+            }
+        }
+        [StructLayout(LayoutKind.Sequential)]
+        private struct _StructSyncFilter {
+            public _RawSlice addresses;
+            public _RawSlice paymentCredentials;
+            public _RawSlice stakeCredentials;
+            public _RawSlice policyIds;
+            public _RawSlice assetNames;
+            public _RawSlice metadataLabels;
+            public _RawSlice scriptHashes;
+            public static _StructSyncFilter Encode(SyncFilter structArg) {
+                return new _StructSyncFilter {
+                    addresses = _AllocSlice<string, _RawSlice>(structArg.addresses, 16, 8, _arg121 => _AllocStr(_arg121)),
+                    paymentCredentials = _AllocSlice<List<byte>, _RawSlice>(structArg.paymentCredentials, 16, 8, _arg122 => _AllocSlice<byte, byte>(_arg122, 1, 1, _arg123 => _arg123)),
+                    stakeCredentials = _AllocSlice<List<byte>, _RawSlice>(structArg.stakeCredentials, 16, 8, _arg124 => _AllocSlice<byte, byte>(_arg124, 1, 1, _arg125 => _arg125)),
+                    policyIds = _AllocSlice<List<byte>, _RawSlice>(structArg.policyIds, 16, 8, _arg126 => _AllocSlice<byte, byte>(_arg126, 1, 1, _arg127 => _arg127)),
+                    assetNames = _AllocSlice<List<byte>, _RawSlice>(structArg.assetNames, 16, 8, _arg128 => _AllocSlice<byte, byte>(_arg128, 1, 1, _arg129 => _arg129)),
+                    metadataLabels = _AllocSlice<ulong, ulong>(structArg.metadataLabels, 8, 8, _arg130 => _arg130),
+                    scriptHashes = _AllocSlice<List<byte>, _RawSlice>(structArg.scriptHashes, 16, 8, _arg131 => _AllocSlice<byte, byte>(_arg131, 1, 1, _arg132 => _arg132))
+                };
+            }
+            public SyncFilter Decode() {
+                return new SyncFilter {
+                    addresses = _FreeSlice<string, _RawSlice, List<string>>(this.addresses, 16, 8, _arg133 => _FreeStr(_arg133)),
+                    paymentCredentials = _FreeSlice<List<byte>, _RawSlice, List<List<byte>>>(this.paymentCredentials, 16, 8, _arg134 => _FreeSlice<byte, byte, List<byte>>(_arg134, 1, 1, _arg135 => _arg135)),
+                    stakeCredentials = _FreeSlice<List<byte>, _RawSlice, List<List<byte>>>(this.stakeCredentials, 16, 8, _arg136 => _FreeSlice<byte, byte, List<byte>>(_arg136, 1, 1, _arg137 => _arg137)),
+                    policyIds = _FreeSlice<List<byte>, _RawSlice, List<List<byte>>>(this.policyIds, 16, 8, _arg138 => _FreeSlice<byte, byte, List<byte>>(_arg138, 1, 1, _arg139 => _arg139)),
+                    assetNames = _FreeSlice<List<byte>, _RawSlice, List<List<byte>>>(this.assetNames, 16, 8, _arg140 => _FreeSlice<byte, byte, List<byte>>(_arg140, 1, 1, _arg141 => _arg141)),
+                    metadataLabels = _FreeSlice<ulong, ulong, List<ulong>>(this.metadataLabels, 8, 8, _arg142 => _arg142),
+                    scriptHashes = _FreeSlice<List<byte>, _RawSlice, List<List<byte>>>(this.scriptHashes, 16, 8, _arg143 => _FreeSlice<byte, byte, List<byte>>(_arg143, 1, 1, _arg144 => _arg144))
+                };
+            }
+        }
+        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_ack_point", CallingConvention = CallingConvention.Cdecl)]
+        private static extern byte _FnAckPoint(
+            _StructClientWrapper clientWrapper,
+            _StructPoint point
+        );
+        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_get_cursor_points", CallingConvention = CallingConvention.Cdecl)]
+        private static extern _RawSlice _FnGetCursorPoints(
+            _StructClientWrapper clientWrapper
+        );
+        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_stop_server", CallingConvention = CallingConvention.Cdecl)]
+        private static extern void _FnStopServer(
+            _StructServerWrapper serverWrapper
+        );
+        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_start_server", CallingConvention = CallingConvention.Cdecl)]
+        private static extern _RawTuple2 _FnStartServer(
+            _RawSlice address,
+            ulong networkMagic,
+            byte client,
+            byte bearer,
+            ulong capacity
+        );
+        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_get_server_tip", CallingConvention = CallingConvention.Cdecl)]
+        private static extern _StructPoint _FnGetServerTip(
+            _StructServerWrapper serverWrapper
+        );
+        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_get_server_address", CallingConvention = CallingConvention.Cdecl)]
+        private static extern _RawSlice _FnGetServerAddress(
+            _StructServerWrapper serverWrapper
+        );
+        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_server_roll_forward", CallingConvention = CallingConvention.Cdecl)]
+        private static extern byte _FnServerRollForward(
+            _StructServerWrapper serverWrapper,
+            _RawSlice blockCbor
+        );
+        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_server_roll_backward", CallingConvention = CallingConvention.Cdecl)]
+        private static extern byte _FnServerRollBackward(
+            _StructServerWrapper serverWrapper,
+            _StructPoint point
+        );
+        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_chain_sync_try_next", CallingConvention = CallingConvention.Cdecl)]
+        private static extern _StructNextResponse _FnChainSyncTryNext(
+            _StructClientWrapper clientWrapper
+        );
+        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_stop_chain_sync_stream", CallingConvention = CallingConvention.Cdecl)]
+        private static extern void _FnStopChainSyncStream(
+            _StructClientWrapper clientWrapper
+        );
+        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_start_chain_sync_stream", CallingConvention = CallingConvention.Cdecl)]
+        private static extern void _FnStartChainSyncStream(
+            _StructClientWrapper clientWrapper,
+            ulong bufferSize
+        );
+        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_set_finality_depth", CallingConvention = CallingConvention.Cdecl)]
+        private static extern void _FnSetFinalityDepth(
+            _StructClientWrapper clientWrapper,
+            ulong depth
+        );
+        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_chain_sync_next_confirmed", CallingConvention = CallingConvention.Cdecl)]
+        private static extern _StructNextResponse _FnChainSyncNextConfirmed(
+            _StructClientWrapper clientWrapper
+        );
+        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_query_versions", CallingConvention = CallingConvention.Cdecl)]
+        private static extern _RawSlice _FnQueryVersions(
+            _RawSlice pathOrServer,
+            ulong networkMagic,
+            byte client,
+            _StructClientOptions options
+        );
+        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_get_negotiated_version", CallingConvention = CallingConvention.Cdecl)]
+        private static extern _StructVersionInfo _FnGetNegotiatedVersion(
+            _StructClientWrapper clientWrapper
+        );
+        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_get_keep_alive_stats", CallingConvention = CallingConvention.Cdecl)]
+        private static extern _StructKeepAliveStats _FnGetKeepAliveStats(
+            _StructClientWrapper clientWrapper
+        );
+        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_share_peers", CallingConvention = CallingConvention.Cdecl)]
+        private static extern _RawSlice _FnSharePeers(
+            _StructClientWrapper clientWrapper,
+            byte amount
+        );
+        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_stop_sink", CallingConvention = CallingConvention.Cdecl)]
+        private static extern void _FnStopSink(
+            _StructClientWrapper clientWrapper
+        );
+        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_start_sink", CallingConvention = CallingConvention.Cdecl)]
+        private static extern byte _FnStartSink(
+            _StructClientWrapper clientWrapper,
+            _StructSinkOptions options
+        );
+        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_get_sink_status", CallingConvention = CallingConvention.Cdecl)]
+        private static extern _StructSinkStatus _FnGetSinkStatus(
+            _StructClientWrapper clientWrapper
+        );
+        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_evaluate_tx", CallingConvention = CallingConvention.Cdecl)]
+        private static extern _StructEvaluationResult _FnEvaluateTx(
+            _RawSlice txCbor,
+            _RawSlice resolvedInputs,
+            _StructProtocolParameters protocolParams,
+            _StructSlotConfig slotConfig
+        );
+        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_close_utxo_indexer", CallingConvention = CallingConvention.Cdecl)]
+        private static extern void _FnCloseUtxoIndexer(
+            _StructUtxoIndexerWrapper indexerWrapper
+        );
+        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_create_utxo_indexer", CallingConvention = CallingConvention.Cdecl)]
+        private static extern _StructUtxoIndexerWrapper _FnCreateUtxoIndexer(
+            ulong maxRollback
+        );
+        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_get_utxo_indexer_tip", CallingConvention = CallingConvention.Cdecl)]
+        private static extern _StructPoint _FnGetUtxoIndexerTip(
+            _StructUtxoIndexerWrapper indexerWrapper
+        );
+        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_get_utxo_indexer_error", CallingConvention = CallingConvention.Cdecl)]
+        private static extern _RawTuple0 _FnGetUtxoIndexerError(
+            _StructUtxoIndexerWrapper indexerWrapper
+        );
+        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_get_indexed_utxos_by_refs", CallingConvention = CallingConvention.Cdecl)]
+        private static extern _RawSlice _FnGetIndexedUtxosByRefs(
+            _StructUtxoIndexerWrapper indexerWrapper,
+            _RawSlice refs,
+            _StructPoint at
+        );
+        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_utxo_indexer_roll_forward", CallingConvention = CallingConvention.Cdecl)]
+        private static extern byte _FnUtxoIndexerRollForward(
+            _StructUtxoIndexerWrapper indexerWrapper,
+            _RawSlice blockCbor
+        );
+        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_get_indexed_utxos_by_asset", CallingConvention = CallingConvention.Cdecl)]
+        private static extern _RawSlice _FnGetIndexedUtxosByAsset(
+            _StructUtxoIndexerWrapper indexerWrapper,
+            _RawSlice policyId,
+            _RawSlice assetName,
+            _StructPoint at
+        );
+        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_utxo_indexer_roll_backward", CallingConvention = CallingConvention.Cdecl)]
+        private static extern byte _FnUtxoIndexerRollBackward(
+            _StructUtxoIndexerWrapper indexerWrapper,
+            _StructPoint point
+        );
+        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_get_indexed_utxos_by_policy", CallingConvention = CallingConvention.Cdecl)]
+        private static extern _RawSlice _FnGetIndexedUtxosByPolicy(
+            _StructUtxoIndexerWrapper indexerWrapper,
+            _RawSlice policyId,
+            _StructPoint at
+        );
+        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_get_indexed_utxos_by_address", CallingConvention = CallingConvention.Cdecl)]
+        private static extern _RawSlice _FnGetIndexedUtxosByAddress(
+            _StructUtxoIndexerWrapper indexerWrapper,
+            _RawSlice address,
+            _StructPoint at
+        );
+        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_get_indexed_utxos_by_payment_credential", CallingConvention = CallingConvention.Cdecl)]
+        private static extern _RawSlice _FnGetIndexedUtxosByPaymentCredential(
+            _StructUtxoIndexerWrapper indexerWrapper,
+            _RawSlice credential,
+            _StructPoint at
+        );
+        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_attach_utxo_indexer", CallingConvention = CallingConvention.Cdecl)]
+        private static extern void _FnAttachUtxoIndexer(
+            _StructClientWrapper clientWrapper,
+            _StructUtxoIndexerWrapper indexerWrapper
+        );
+        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_detach_utxo_indexer", CallingConvention = CallingConvention.Cdecl)]
+        private static extern void _FnDetachUtxoIndexer(
+            _StructClientWrapper clientWrapper
+        );
+        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_validate_tx", CallingConvention = CallingConvention.Cdecl)]
+        private static extern _RawSlice _FnValidateTx(
+            _RawSlice txCbor,
+            _RawSlice resolvedInputs,
+            _StructProtocolParameters protocolParams,
+            ulong networkMagic,
+            ulong slot
+        );
         [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_mainnet_magic", CallingConvention = CallingConvention.Cdecl)]
         private static extern ulong _FnMainnetMagic(
         );
-        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_testnet_magic", CallingConvention = CallingConvention.Cdecl)]
-        private static extern ulong _FnTestnetMagic(
-        );
         [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_preview_magic", CallingConvention = CallingConvention.Cdecl)]
         private static extern ulong _FnPreviewMagic(
         );
+        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_testnet_magic", CallingConvention = CallingConvention.Cdecl)]
+        private static extern ulong _FnTestnetMagic(
+        );
         [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_pre_production_magic", CallingConvention = CallingConvention.Cdecl)]
         private static extern ulong _FnPreProductionMagic(
         );
+        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_cancel", CallingConvention = CallingConvention.Cdecl)]
+        private static extern void _FnCancel(
+            _StructClientWrapper clientWrapper
+        );
         [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_connect", CallingConvention = CallingConvention.Cdecl)]
-        private static extern _StructClientWrapper _FnConnect(
+        private static extern _RawTuple3 _FnConnect(
             _RawSlice pathOrServer,
             ulong networkMagic,
             byte client
         );
-        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_get_utxo_by_address_cbor", CallingConvention = CallingConvention.Cdecl)]
-        private static extern _RawSlice _FnGetUtxoByAddressCbor(
-            _StructClientWrapper clientWrapper,
-            _RawSlice address
-        );
         [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_get_tip", CallingConvention = CallingConvention.Cdecl)]
         private static extern _StructPoint _FnGetTip(
             _StructClientWrapper clientWrapper
         );
+        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_submit_tx", CallingConvention = CallingConvention.Cdecl)]
+        private static extern _StructTxSubmissionOutcome _FnSubmitTx(
+            _RawSlice server,
+            ulong magic,
+            _RawSlice tx
+        );
+        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_disconnect", CallingConvention = CallingConvention.Cdecl)]
+        private static extern void _FnDisconnect(
+            _StructClientWrapper clientWrapper
+        );
+        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_enqueue_tx", CallingConvention = CallingConvention.Cdecl)]
+        private static extern _RawTuple0 _FnEnqueueTx(
+            _StructClientWrapper clientWrapper,
+            _RawSlice tx
+        );
+        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_submit_txs", CallingConvention = CallingConvention.Cdecl)]
+        private static extern _StructTxSubmissionOutcome _FnSubmitTxs(
+            _RawSlice server,
+            ulong magic,
+            _RawSlice txs
+        );
+        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_fetch_block", CallingConvention = CallingConvention.Cdecl)]
+        private static extern _RawTuple0 _FnFetchBlock(
+            _StructClientWrapper clientWrapper,
+            _StructPoint point
+        );
         [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_find_intersect", CallingConvention = CallingConvention.Cdecl)]
-        private static extern _RawTuple0 _FnFindIntersect(
+        private static extern _RawTuple1 _FnFindIntersect(
             _StructClientWrapper clientWrapper,
             _StructPoint knownPoint
         );
@@ -211,74 +1640,269 @@ namespace PallasDotnetRs
         private static extern _StructNextResponse _FnChainSyncNext(
             _StructClientWrapper clientWrapper
         );
-        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_disconnect", CallingConvention = CallingConvention.Cdecl)]
-        private static extern void _FnDisconnect(
+        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_stop_tx_submission", CallingConvention = CallingConvention.Cdecl)]
+        private static extern void _FnStopTxSubmission(
             _StructClientWrapper clientWrapper
         );
-        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_fetch_block", CallingConvention = CallingConvention.Cdecl)]
-        private static extern _RawTuple1 _FnFetchBlock(
+        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_start_tx_submission", CallingConvention = CallingConvention.Cdecl)]
+        private static extern byte _FnStartTxSubmission(
+            _StructClientWrapper clientWrapper
+        );
+        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_connect_with_options", CallingConvention = CallingConvention.Cdecl)]
+        private static extern _RawTuple3 _FnConnectWithOptions(
+            _RawSlice pathOrServer,
+            ulong networkMagic,
+            byte client,
+            _StructClientOptions options
+        );
+        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_chain_sync_next_batch", CallingConvention = CallingConvention.Cdecl)]
+        private static extern _StructNextBatch _FnChainSyncNextBatch(
             _StructClientWrapper clientWrapper,
-            _StructPoint point
+            ulong maxCount,
+            ulong maxWaitMs
         );
-        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_submit_tx", CallingConvention = CallingConvention.Cdecl)]
-        private static extern _RawSlice _FnSubmitTx(
-            _RawSlice server,
-            ulong magic,
-            _RawSlice tx
+        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_get_tx_submission_status", CallingConvention = CallingConvention.Cdecl)]
+        private static extern _StructTxSubmissionOutcome _FnGetTxSubmissionStatus(
+            _StructClientWrapper clientWrapper
+        );
+        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_get_utxo_by_address_cbor", CallingConvention = CallingConvention.Cdecl)]
+        private static extern _RawSlice _FnGetUtxoByAddressCbor(
+            _StructClientWrapper clientWrapper,
+            _RawSlice address
         );
         [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_address_bytes_to_bech32", CallingConvention = CallingConvention.Cdecl)]
         private static extern _RawSlice _FnAddressBytesToBech32(
             _RawSlice addressBytes
         );
+        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_chain_sync_next_events", CallingConvention = CallingConvention.Cdecl)]
+        private static extern _StructChainEvents _FnChainSyncNextEvents(
+            _StructClientWrapper clientWrapper
+        );
+        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_immutable_db_next", CallingConvention = CallingConvention.Cdecl)]
+        private static extern _StructNextResponse _FnImmutableDbNext(
+            _StructImmutableDbWrapper dbWrapper
+        );
+        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_open_immutable_db", CallingConvention = CallingConvention.Cdecl)]
+        private static extern _RawTuple4 _FnOpenImmutableDb(
+            _RawSlice path,
+            _StructPoint point
+        );
+        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_close_immutable_db", CallingConvention = CallingConvention.Cdecl)]
+        private static extern void _FnCloseImmutableDb(
+            _StructImmutableDbWrapper dbWrapper
+        );
+        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_get_immutable_db_tip", CallingConvention = CallingConvention.Cdecl)]
+        private static extern _StructPoint _FnGetImmutableDbTip(
+            _RawSlice path
+        );
+        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_stop_acked_stream", CallingConvention = CallingConvention.Cdecl)]
+        private static extern void _FnStopAckedStream(
+            _StructClientWrapper clientWrapper
+        );
+        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_start_acked_stream", CallingConvention = CallingConvention.Cdecl)]
+        private static extern byte _FnStartAckedStream(
+            _StructClientWrapper clientWrapper,
+            ulong window
+        );
+        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_acked_stream_try_next", CallingConvention = CallingConvention.Cdecl)]
+        private static extern _StructAckedResponse _FnAckedStreamTryNext(
+            _StructClientWrapper clientWrapper
+        );
+        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_init_runtime", CallingConvention = CallingConvention.Cdecl)]
+        private static extern byte _FnInitRuntime(
+            uint workerThreads,
+            _RawSlice threadNamePrefix,
+            byte enableCurrentThread
+        );
+        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_shutdown_runtime", CallingConvention = CallingConvention.Cdecl)]
+        private static extern void _FnShutdownRuntime(
+            ulong timeoutMs
+        );
+        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_connect_peer_set", CallingConvention = CallingConvention.Cdecl)]
+        private static extern _RawTuple3 _FnConnectPeerSet(
+            _RawSlice servers,
+            ulong networkMagic,
+            _StructClientOptions options,
+            byte fetchFromFastest
+        );
+        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_get_peer_set_status", CallingConvention = CallingConvention.Cdecl)]
+        private static extern _RawSlice _FnGetPeerSetStatus(
+            _StructClientWrapper clientWrapper
+        );
+        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_get_block", CallingConvention = CallingConvention.Cdecl)]
+        private static extern _RawTuple0 _FnGetBlock(
+            _StructBlockStoreWrapper storeWrapper,
+            _StructPoint point
+        );
+        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_put_block", CallingConvention = CallingConvention.Cdecl)]
+        private static extern byte _FnPutBlock(
+            _StructBlockStoreWrapper storeWrapper,
+            _RawSlice blockCbor
+        );
+        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_rollback_to", CallingConvention = CallingConvention.Cdecl)]
+        private static extern byte _FnRollbackTo(
+            _StructBlockStoreWrapper storeWrapper,
+            _StructPoint point
+        );
+        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_get_block_range", CallingConvention = CallingConvention.Cdecl)]
+        private static extern _RawSlice _FnGetBlockRange(
+            _StructBlockStoreWrapper storeWrapper,
+            _StructPoint from,
+            _StructPoint to
+        );
+        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_get_blocks_after", CallingConvention = CallingConvention.Cdecl)]
+        private static extern _StructBlockPage _FnGetBlocksAfter(
+            _StructBlockStoreWrapper storeWrapper,
+            _StructPoint point,
+            ulong maxCount
+        );
+        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_open_block_store", CallingConvention = CallingConvention.Cdecl)]
+        private static extern _RawTuple5 _FnOpenBlockStore(
+            _RawSlice path
+        );
+        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_close_block_store", CallingConvention = CallingConvention.Cdecl)]
+        private static extern void _FnCloseBlockStore(
+            _StructBlockStoreWrapper storeWrapper
+        );
+        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_get_block_by_number", CallingConvention = CallingConvention.Cdecl)]
+        private static extern _RawTuple0 _FnGetBlockByNumber(
+            _StructBlockStoreWrapper storeWrapper,
+            ulong number
+        );
+        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_get_block_store_tip", CallingConvention = CallingConvention.Cdecl)]
+        private static extern _StructPoint _FnGetBlockStoreTip(
+            _StructBlockStoreWrapper storeWrapper
+        );
+        [DllImport("pallas_dotnet_rs", EntryPoint = "rnet_export_set_chain_sync_filter", CallingConvention = CallingConvention.Cdecl)]
+        private static extern byte _FnSetChainSyncFilter(
+            _StructClientWrapper clientWrapper,
+            _StructSyncFilter filter
+        );
         [StructLayout(LayoutKind.Sequential)]
         private struct _RawTuple0 {
-            public _StructPoint elem0;
+            public _RawSlice elem0;
             public byte elem1;
         }
-        private static _RawTuple0 _EncodeOption<T>(T arg, Func<T, _StructPoint> converter) {
+        private static _RawTuple0 _EncodeOption<T>(T arg, Func<T, _RawSlice> converter) {
             if (arg != null) {
                 return new _RawTuple0 { elem0 = converter(arg), elem1 = 1 };
             } else {
-                return new _RawTuple0 { elem0 = default(_StructPoint), elem1 = 0 };
+                return new _RawTuple0 { elem0 = default(_RawSlice), elem1 = 0 };
             }
         }
-        private static T _DecodeOption<T>(_RawTuple0 arg, Func<_StructPoint, T> converter) {
+        private static T _DecodeOption<T>(_RawTuple0 arg, Func<_RawSlice, T> converter) {
             if (arg.elem1 != 0) {
                 return converter(arg.elem0);
             } else {
                 return default(T);
             }
         }
+        private static _RawTuple0 _EncodeResult(Action f) {
+            try {
+                f();
+                return new _RawTuple0 { elem0 = default(_RawSlice), elem1 = 1 };
+            } catch (Exception e) {
+                return new _RawTuple0 { elem0 = _AllocStr(e.Message), elem1 = 0 };
+            }
+        }
+        private static void _DecodeResult(_RawTuple0 arg) {
+            if (arg.elem1 == 0) {
+                throw new RustException(_FreeStr(arg.elem0));
+            }
+        }
         [StructLayout(LayoutKind.Sequential)]
         private struct _RawTuple1 {
-            public _RawSlice elem0;
+            public _StructPoint elem0;
             public byte elem1;
         }
-        private static _RawTuple1 _EncodeOption<T>(T arg, Func<T, _RawSlice> converter) {
+        private static _RawTuple1 _EncodeOption<T>(T arg, Func<T, _StructPoint> converter) {
             if (arg != null) {
                 return new _RawTuple1 { elem0 = converter(arg), elem1 = 1 };
             } else {
-                return new _RawTuple1 { elem0 = default(_RawSlice), elem1 = 0 };
+                return new _RawTuple1 { elem0 = default(_StructPoint), elem1 = 0 };
             }
         }
-        private static T _DecodeOption<T>(_RawTuple1 arg, Func<_RawSlice, T> converter) {
+        private static T _DecodeOption<T>(_RawTuple1 arg, Func<_StructPoint, T> converter) {
             if (arg.elem1 != 0) {
                 return converter(arg.elem0);
             } else {
                 return default(T);
             }
         }
-        private static _RawTuple1 _EncodeResult(Action f) {
-            try {
-                f();
-                return new _RawTuple1 { elem0 = default(_RawSlice), elem1 = 1 };
-            } catch (Exception e) {
-                return new _RawTuple1 { elem0 = _AllocStr(e.Message), elem1 = 0 };
+        [StructLayout(LayoutKind.Sequential)]
+        private struct _RawTuple2 {
+            public _StructServerWrapper elem0;
+            public byte elem1;
+        }
+        private static _RawTuple2 _EncodeOption<T>(T arg, Func<T, _StructServerWrapper> converter) {
+            if (arg != null) {
+                return new _RawTuple2 { elem0 = converter(arg), elem1 = 1 };
+            } else {
+                return new _RawTuple2 { elem0 = default(_StructServerWrapper), elem1 = 0 };
             }
         }
-        private static void _DecodeResult(_RawTuple1 arg) {
-            if (arg.elem1 == 0) {
-                throw new RustException(_FreeStr(arg.elem0));
+        private static T _DecodeOption<T>(_RawTuple2 arg, Func<_StructServerWrapper, T> converter) {
+            if (arg.elem1 != 0) {
+                return converter(arg.elem0);
+            } else {
+                return default(T);
+            }
+        }
+        [StructLayout(LayoutKind.Sequential)]
+        private struct _RawTuple3 {
+            public _StructClientWrapper elem0;
+            public byte elem1;
+        }
+        private static _RawTuple3 _EncodeOption<T>(T arg, Func<T, _StructClientWrapper> converter) {
+            if (arg != null) {
+                return new _RawTuple3 { elem0 = converter(arg), elem1 = 1 };
+            } else {
+                return new _RawTuple3 { elem0 = default(_StructClientWrapper), elem1 = 0 };
+            }
+        }
+        private static T _DecodeOption<T>(_RawTuple3 arg, Func<_StructClientWrapper, T> converter) {
+            if (arg.elem1 != 0) {
+                return converter(arg.elem0);
+            } else {
+                return default(T);
+            }
+        }
+        [StructLayout(LayoutKind.Sequential)]
+        private struct _RawTuple4 {
+            public _StructImmutableDbWrapper elem0;
+            public byte elem1;
+        }
+        private static _RawTuple4 _EncodeOption<T>(T arg, Func<T, _StructImmutableDbWrapper> converter) {
+            if (arg != null) {
+                return new _RawTuple4 { elem0 = converter(arg), elem1 = 1 };
+            } else {
+                return new _RawTuple4 { elem0 = default(_StructImmutableDbWrapper), elem1 = 0 };
+            }
+        }
+        private static T _DecodeOption<T>(_RawTuple4 arg, Func<_StructImmutableDbWrapper, T> converter) {
+            if (arg.elem1 != 0) {
+                return converter(arg.elem0);
+            } else {
+                return default(T);
+            }
+        }
+        [StructLayout(LayoutKind.Sequential)]
+        private struct _RawTuple5 {
+            public _StructBlockStoreWrapper elem0;
+            public byte elem1;
+        }
+        private static _RawTuple5 _EncodeOption<T>(T arg, Func<T, _StructBlockStoreWrapper> converter) {
+            if (arg != null) {
+                return new _RawTuple5 { elem0 = converter(arg), elem1 = 1 };
+            } else {
+                return new _RawTuple5 { elem0 = default(_StructBlockStoreWrapper), elem1 = 0 };
+            }
+        }
+        private static T _DecodeOption<T>(_RawTuple5 arg, Func<_StructBlockStoreWrapper, T> converter) {
+            if (arg.elem1 != 0) {
+                return converter(arg.elem0);
+            } else {
+                return default(T);
             }
         }
 
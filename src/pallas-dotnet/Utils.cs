@@ -6,4 +6,103 @@ public class Utils
 {
     public static Point MapPallasPoint(PallasDotnetRs.PallasDotnetRs.Point rsPoint)
         => new(rsPoint.slot, Convert.ToHexString(rsPoint.hash.ToArray()));
+
+    public static NextResponse MapNextResponse(PallasDotnetRs.PallasDotnetRs.NextResponse rsResponse)
+        => (NextResponseAction)rsResponse.action switch
+        {
+            NextResponseAction.RollForward => new(NextResponseAction.RollForward, MapPallasPoint(rsResponse.tip), [.. rsResponse.blockCbor]),
+            NextResponseAction.RollBack => new(NextResponseAction.RollBack, MapPallasPoint(rsResponse.tip), default!),
+            var action => new(action, default!, default!)
+        };
+
+    // Slices each roll forward's block out of the batch's shared buffer
+    public static List<NextResponse> MapNextBatch(PallasDotnetRs.PallasDotnetRs.NextBatch rsBatch)
+    {
+        byte[] blocks = [.. rsBatch.blocks];
+
+        return rsBatch.responses
+            .Select((rsResponse, i) => (NextResponseAction)rsResponse.action switch
+            {
+                NextResponseAction.RollForward => new NextResponse(
+                    NextResponseAction.RollForward,
+                    MapPallasPoint(rsResponse.tip),
+                    blocks[(int)rsBatch.offsets[i]..(int)rsBatch.offsets[i + 1]]),
+                _ => MapNextResponse(rsResponse)
+            })
+            .ToList();
+    }
+
+    public static PallasDotnetRs.PallasDotnetRs.ClientOptions MapClientOptions(ClientOptions? options)
+    {
+        options ??= new ClientOptions();
+
+        return new()
+        {
+            connectTimeoutMs = (ulong)options.ConnectTimeout.TotalMilliseconds,
+            handshakeTimeoutMs = (ulong)options.HandshakeTimeout.TotalMilliseconds,
+            requestTimeoutMs = (ulong)options.RequestTimeout.TotalMilliseconds,
+            idleTimeoutMs = (ulong)options.IdleTimeout.TotalMilliseconds,
+            keepaliveIntervalMs = (ulong)options.KeepAliveInterval.TotalMilliseconds,
+            maxReconnectAttempts = options.MaxReconnectAttempts,
+            reconnectBackoffMs = (ulong)options.ReconnectBackoff.TotalMilliseconds,
+            bearer = (byte)options.Bearer,
+            handshake = new()
+            {
+                versions = options.Handshake?.Versions ?? [],
+                query = options.Handshake?.Query ?? false,
+                initiatorAndResponder = options.Handshake?.InitiatorAndResponder ?? false,
+                peerSharing = options.Handshake?.PeerSharing ?? false
+            },
+            cursorPath = options.CursorPath ?? "",
+            cursorPoints = options.CursorPoints
+        };
+    }
+
+    public static PallasDotnetRs.PallasDotnetRs.SyncFilter MapSyncFilter(SyncFilter filter)
+    {
+        static List<List<byte>> FromHex(List<string>? hashes)
+            => hashes?.Select(hash => new List<byte>(Convert.FromHexString(hash))).ToList() ?? [];
+
+        return new()
+        {
+            addresses = filter.Addresses ?? [],
+            paymentCredentials = FromHex(filter.PaymentCredentials),
+            stakeCredentials = FromHex(filter.StakeCredentials),
+            policyIds = FromHex(filter.PolicyIds),
+            assetNames = filter.AssetNames?.Select(name => new List<byte>(name)).ToList() ?? [],
+            metadataLabels = filter.MetadataLabels ?? [],
+            scriptHashes = FromHex(filter.ScriptHashes)
+        };
+    }
+
+    public static PallasDotnetRs.PallasDotnetRs.SinkOptions MapSinkOptions(SinkOptions options)
+        => new()
+        {
+            directory = options.Directory,
+            format = (byte)options.Format,
+            maxFileBytes = options.MaxFileBytes
+        };
+
+    public static ChainEvent MapChainEvent(PallasDotnetRs.PallasDotnetRs.ChainEvent rsEvent)
+    {
+        static string? Hex(List<byte> bytes) => bytes.Count == 0 ? null : Convert.ToHexString([.. bytes]);
+
+        return new(
+            (ChainEventKind)rsEvent.kind,
+            rsEvent.undo,
+            new(rsEvent.slot, Convert.ToHexString([.. rsEvent.blockHash])),
+            rsEvent.blockNumber,
+            Hex(rsEvent.txHash),
+            rsEvent.txIndex,
+            Hex(rsEvent.outputTxHash),
+            rsEvent.index,
+            [.. rsEvent.cbor],
+            Hex(rsEvent.policyId),
+            (ChainEventKind)rsEvent.kind == ChainEventKind.Mint ? [.. rsEvent.assetName] : null,
+            rsEvent.quantity
+        );
+    }
+
+    public static ProtocolVersion MapVersionInfo(PallasDotnetRs.PallasDotnetRs.VersionInfo rsVersion)
+        => new(rsVersion.version, rsVersion.networkMagic, rsVersion.query, rsVersion.initiatorOnlyDiffusionMode, rsVersion.peerSharing);
 }
\ No newline at end of file
@@ -0,0 +1,81 @@
+using PallasDotnet.Models;
+
+namespace PallasDotnet;
+
+// In-memory UTxO set built from the blocks it is fed, either pushed with RollForward/RollBackward
+// or followed by attaching it to a client. Outputs created before the first block are unknown,
+// sync from origin for a complete set. Queries take an optional point of the rollback window.
+public class UtxoIndexer
+{
+    private PallasDotnetRs.PallasDotnetRs.UtxoIndexerWrapper? _indexer;
+
+    internal PallasDotnetRs.PallasDotnetRs.UtxoIndexerWrapper Wrapper
+        => _indexer ?? throw new Exception("Indexer not created");
+
+    public bool IsCreated => _indexer != null;
+
+    // Rollbacks deeper than `maxRollback` blocks are refused, 0 keeps 2160 blocks
+    public void Create(ulong maxRollback = 0)
+    {
+        _indexer = PallasDotnetRs.PallasDotnetRs.CreateUtxoIndexer(maxRollback);
+    }
+
+    public Point Tip => Utils.MapPallasPoint(PallasDotnetRs.PallasDotnetRs.GetUtxoIndexerTip(Wrapper));
+
+    // Why a block or rollback of the client the indexer is attached to could not be applied, null
+    // when all were. Cleared by the next rollback.
+    public string? Error => PallasDotnetRs.PallasDotnetRs.GetUtxoIndexerError(Wrapper);
+
+    // Returns false when the block cannot be decoded or does not extend the tip
+    public bool RollForward(byte[] blockCbor)
+        => PallasDotnetRs.PallasDotnetRs.UtxoIndexerRollForward(Wrapper, blockCbor);
+
+    // Returns false when the point is past the rollback window. An empty hash rolls back to origin.
+    public bool RollBackward(Point point)
+        => PallasDotnetRs.PallasDotnetRs.UtxoIndexerRollBackward(Wrapper, MapPoint(point));
+
+    public List<Utxo> GetUtxosByAddress(string address, Point? at = null)
+        => MapUtxos(PallasDotnetRs.PallasDotnetRs.GetIndexedUtxosByAddress(Wrapper, address, MapPoint(at)));
+
+    // Key or script hash of the payment part, in hex
+    public List<Utxo> GetUtxosByPaymentCredential(string credential, Point? at = null)
+        => MapUtxos(PallasDotnetRs.PallasDotnetRs.GetIndexedUtxosByPaymentCredential(Wrapper, Convert.FromHexString(credential), MapPoint(at)));
+
+    public List<Utxo> GetUtxosByPolicy(string policyId, Point? at = null)
+        => MapUtxos(PallasDotnetRs.PallasDotnetRs.GetIndexedUtxosByPolicy(Wrapper, Convert.FromHexString(policyId), MapPoint(at)));
+
+    public List<Utxo> GetUtxosByAsset(string policyId, byte[] assetName, Point? at = null)
+        => MapUtxos(PallasDotnetRs.PallasDotnetRs.GetIndexedUtxosByAsset(Wrapper, Convert.FromHexString(policyId), assetName, MapPoint(at)));
+
+    // Only the unspent ones are returned
+    public List<Utxo> GetUtxosByRefs(IEnumerable<(string TxHash, ulong Index)> refs, Point? at = null)
+    {
+        var rsRefs = refs.Select(utxoRef => new PallasDotnetRs.PallasDotnetRs.UtxoRef
+        {
+            txHash = new List<byte>(Convert.FromHexString(utxoRef.TxHash)),
+            index = utxoRef.Index
+        }).ToList();
+
+        return MapUtxos(PallasDotnetRs.PallasDotnetRs.GetIndexedUtxosByRefs(Wrapper, rsRefs, MapPoint(at)));
+    }
+
+    public void Close()
+    {
+        if (_indexer is null)
+        {
+            return;
+        }
+
+        PallasDotnetRs.PallasDotnetRs.CloseUtxoIndexer(_indexer.Value);
+        _indexer = null;
+    }
+
+    private static PallasDotnetRs.PallasDotnetRs.Point MapPoint(Point? point) => new()
+    {
+        slot = point?.Slot ?? 0,
+        hash = point is null ? [] : new List<byte>(Convert.FromHexString(point.Hash))
+    };
+
+    private static List<Utxo> MapUtxos(List<PallasDotnetRs.PallasDotnetRs.ResolvedInput> utxos)
+        => utxos.Select(utxo => new Utxo(Convert.ToHexString([.. utxo.txHash]), utxo.index, [.. utxo.outputCbor])).ToList();
+}